@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::utils::SimplePoint;
+
+/// Point présent dans les deux exports mais déplacé d'au plus la tolérance.
+#[derive(Serialize, Debug, Clone)]
+pub struct MovedPoint {
+    pub from: SimplePoint,
+    pub to: SimplePoint,
+}
+
+/// Différence entre un export de référence et un nouvel export.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ExportDiff {
+    /// Points du nouvel export sans correspondant dans la référence
+    pub added: Vec<SimplePoint>,
+    /// Points de la référence sans correspondant dans le nouvel export
+    pub removed: Vec<SimplePoint>,
+    /// Points appariés mais dont la position a changé
+    pub moved: Vec<MovedPoint>,
+    /// Nombre de points identiques dans les deux exports
+    pub unchanged: usize,
+}
+
+/// Lit les coordonnées X/Y des lignes d'un fichier d'export, en ignorant l'en-tête.
+/// Les coordonnées écrites avec une virgule décimale sont acceptées.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier d'export
+///
+/// # Retours
+/// Les points de l'export, dans l'ordre du fichier
+pub fn read_export_points(file_path: &str) -> Result<Vec<SimplePoint>, String> {
+    let content = std::fs::read_to_string(file_path)
+        .map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+
+    content
+        .lines()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let mut fields = line.split('\t');
+            let mut coordinate = || {
+                fields
+                    .next()
+                    .and_then(|v| v.trim().replace(',', ".").parse::<f64>().ok())
+            };
+            let x = coordinate();
+            let y = coordinate();
+            match (x, y) {
+                (Some(x), Some(y)) => Ok(SimplePoint { x, y }),
+                _ => Err(format!(
+                    "Invalid coordinates on line {} of {}",
+                    index + 1,
+                    file_path
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Compare deux ensembles de points. Chaque point de `new` est apparié au point
+/// non encore apparié de `baseline` le plus proche à moins de `tolerance`.
+///
+/// # Arguments
+/// * `baseline` - Points de référence
+/// * `new` - Points à comparer
+/// * `tolerance` - Distance maximale pour apparier deux points
+///
+/// # Retours
+/// Les points ajoutés, supprimés et déplacés
+pub fn diff_points(baseline: &[SimplePoint], new: &[SimplePoint], tolerance: f64) -> ExportDiff {
+    let cell_of = |point: &SimplePoint| {
+        (
+            (point.x / tolerance).floor() as i64,
+            (point.y / tolerance).floor() as i64,
+        )
+    };
+
+    let mut index: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, point) in baseline.iter().enumerate() {
+        index.entry(cell_of(point)).or_default().push(i);
+    }
+
+    let mut matched = vec![false; baseline.len()];
+    let mut diff = ExportDiff::default();
+
+    for point in new {
+        let (cell_x, cell_y) = cell_of(point);
+        let mut best: Option<(usize, f64)> = None;
+
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let Some(candidates) = index.get(&(cell_x + dx, cell_y + dy)) else {
+                    continue;
+                };
+                for &candidate in candidates {
+                    if matched[candidate] {
+                        continue;
+                    }
+                    let other = &baseline[candidate];
+                    let distance =
+                        ((point.x - other.x).powi(2) + (point.y - other.y).powi(2)).sqrt();
+                    if distance <= tolerance && best.is_none_or(|(_, d)| distance < d) {
+                        best = Some((candidate, distance));
+                    }
+                }
+            }
+        }
+
+        match best {
+            Some((candidate, distance)) => {
+                matched[candidate] = true;
+                if distance == 0.0 {
+                    diff.unchanged += 1;
+                } else {
+                    diff.moved.push(MovedPoint {
+                        from: baseline[candidate].clone(),
+                        to: point.clone(),
+                    });
+                }
+            }
+            None => diff.added.push(point.clone()),
+        }
+    }
+
+    diff.removed = baseline
+        .iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .map(|(point, _)| point.clone())
+        .collect();
+
+    diff
+}
+
+/// Commande Tauri pour comparer un export à un export de référence.
+///
+/// # Arguments
+/// * `path_a` - Chemin de l'export de référence
+/// * `path_b` - Chemin de l'export à comparer
+/// * `tolerance` - Distance maximale pour considérer qu'un point a été déplacé plutôt que remplacé
+///
+/// # Retours
+/// La différence entre les deux exports ou une erreur
+#[tauri::command]
+pub fn diff_exports(path_a: &str, path_b: &str, tolerance: f64) -> Result<ExportDiff, String> {
+    if tolerance.is_nan() || tolerance <= 0.0 {
+        return Err("Tolerance must be positive.".to_string());
+    }
+    let baseline = read_export_points(path_a)?;
+    let new = read_export_points(path_b)?;
+    Ok(diff_points(&baseline, &new, tolerance))
+}
@@ -1,66 +1,40 @@
+pub mod diff;
 pub mod models;
+pub mod risk;
 pub mod sampling;
+pub mod updates;
 pub mod utils;
 
 pub use models::vegetations::{
-    get_default_vegetation_params, get_user_vegetation_params, set_user_vegetation_params,
+    get_default_vegetation_params, get_user_vegetation_params, get_vegetation_types,
+    set_user_vegetation_params,
 };
 
-pub use models::settings::get_export_path;
-
-use tauri::AppHandle;
-use tauri_plugin_updater::UpdaterExt;
-pub use utils::{export_results, get_preview_data, parse_csv_file};
-
-pub use sampling::fill_polygon;
-
-use crate::models::processing::{VegetationProcessingState, get_vegetation_progress};
-
-async fn check_for_updates(app: AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    let updater = app.updater_builder().build()?;
-    let timeout_duration = std::time::Duration::from_secs(15);
-
-    match tokio::time::timeout(timeout_duration, updater.check()).await {
-        Ok(Ok(Some(update))) => {
-            println!(
-                "Update available: {} -> {}",
-                update.current_version, update.version
-            );
-            println!("Downloading update...");
+pub use models::export::{apply_export_preset, list_export_presets, save_export_preset};
+pub use models::settings::{
+    create_profile, delete_profile, export_settings_bundle, flush_settings,
+    get_coordinate_precision, get_export_path, get_output_template, import_settings_bundle,
+    list_profiles, rebuild_default_params, set_coordinate_precision, set_output_template,
+    switch_profile,
+};
 
-            let download_timeout = std::time::Duration::from_secs(300);
+pub use utils::{
+    export_all_types, export_results, export_single_wkt, get_output_columns, get_preview_data,
+    parse_csv_file, parse_csv_file_lenient, parse_csv_overrides, parse_geojson_file,
+    parse_geojson_file_lenient, parse_geometry_file, parse_input_file,
+};
 
-            match tokio::time::timeout(
-                download_timeout,
-                update.download_and_install(
-                    |chunk_length, content_length| {
-                        if let Some(total) = content_length {
-                            let progress = (chunk_length as f64 / total as f64) * 100.0;
-                            println!("Download progress: {:.1}%", progress);
-                        } else {
-                            println!("Downloaded: {} bytes", chunk_length);
-                        }
-                    },
-                    || println!("Download finished"),
-                ),
-            )
-            .await
-            {
-                Ok(Ok(_)) => {
-                    println!("Update installed successfully\nRestarting application...");
-                    app.restart();
-                }
-                Ok(Err(e)) => eprintln!("Failed to download/install update: {}", e),
-                Err(_) => eprintln!("Update download timed out after 5 minutes"),
-            }
-        }
-        Ok(Ok(None)) => println!("No updates available - you're on the latest version"),
-        Ok(Err(e)) => eprintln!("Update check failed: {}", e),
-        Err(_) => eprintln!("Update check timed out after 15 seconds - continuing startup"),
-    }
+pub use diff::diff_exports;
+pub use risk::risk_report;
+pub use sampling::{
+    estimate_total_points, fill_polygon, fill_polygon_with_stats, get_sampling_stats,
+    resolve_effective_min_distance, suggest_point,
+};
+pub use updates::{check_for_updates_now, install_update};
 
-    Ok(())
-}
+use crate::models::processing::{
+    VegetationProcessingState, cancel_vegetation_export, get_vegetation_progress,
+};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -72,12 +46,47 @@ pub fn run() {
             get_default_vegetation_params,
             get_user_vegetation_params,
             set_user_vegetation_params,
+            get_vegetation_types,
             get_vegetation_progress,
+            cancel_vegetation_export,
             fill_polygon,
+            fill_polygon_with_stats,
+            get_sampling_stats,
+            suggest_point,
+            estimate_total_points,
+            resolve_effective_min_distance,
             parse_csv_file,
+            parse_csv_file_lenient,
+            parse_csv_overrides,
+            parse_geojson_file,
+            parse_geojson_file_lenient,
+            parse_geometry_file,
+            parse_input_file,
             get_preview_data,
             export_results,
-            get_export_path
+            export_all_types,
+            export_single_wkt,
+            get_output_columns,
+            get_export_path,
+            get_coordinate_precision,
+            set_coordinate_precision,
+            get_output_template,
+            set_output_template,
+            create_profile,
+            switch_profile,
+            list_profiles,
+            delete_profile,
+            export_settings_bundle,
+            import_settings_bundle,
+            flush_settings,
+            rebuild_default_params,
+            diff_exports,
+            save_export_preset,
+            list_export_presets,
+            apply_export_preset,
+            risk_report,
+            check_for_updates_now,
+            install_update
         ])
         .setup(|app| {
             if let Err(e) = models::settings::Settings::init(app.handle().clone()) {
@@ -85,11 +94,7 @@ pub fn run() {
                 std::process::exit(1);
             }
             let app_handle = app.handle().clone();
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) = check_for_updates(app_handle).await {
-                    eprintln!("Error during update check: {}", e);
-                }
-            });
+            tauri::async_runtime::spawn(updates::check_for_updates(app_handle));
 
             Ok(())
         })
@@ -0,0 +1,353 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::settings::Settings;
+use crate::models::vegetations::VegetationParams;
+use crate::utils::{OUTPUT_DEFAULT_VALUES, OUTPUT_HEADER};
+
+/// Format du fichier produit par l'export.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputFormat {
+    /// Fichier texte tabulé au format SDIS, avec l'en-tête complet
+    #[default]
+    SdisTxt,
+    /// FeatureCollection GeoJSON dont chaque point est une entité `Point`,
+    /// avec les propriétés `type` et `z`
+    GeoJson,
+    /// Une ligne tabulée par polygone : tous ses points en une géométrie WKT `MULTIPOINT`,
+    /// suivie de la valeur de type
+    WktMultipoint,
+    /// Fichier CSV minimal séparé par des virgules, d'en-tête `X,Y,type,z`,
+    /// pour l'import dans un tableur
+    Csv,
+}
+
+impl OutputFormat {
+    /// Extension du fichier produit, sans le point.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::SdisTxt => "txt",
+            OutputFormat::GeoJson => "geojson",
+            OutputFormat::WktMultipoint | OutputFormat::Csv => "csv",
+        }
+    }
+
+    /// Séparateur des champs d'une ligne, ou None si les coordonnées ne sont pas écrites
+    /// sous forme de texte libre.
+    pub fn field_separator(&self) -> Option<char> {
+        match self {
+            OutputFormat::SdisTxt | OutputFormat::WktMultipoint => Some('\t'),
+            OutputFormat::Csv => Some(','),
+            OutputFormat::GeoJson => None,
+        }
+    }
+
+    /// Texte écrit entre deux points successifs.
+    pub fn row_separator(&self) -> &'static str {
+        match self {
+            OutputFormat::SdisTxt | OutputFormat::WktMultipoint | OutputFormat::Csv => "",
+            OutputFormat::GeoJson => ",",
+        }
+    }
+}
+
+/// Fin de ligne utilisée dans le fichier produit.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Origine de la valeur écrite dans la colonne `z` de chaque point.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ZSource {
+    /// Valeur constante de la colonne, ou celle de la colonne `z` du fichier source
+    Constant,
+    /// Altitude moyenne de la géométrie `POLYGON Z` du polygone, la valeur constante
+    /// pour une géométrie en deux dimensions
+    #[default]
+    PolygonMean,
+    /// Colonne `z` laissée vide
+    None,
+}
+
+/// Disposition des colonnes du format `SdisTxt` : noms des colonnes de l'en-tête et
+/// modèle de la ligne d'un point, dont les champs sont séparés par des tabulations.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OutputTemplate {
+    /// Noms des colonnes, dans l'ordre de l'en-tête
+    pub columns: Vec<String>,
+    /// Ligne d'un point, où `{x}`, `{y}`, `{z}` et `{type}` sont remplacés par
+    /// les valeurs du point
+    pub row: String,
+}
+
+/// Marqueurs des champs tirés du point, que les attributs du polygone ne remplacent pas.
+const POINT_PLACEHOLDERS: [&str; 3] = ["{x}", "{y}", "{type}"];
+
+impl Default for OutputTemplate {
+    /// Modèle intégré : l'en-tête `OUTPUT_HEADER` et ses valeurs par défaut.
+    fn default() -> Self {
+        let columns: Vec<String> = OUTPUT_HEADER.split('\t').map(str::to_string).collect();
+        let mut row = "       {x}\t       {y}".to_string();
+        for column in &columns[2..] {
+            row.push('\t');
+            row.push_str(match column.as_str() {
+                "z" => "{z}",
+                "type" => "{type}",
+                column => OUTPUT_DEFAULT_VALUES
+                    .iter()
+                    .find(|(name, _)| *name == column)
+                    .map_or("", |(_, value)| value),
+            });
+        }
+        OutputTemplate { columns, row }
+    }
+}
+
+impl OutputTemplate {
+    /// En-tête du fichier, colonnes séparées par des tabulations.
+    pub fn header(&self) -> String {
+        self.columns.join("\t")
+    }
+
+    /// Vérifie que le modèle produit une ligne par point, avec ses coordonnées.
+    ///
+    /// # Retours
+    /// Ok(()) ou la raison pour laquelle le modèle est inutilisable
+    pub fn validate(&self) -> Result<(), String> {
+        if self.columns.is_empty() {
+            return Err("Output template has no columns".to_string());
+        }
+        if let Some(column) = self
+            .columns
+            .iter()
+            .find(|column| column.contains(['\t', '\n', '\r']))
+        {
+            return Err(format!(
+                "Output template column {:?} contains a tab or a line break",
+                column
+            ));
+        }
+        if self.row.contains(['\n', '\r']) {
+            return Err("Output template row contains a line break".to_string());
+        }
+        if !self.row.contains("{x}") || !self.row.contains("{y}") {
+            return Err("Output template row must contain {x} and {y}".to_string());
+        }
+        Ok(())
+    }
+
+    /// Met en forme la ligne d'un point, sans fin de ligne. Si chaque champ du modèle
+    /// correspond à une colonne, l'attribut du polygone de même nom que la colonne
+    /// remplace le champ, sauf pour les champs contenant `{x}`, `{y}` ou `{type}`.
+    ///
+    /// # Arguments
+    /// * `x`, `y` - Les coordonnées déjà mises en forme
+    /// * `z` - La valeur de la colonne `z`
+    /// * `type_value` - La valeur de type du point
+    /// * `attributes` - Les attributs du polygone, par nom de colonne
+    pub fn render(
+        &self,
+        x: &str,
+        y: &str,
+        z: &str,
+        type_value: &str,
+        attributes: &BTreeMap<String, String>,
+    ) -> String {
+        let fill = |field: &str| {
+            field
+                .replace("{x}", x)
+                .replace("{y}", y)
+                .replace("{z}", z)
+                .replace("{type}", type_value)
+        };
+        let fields: Vec<&str> = self.row.split('\t').collect();
+        if attributes.is_empty() || fields.len() != self.columns.len() {
+            return fill(&self.row);
+        }
+        fields
+            .iter()
+            .zip(&self.columns)
+            .map(|(field, column)| match attributes.get(column) {
+                Some(value) if !POINT_PLACEHOLDERS.iter().any(|p| field.contains(p)) => {
+                    value.clone()
+                }
+                _ => fill(field),
+            })
+            .collect::<Vec<_>>()
+            .join("\t")
+    }
+}
+
+/// Options de mise en forme d'un export.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ExportOptions {
+    pub format: OutputFormat,
+    /// Nombre de décimales des coordonnées, ou précision complète si None
+    pub coordinate_precision: Option<u8>,
+    pub line_ending: LineEnding,
+    /// Début du nom de fichier, suivi de l'horodatage de l'export
+    pub filename_prefix: String,
+    /// Séparateur décimal des coordonnées, par exemple ',' pour les outils français ;
+    /// ignoré en GeoJSON et en WKT, qui imposent le point
+    pub decimal_separator: char,
+    /// Réduit progressivement la densité des polygones restants si l'export
+    /// risque de dépasser `target_duration_seconds`
+    pub adaptive_density: bool,
+    /// Durée visée de l'export, en secondes
+    pub target_duration_seconds: Option<f64>,
+    /// Écrit un résumé `<nom>.summary.json` à côté du fichier exporté
+    pub write_summary_json: bool,
+    /// Code EPSG des coordonnées écrites ; celles du fichier source si non défini
+    pub output_epsg: Option<u32>,
+    /// Nombre maximal de threads remplissant les polygones en parallèle,
+    /// tous les cœurs si non défini ; 1 pour un traitement séquentiel
+    pub max_threads: Option<usize>,
+    /// Origine de la valeur de la colonne `z`
+    pub z_source: ZSource,
+    /// Disposition des colonnes du format `SdisTxt`, le modèle intégré si non définie
+    pub template: Option<OutputTemplate>,
+    /// Compresse le fichier exporté au format gzip, au fil de l'écriture
+    pub compress: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            format: OutputFormat::default(),
+            coordinate_precision: None,
+            line_ending: LineEnding::default(),
+            filename_prefix: "Export".to_string(),
+            decimal_separator: '.',
+            adaptive_density: false,
+            target_duration_seconds: None,
+            write_summary_json: false,
+            output_epsg: None,
+            max_threads: None,
+            z_source: ZSource::default(),
+            template: None,
+            compress: false,
+        }
+    }
+}
+
+impl ExportOptions {
+    /// Avertissement si le séparateur décimal est aussi le séparateur des champs,
+    /// ce qui rendrait les coordonnées illisibles.
+    pub fn separator_warning(&self) -> Option<String> {
+        (self.format.field_separator() == Some(self.decimal_separator)).then(|| {
+            format!(
+                "Decimal separator {:?} is also the field separator of the output format",
+                self.decimal_separator
+            )
+        })
+    }
+
+    /// Extension du fichier exporté, suivie de `.gz` s'il est compressé.
+    pub fn extension(&self) -> String {
+        if self.compress {
+            format!("{}.gz", self.format.extension())
+        } else {
+            self.format.extension().to_string()
+        }
+    }
+}
+
+/// Résumé d'un export, lisible par les outils d'automatisation.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExportSummary {
+    /// Fichier CSV d'origine, si connu
+    pub input_file: Option<String>,
+    pub output_file: String,
+    pub parameters: VegetationParams,
+    pub format: OutputFormat,
+    pub total_points: usize,
+    /// Nombre de points écrits par valeur de type
+    pub points_per_type: BTreeMap<u8, usize>,
+    pub duration_seconds: f64,
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+impl ExportSummary {
+    /// Nom du fichier de résumé associé à un fichier exporté.
+    ///
+    /// # Arguments
+    /// * `output_file` - Nom du fichier exporté
+    pub fn filename(output_file: &str) -> String {
+        let output_file = output_file.strip_suffix(".gz").unwrap_or(output_file);
+        let stem = std::path::Path::new(output_file).file_stem().map_or_else(
+            || output_file.to_string(),
+            |s| s.to_string_lossy().into_owned(),
+        );
+        format!("{}.summary.json", stem)
+    }
+}
+
+/// Préréglage d'export enregistré sous un nom.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ExportPreset {
+    #[serde(flatten)]
+    pub options: ExportOptions,
+    /// Densité remplaçant celle des paramètres de végétation, si définie
+    #[serde(default)]
+    pub density: Option<f64>,
+}
+
+impl ExportPreset {
+    /// Retourne les paramètres de végétation et les options d'export résultant du préréglage.
+    ///
+    /// # Arguments
+    /// * `param` - Les paramètres de végétation sur lesquels appliquer le préréglage
+    pub fn resolve(&self, param: &VegetationParams) -> (VegetationParams, ExportOptions) {
+        let mut param = param.clone();
+        if let Some(density) = self.density {
+            param.density = density;
+        }
+        (param, self.options.clone())
+    }
+}
+
+/// Commande Tauri pour enregistrer un préréglage d'export.
+///
+/// # Arguments
+/// * `name` - Nom du préréglage, remplacé s'il existe déjà
+/// * `preset` - Le préréglage à enregistrer
+///
+/// # Retours
+/// Ok(()) en cas de succès ou une erreur
+#[tauri::command]
+pub fn save_export_preset(name: String, preset: ExportPreset) -> Result<(), String> {
+    Settings::with_write(|s| s.save_export_preset(&name, &preset)).map_err(|e| e.to_string())
+}
+
+/// Commande Tauri pour lister les noms des préréglages d'export enregistrés.
+#[tauri::command]
+pub fn list_export_presets() -> Result<Vec<String>, String> {
+    Settings::with_read(|s| s.list_export_presets()).map_err(|e| e.to_string())
+}
+
+/// Commande Tauri pour obtenir un préréglage d'export à appliquer à un export.
+///
+/// # Arguments
+/// * `name` - Nom du préréglage
+///
+/// # Retours
+/// Le préréglage enregistré ou une erreur s'il n'existe pas
+#[tauri::command]
+pub fn apply_export_preset(name: String) -> Result<ExportPreset, String> {
+    Settings::with_read(|s| s.get_export_preset(&name)).map_err(|e| e.to_string())
+}
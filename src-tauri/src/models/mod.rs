@@ -1,6 +1,7 @@
 use geo::Point;
 use serde::Serialize;
 
+pub mod export;
 pub mod processing;
 pub mod settings;
 pub mod vegetations;
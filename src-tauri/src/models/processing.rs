@@ -1,7 +1,8 @@
 use serde::Serialize;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Runtime, State};
 
 #[derive(Serialize, Clone)]
 pub struct VegetationProgressInfo {
@@ -12,7 +13,46 @@ pub struct VegetationProgressInfo {
     pub percentage: f64,
     pub elapsed_seconds: Option<u64>,
     pub estimated_remaining_seconds: Option<u64>,
+    /// Polygones traités par seconde depuis le début de l'export
+    pub rows_per_second: Option<f64>,
+    /// Points créés par seconde depuis le début de l'export
+    pub points_per_second: Option<f64>,
     pub is_finished: bool,
+    /// Emprise (min_x, min_y, max_x, max_y) du polygone en cours de traitement
+    pub current_bounds: Option<(f64, f64, f64, f64)>,
+    /// Réductions de densité appliquées pour tenir la durée visée de l'export
+    pub density_adjustments: Vec<DensityAdjustment>,
+}
+
+/// Réduction de densité décidée en cours d'export, appliquée à partir d'un polygone.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct DensityAdjustment {
+    /// Numéro (à partir de 1) du premier polygone concerné
+    pub from_polygon: usize,
+    /// Facteur appliqué aux distances minimales des paramètres de végétation
+    pub factor: f64,
+}
+
+/// Destination des notifications de progression d'un export.
+pub trait ProgressReporter {
+    fn report(&self, progress: &VegetationProgressInfo);
+}
+
+impl<R: Runtime> ProgressReporter for AppHandle<R> {
+    fn report(&self, progress: &VegetationProgressInfo) {
+        if let Err(e) = self.emit("vegetation-progress", progress) {
+            eprintln!("Failed to emit progress event: {}", e);
+        }
+    }
+}
+
+/// Adapte une fonction en `ProgressReporter`, pour suivre un export hors de Tauri.
+pub struct CallbackReporter<F>(pub F);
+
+impl<F: Fn(&VegetationProgressInfo)> ProgressReporter for CallbackReporter<F> {
+    fn report(&self, progress: &VegetationProgressInfo) {
+        (self.0)(progress)
+    }
 }
 
 #[derive(Debug)]
@@ -23,6 +63,11 @@ pub struct VegetationProcessingState {
     pub created_items: Mutex<usize>,
     pub start_time: Mutex<Option<Instant>>,
     pub end_time: Mutex<Option<Instant>>,
+    pub current_bounds: Mutex<Option<(f64, f64, f64, f64)>>,
+    pub density_adjustments: Mutex<Vec<DensityAdjustment>>,
+    /// Demande d'annulation de l'export en cours, partagée entre l'état géré par Tauri
+    /// et la copie utilisée par le thread d'export
+    pub cancel_requested: Arc<AtomicBool>,
 }
 
 impl Clone for VegetationProcessingState {
@@ -34,6 +79,9 @@ impl Clone for VegetationProcessingState {
             created_items: Mutex::new(*self.created_items.lock().unwrap()),
             start_time: Mutex::new(*self.start_time.lock().unwrap()),
             end_time: Mutex::new(*self.end_time.lock().unwrap()),
+            current_bounds: Mutex::new(*self.current_bounds.lock().unwrap()),
+            density_adjustments: Mutex::new(self.density_adjustments.lock().unwrap().clone()),
+            cancel_requested: Arc::clone(&self.cancel_requested),
         }
     }
 }
@@ -53,44 +101,87 @@ impl VegetationProcessingState {
             errors: Mutex::new(Vec::new()),
             start_time: Mutex::new(None),
             end_time: Mutex::new(None),
+            current_bounds: Mutex::new(None),
+            density_adjustments: Mutex::new(Vec::new()),
+            cancel_requested: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    pub fn emit_progress(&self, app_handle: &AppHandle) {
-        let progress_info = self.get_progress_info();
-        if let Err(e) = app_handle.emit("vegetation-progress", &progress_info) {
-            eprintln!("Failed to emit progress event: {}", e);
-        }
+    /// Demande l'arrêt de l'export en cours, avant le prochain polygone.
+    pub fn request_cancel(&self) {
+        self.cancel_requested.store(true, Ordering::SeqCst);
     }
 
-    pub fn update_processed_rows(&self, count: usize, app_handle: &AppHandle) {
+    /// Oublie l'annulation demandée pour un export précédent. Appelée une seule fois avant
+    /// de lancer un export, pour qu'une annulation demandée pendant le comptage des lignes
+    /// ou entre deux types de végétation ne soit pas effacée par `initialize`.
+    pub fn clear_cancel(&self) {
+        self.cancel_requested.store(false, Ordering::SeqCst);
+    }
+
+    /// Indique si l'annulation de l'export en cours a été demandée.
+    pub fn is_cancel_requested(&self) -> bool {
+        self.cancel_requested.load(Ordering::SeqCst)
+    }
+
+    pub fn emit_progress(&self, reporter: &impl ProgressReporter) {
+        reporter.report(&self.get_progress_info());
+    }
+
+    pub fn update_processed_rows(&self, count: usize, reporter: &impl ProgressReporter) {
         *self.processed_rows.lock().unwrap() = count;
-        self.emit_progress(app_handle);
+        self.emit_progress(reporter);
+    }
+
+    /// Compte un polygone traité de plus ; utilisable depuis plusieurs threads à la fois.
+    pub fn increment_processed_rows(&self, reporter: &impl ProgressReporter) {
+        *self.processed_rows.lock().unwrap() += 1;
+        self.emit_progress(reporter);
     }
 
-    pub fn update_created_items(&self, count: usize, app_handle: &AppHandle) {
+    pub fn update_created_items(&self, count: usize, reporter: &impl ProgressReporter) {
         *self.created_items.lock().unwrap() = count;
-        self.emit_progress(app_handle);
+        self.emit_progress(reporter);
     }
 
-    pub fn add_error(&self, error: String, app_handle: &AppHandle) {
+    pub fn add_error(&self, error: String, reporter: &impl ProgressReporter) {
         self.errors.lock().unwrap().push(error);
-        self.emit_progress(app_handle);
+        self.emit_progress(reporter);
     }
 
-    pub fn set_finished(&self, app_handle: &AppHandle) {
+    pub fn set_current_bounds(
+        &self,
+        bounds: Option<(f64, f64, f64, f64)>,
+        reporter: &impl ProgressReporter,
+    ) {
+        *self.current_bounds.lock().unwrap() = bounds;
+        self.emit_progress(reporter);
+    }
+
+    pub fn add_density_adjustment(
+        &self,
+        adjustment: DensityAdjustment,
+        reporter: &impl ProgressReporter,
+    ) {
+        self.density_adjustments.lock().unwrap().push(adjustment);
+        self.emit_progress(reporter);
+    }
+
+    pub fn set_finished(&self, reporter: &impl ProgressReporter) {
         *self.end_time.lock().unwrap() = Some(Instant::now());
-        self.emit_progress(app_handle);
+        self.emit_progress(reporter);
     }
 
-    pub fn initialize(&self, total_rows: usize, app_handle: &AppHandle) {
+    pub fn initialize(&self, total_rows: usize, reporter: &impl ProgressReporter) {
         *self.processed_rows.lock().unwrap() = 0;
         *self.total_rows.lock().unwrap() = total_rows;
         *self.created_items.lock().unwrap() = 0;
         *self.errors.lock().unwrap() = Vec::new();
         *self.start_time.lock().unwrap() = Some(Instant::now());
         *self.end_time.lock().unwrap() = None;
-        self.emit_progress(app_handle);
+        *self.current_bounds.lock().unwrap() = None;
+        *self.density_adjustments.lock().unwrap() = Vec::new();
+        self.emit_progress(reporter);
     }
 
     fn get_progress_info(&self) -> VegetationProgressInfo {
@@ -100,6 +191,8 @@ impl VegetationProcessingState {
         let errors = self.errors.lock().unwrap().clone();
         let start_time = *self.start_time.lock().unwrap();
         let end_time = *self.end_time.lock().unwrap();
+        let current_bounds = *self.current_bounds.lock().unwrap();
+        let density_adjustments = self.density_adjustments.lock().unwrap().clone();
 
         let percentage = if total_rows > 0 {
             (current_row as f64 / total_rows as f64) * 100.0
@@ -128,6 +221,18 @@ impl VegetationProcessingState {
             None
         };
 
+        // Aucun débit tant qu'aucune durée ne s'est écoulée
+        let elapsed = start_time
+            .map(|start| {
+                end_time
+                    .unwrap_or_else(Instant::now)
+                    .duration_since(start)
+                    .as_secs_f64()
+            })
+            .filter(|&elapsed| elapsed > 0.0);
+        let rows_per_second = elapsed.map(|elapsed| current_row as f64 / elapsed);
+        let points_per_second = elapsed.map(|elapsed| created_items as f64 / elapsed);
+
         let is_finished = end_time.is_some() || (total_rows > 0 && current_row >= total_rows);
 
         VegetationProgressInfo {
@@ -138,7 +243,11 @@ impl VegetationProcessingState {
             percentage,
             elapsed_seconds,
             estimated_remaining_seconds,
+            rows_per_second,
+            points_per_second,
             is_finished,
+            current_bounds,
+            density_adjustments,
         }
     }
 }
@@ -149,3 +258,10 @@ pub fn get_vegetation_progress(
 ) -> VegetationProgressInfo {
     state.get_progress_info()
 }
+
+/// Commande Tauri pour annuler l'export en cours. Les points déjà écrits sont supprimés
+/// avec le fichier et l'événement `vegetation-export-cancelled` est émis.
+#[tauri::command]
+pub fn cancel_vegetation_export(state: State<'_, VegetationProcessingState>) {
+    state.request_cancel();
+}
@@ -1,12 +1,16 @@
 use directories::UserDirs;
 use rusqlite::{Connection, Result as SqliteResult, params};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, OnceLock, RwLock};
 use tauri::{AppHandle, Manager};
 use thiserror::Error;
 
-use crate::models::vegetations::VegetationParams;
+use crate::models::export::{ExportPreset, OutputTemplate};
+use crate::models::vegetations::{
+    ParamsError, VegetationParams, VegetationParamsBuilder, VegetationTypeInfo,
+};
 
 #[derive(Error, Debug)]
 pub enum SettingsError {
@@ -22,10 +26,72 @@ pub enum SettingsError {
     InvalidPath(String),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("Export preset not found: {0}")]
+    PresetNotFound(String),
+    #[error("Invalid coordinate precision: {0}")]
+    InvalidCoordinatePrecision(u8),
+    #[error("Settings profile not found: {0}")]
+    ProfileNotFound(String),
+    #[error("Invalid settings bundle: {0}")]
+    InvalidBundle(String),
+    #[error("Invalid vegetation parameters: {0}")]
+    InvalidParams(#[from] ParamsError),
+    #[error("Invalid output template: {0}")]
+    InvalidOutputTemplate(String),
 }
 
 type Result<T> = std::result::Result<T, SettingsError>;
 
+/// Nombre de décimales des coordonnées exportées, tant que l'utilisateur n'en a pas choisi.
+pub const DEFAULT_COORDINATE_PRECISION: u8 = 3;
+
+/// Nombre maximal de décimales des coordonnées ; au-delà, un `f64` n'est plus exact.
+pub const MAX_COORDINATE_PRECISION: u8 = 15;
+
+/// Libellés des types de végétation définis par défaut.
+const DEFAULT_VEGETATION_LABELS: [(i8, &str); 3] =
+    [(1, "Arbres"), (2, "Surfaces"), (3, "Roccailles")];
+
+/// Colonnes ajoutées aux tables de paramètres après leur création, avec leur définition.
+const VEGETATION_PARAMS_MIGRATIONS: [(&str, &str); 2] =
+    [("variation", "REAL NOT NULL DEFAULT 0"), ("label", "TEXT")];
+
+/// Profil de paramètres nommé, par exemple propre à une région.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SettingsProfile {
+    /// Dossier d'export du profil
+    pub export_path: PathBuf,
+    /// Paramètres des types de végétation définis par le profil ; les autres types
+    /// gardent leurs paramètres généraux
+    pub vegetation_params: Vec<VegetationParams>,
+}
+
+/// Version du format des lots de paramètres écrits par `export_bundle`.
+pub const SETTINGS_BUNDLE_VERSION: u32 = 1;
+
+/// Lot de paramètres échangé entre postes : dossier d'export et paramètres de
+/// végétation, par défaut et de l'utilisateur. Les clés inconnues sont refusées.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SettingsBundle {
+    pub version: u32,
+    pub export_path: PathBuf,
+    pub default_vegetation_params: Vec<BundledVegetationParams>,
+    pub user_vegetation_params: Vec<BundledVegetationParams>,
+}
+
+/// Paramètres d'un type de végétation tels qu'enregistrés dans la base.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BundledVegetationParams {
+    pub vegetation_type: i8,
+    pub density: f64,
+    pub type_value: u8,
+    pub variation: f64,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Settings {
     db_path: PathBuf,
@@ -48,8 +114,14 @@ impl Settings {
     }
 
     fn new(app_handle: AppHandle) -> Result<Self> {
-        let db_path = Self::get_database_path(&app_handle)?;
+        Self::open(Self::get_database_path(&app_handle)?)
+    }
 
+    /// Ouvre (ou crée) la base de paramètres au chemin donné, sans passer par Tauri.
+    ///
+    /// # Arguments
+    /// * `db_path` - Chemin du fichier SQLite
+    pub fn open(db_path: PathBuf) -> Result<Self> {
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
@@ -84,7 +156,9 @@ impl Settings {
             "CREATE TABLE IF NOT EXISTS default_vegetation_params (
                 vegetation_type INTEGER PRIMARY KEY,
                 density REAL NOT NULL,
-                type_value INTEGER NOT NULL
+                type_value INTEGER NOT NULL,
+                variation REAL NOT NULL DEFAULT 0,
+                label TEXT
             )",
             [],
         )?;
@@ -93,15 +167,77 @@ impl Settings {
             "CREATE TABLE IF NOT EXISTS user_vegetation_params (
                 vegetation_type INTEGER PRIMARY KEY,
                 density REAL NOT NULL,
-                type_value INTEGER NOT NULL
+                type_value INTEGER NOT NULL,
+                variation REAL NOT NULL DEFAULT 0,
+                label TEXT
             )",
             [],
         )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS export_presets (
+                name TEXT PRIMARY KEY,
+                preset TEXT NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS profiles (
+                name TEXT PRIMARY KEY,
+                export_path TEXT NOT NULL,
+                vegetation_params TEXT NOT NULL
+            )",
+            [],
+        )?;
+        Self::migrate_vegetation_params(&conn)?;
         self.initialize_default_values(&conn)?;
 
         Ok(())
     }
 
+    /// Ajoute aux tables de paramètres créées par une version antérieure
+    /// les colonnes qui leur manquent.
+    ///
+    /// # Arguments
+    /// * `conn` - Connexion à la base à migrer
+    fn migrate_vegetation_params(conn: &Connection) -> Result<()> {
+        for table in ["default_vegetation_params", "user_vegetation_params"] {
+            let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+            let columns = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .collect::<SqliteResult<Vec<_>>>()?;
+            for (column, definition) in VEGETATION_PARAMS_MIGRATIONS {
+                if !columns.iter().any(|existing| existing == column) {
+                    conn.execute(
+                        &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+                        [],
+                    )?;
+                }
+            }
+        }
+
+        // Les types par défaut enregistrés avant l'ajout des libellés reçoivent le leur
+        for (vegetation_type, label) in DEFAULT_VEGETATION_LABELS {
+            conn.execute(
+                "UPDATE default_vegetation_params SET label = ?2
+                 WHERE vegetation_type = ?1 AND label IS NULL",
+                params![vegetation_type, label],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Construit les paramètres de végétation à partir d'une ligne
+    /// `vegetation_type, density, type_value, variation`.
+    fn params_from_row(row: &rusqlite::Row) -> SqliteResult<VegetationParams> {
+        Ok(VegetationParams {
+            vegetation_type: row.get::<_, u8>(0)?,
+            density: row.get(1)?,
+            type_value: row.get::<_, u8>(2)?,
+            variation: row.get(3)?,
+            ..Default::default()
+        })
+    }
+
     fn initialize_default_values(&self, conn: &Connection) -> Result<()> {
         let export_path_exists: bool = conn.query_row(
             "SELECT EXISTS(SELECT 1 FROM settings WHERE key = 'export_path')",
@@ -123,25 +259,55 @@ impl Settings {
         )?;
 
         if default_params_count == 0 {
-            let default_params = Self::create_default_vegetation_params();
-            for (vegetation_type, params) in default_params {
-                conn.execute(
-                    "INSERT INTO default_vegetation_params (vegetation_type, density, type_value) 
-                     VALUES (?1, ?2, ?3)",
-                    params![vegetation_type, params.density, params.type_value],
-                )?;
-            }
+            Self::insert_default_vegetation_params(conn)?;
         }
 
         Ok(())
     }
 
+    fn insert_default_vegetation_params(conn: &Connection) -> Result<()> {
+        let default_params = Self::create_default_vegetation_params();
+        for (vegetation_type, params) in default_params {
+            conn.execute(
+                "INSERT INTO default_vegetation_params (vegetation_type, density, type_value, variation, label) 
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    vegetation_type,
+                    params.density,
+                    params.type_value,
+                    params.variation,
+                    Self::default_label(vegetation_type)
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Remplace les paramètres par défaut enregistrés par ceux définis dans le code.
+    /// Les paramètres de l'utilisateur ne sont pas modifiés.
+    pub fn rebuild_default_params(&self) -> Result<()> {
+        let mut conn = self.get_connection()?;
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM default_vegetation_params", [])?;
+        Self::insert_default_vegetation_params(&tx)?;
+        tx.commit()?;
+        Ok(())
+    }
+
     fn get_default_export_path() -> PathBuf {
         UserDirs::new()
             .and_then(|dirs| dirs.download_dir().map(|p| p.to_path_buf()))
             .unwrap_or_else(|| PathBuf::from("Downloads"))
     }
 
+    /// Libellé par défaut d'un type de végétation, s'il en a un.
+    fn default_label(vegetation_type: i8) -> Option<&'static str> {
+        DEFAULT_VEGETATION_LABELS
+            .iter()
+            .find(|(default_type, _)| *default_type == vegetation_type)
+            .map(|(_, label)| *label)
+    }
+
     fn create_default_vegetation_params() -> HashMap<i8, VegetationParams> {
         HashMap::from([
             (
@@ -150,6 +316,7 @@ impl Settings {
                     vegetation_type: 1,
                     density: 28.0,
                     type_value: 10,
+                    ..Default::default()
                 },
             ),
             (
@@ -158,6 +325,7 @@ impl Settings {
                     vegetation_type: 2,
                     density: 5.0,
                     type_value: 20,
+                    ..Default::default()
                 },
             ),
             (
@@ -166,6 +334,7 @@ impl Settings {
                     vegetation_type: 3,
                     density: 3.0,
                     type_value: 30,
+                    ..Default::default()
                 },
             ),
         ])
@@ -193,7 +362,21 @@ impl Settings {
         f(&settings)
     }
 
+    /// Garantit que toutes les écritures sont persistées sur disque.
+    /// Les écritures SQLite sont immédiates ; un checkpoint force en plus le report
+    /// du journal WAL dans la base lorsqu'il est actif.
+    pub fn flush(&self) -> Result<()> {
+        let conn = self.get_connection()?;
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        Ok(())
+    }
+
+    /// Retourne le dossier d'export : celui du profil actif s'il y en a un, sinon
+    /// celui des paramètres généraux.
     pub fn get_export_path(&self) -> Result<PathBuf> {
+        if let Some(profile) = self.get_active_profile()? {
+            return Ok(self.get_profile(&profile)?.export_path);
+        }
         let conn = self.get_connection()?;
         let path_str: String = conn.query_row(
             "SELECT value FROM settings WHERE key = 'export_path'",
@@ -203,7 +386,25 @@ impl Settings {
         Ok(PathBuf::from(path_str))
     }
 
+    /// Enregistre le dossier d'export : dans le profil actif s'il y en a un, sinon
+    /// dans les paramètres généraux.
     pub fn set_export_path(&self, path: PathBuf) -> Result<()> {
+        Self::validate_export_path(&path)?;
+        if self.update_active_profile(|profile| profile.export_path = path.clone())? {
+            return Ok(());
+        }
+
+        let conn = self.get_connection()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES ('export_path', ?1)",
+            params![path.to_string_lossy().to_string()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Vérifie qu'un dossier d'export existe et est bien un dossier.
+    fn validate_export_path(path: &Path) -> Result<()> {
         if !path.exists() {
             return Err(SettingsError::InvalidPath(format!(
                 "Path does not exist: {}",
@@ -216,26 +417,104 @@ impl Settings {
                 path.display()
             )));
         }
+        Ok(())
+    }
 
+    /// Retourne le nombre de décimales des coordonnées exportées.
+    ///
+    /// # Retours
+    /// La précision enregistrée, ou `DEFAULT_COORDINATE_PRECISION` si aucune ne l'a été
+    pub fn get_coordinate_precision(&self) -> Result<u8> {
+        let conn = self.get_connection()?;
+        let result = conn.query_row(
+            "SELECT value FROM settings WHERE key = 'coordinate_precision'",
+            [],
+            |row| row.get::<_, String>(0),
+        );
+
+        match result {
+            Ok(value) => Ok(value
+                .parse()
+                .ok()
+                .filter(|precision| *precision <= MAX_COORDINATE_PRECISION)
+                .unwrap_or(DEFAULT_COORDINATE_PRECISION)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(DEFAULT_COORDINATE_PRECISION),
+            Err(e) => Err(SettingsError::Database(e)),
+        }
+    }
+
+    /// Enregistre le nombre de décimales des coordonnées exportées.
+    ///
+    /// # Arguments
+    /// * `precision` - Nombre de décimales, au plus `MAX_COORDINATE_PRECISION`
+    pub fn set_coordinate_precision(&self, precision: u8) -> Result<()> {
+        if precision > MAX_COORDINATE_PRECISION {
+            return Err(SettingsError::InvalidCoordinatePrecision(precision));
+        }
         let conn = self.get_connection()?;
         conn.execute(
-            "INSERT OR REPLACE INTO settings (key, value) VALUES ('export_path', ?1)",
-            params![path.to_string_lossy().to_string()],
+            "INSERT OR REPLACE INTO settings (key, value) VALUES ('coordinate_precision', ?1)",
+            params![precision.to_string()],
         )?;
+        Ok(())
+    }
+
+    /// Retourne le modèle des lignes `SdisTxt` enregistré.
+    ///
+    /// # Retours
+    /// Le modèle, ou None si aucun ne l'a été et que le modèle intégré s'applique
+    pub fn get_output_template(&self) -> Result<Option<OutputTemplate>> {
+        let conn = self.get_connection()?;
+        let result = conn.query_row(
+            "SELECT value FROM settings WHERE key = 'output_template'",
+            [],
+            |row| row.get::<_, String>(0),
+        );
 
+        match result {
+            Ok(json) => Ok(Some(serde_json::from_str(&json)?)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(SettingsError::Database(e)),
+        }
+    }
+
+    /// Enregistre le modèle des lignes `SdisTxt`, ou revient au modèle intégré.
+    ///
+    /// # Arguments
+    /// * `template` - Le modèle à enregistrer, None pour le modèle intégré
+    pub fn set_output_template(&self, template: Option<&OutputTemplate>) -> Result<()> {
+        let conn = self.get_connection()?;
+        let Some(template) = template else {
+            conn.execute("DELETE FROM settings WHERE key = 'output_template'", [])?;
+            return Ok(());
+        };
+        template
+            .validate()
+            .map_err(SettingsError::InvalidOutputTemplate)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES ('output_template', ?1)",
+            params![serde_json::to_string(template)?],
+        )?;
         Ok(())
     }
 
+    /// Retourne les paramètres effectifs d'un type de végétation : ceux du profil actif
+    /// s'il les définit, sinon ceux de l'utilisateur, sinon ceux par défaut.
     pub fn get_vegetation_params(&self, vegetation_type: i8) -> Result<Option<VegetationParams>> {
+        if let Some(profile) = self.get_active_profile()?
+            && let Some(params) = self
+                .get_profile(&profile)?
+                .vegetation_params
+                .into_iter()
+                .find(|params| params.vegetation_type as i8 == vegetation_type)
+        {
+            return Ok(Some(params));
+        }
         let conn = self.get_connection()?;
         let user_result = conn.query_row(
-            "SELECT vegetation_type, density, type_value FROM user_vegetation_params WHERE vegetation_type = ?1",
+            "SELECT vegetation_type, density, type_value, variation FROM user_vegetation_params WHERE vegetation_type = ?1",
             params![vegetation_type],
-            |row| Ok(VegetationParams {
-                vegetation_type: row.get::<_, u8>(0)?,
-                density: row.get(1)?,
-                type_value: row.get::<_, u8>(2)?,
-            })
+            Self::params_from_row,
         );
 
         if let Ok(params) = user_result {
@@ -243,13 +522,9 @@ impl Settings {
         }
 
         let default_result = conn.query_row(
-            "SELECT vegetation_type, density, type_value FROM default_vegetation_params WHERE vegetation_type = ?1",
+            "SELECT vegetation_type, density, type_value, variation FROM default_vegetation_params WHERE vegetation_type = ?1",
             params![vegetation_type],
-            |row| Ok(VegetationParams {
-                vegetation_type: row.get::<_, u8>(0)?,
-                density: row.get(1)?,
-                type_value: row.get::<_, u8>(2)?,
-            })
+            Self::params_from_row,
         );
 
         match default_result {
@@ -266,13 +541,9 @@ impl Settings {
         let conn = self.get_connection()?;
 
         let result = conn.query_row(
-            "SELECT vegetation_type, density, type_value FROM default_vegetation_params WHERE vegetation_type = ?1",
+            "SELECT vegetation_type, density, type_value, variation FROM default_vegetation_params WHERE vegetation_type = ?1",
             params![vegetation_type],
-            |row| Ok(VegetationParams {
-                vegetation_type: row.get::<_, u8>(0)?,
-                density: row.get(1)?,
-                type_value: row.get::<_, u8>(2)?,
-            })
+            Self::params_from_row,
         );
 
         match result {
@@ -282,20 +553,25 @@ impl Settings {
         }
     }
 
+    /// Retourne les paramètres enregistrés par l'utilisateur pour un type : ceux du
+    /// profil actif s'il y en a un, sinon ceux des paramètres généraux.
     pub fn get_user_vegetation_params(
         &self,
         vegetation_type: i8,
     ) -> Result<Option<VegetationParams>> {
+        if let Some(profile) = self.get_active_profile()? {
+            return Ok(self
+                .get_profile(&profile)?
+                .vegetation_params
+                .into_iter()
+                .find(|params| params.vegetation_type as i8 == vegetation_type));
+        }
         let conn = self.get_connection()?;
 
         let result = conn.query_row(
-            "SELECT vegetation_type, density, type_value FROM user_vegetation_params WHERE vegetation_type = ?1",
+            "SELECT vegetation_type, density, type_value, variation FROM user_vegetation_params WHERE vegetation_type = ?1",
             params![vegetation_type],
-            |row| Ok(VegetationParams {
-                vegetation_type: row.get::<_, u8>(0)?,
-                density: row.get(1)?,
-                type_value: row.get::<_, u8>(2)?,
-            })
+            Self::params_from_row,
         );
 
         match result {
@@ -305,37 +581,77 @@ impl Settings {
         }
     }
 
+    /// Enregistre les paramètres de l'utilisateur pour un type : dans le profil actif
+    /// s'il y en a un, sinon dans les paramètres généraux.
     pub fn set_user_vegetation_params(
         &self,
         vegetation_type: i8,
         params: VegetationParams,
     ) -> Result<()> {
-        if vegetation_type < 1 {
-            return Err(SettingsError::InvalidVegetationType(vegetation_type));
-        }
-
-        if params.density < 0.0 {
-            return Err(SettingsError::InvalidPath(
-                "Density cannot be negative".to_string(),
-            ));
+        Self::validate_vegetation_params(vegetation_type, &params)?;
+        let in_profile = self.update_active_profile(|profile| {
+            let params = VegetationParams {
+                vegetation_type: vegetation_type as u8,
+                ..params.clone()
+            };
+            match profile
+                .vegetation_params
+                .iter_mut()
+                .find(|existing| existing.vegetation_type == params.vegetation_type)
+            {
+                Some(existing) => *existing = params,
+                None => profile.vegetation_params.push(params),
+            }
+        })?;
+        if in_profile {
+            return Ok(());
         }
 
         let conn = self.get_connection()?;
         conn.execute(
-            "INSERT OR REPLACE INTO user_vegetation_params (vegetation_type, density, type_value) 
-             VALUES (?1, ?2, ?3)",
-            params![vegetation_type, params.density, params.type_value],
+            "INSERT INTO user_vegetation_params (vegetation_type, density, type_value, variation) 
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(vegetation_type) DO UPDATE SET
+                density = excluded.density,
+                type_value = excluded.type_value,
+                variation = excluded.variation",
+            params![
+                vegetation_type,
+                params.density,
+                params.type_value,
+                params.variation
+            ],
         )?;
 
         Ok(())
     }
 
+    /// Vérifie les paramètres de végétation enregistrés pour un type.
+    fn validate_vegetation_params(vegetation_type: i8, params: &VegetationParams) -> Result<()> {
+        if vegetation_type < 1 {
+            return Err(SettingsError::InvalidVegetationType(vegetation_type));
+        }
+        VegetationParamsBuilder::from(params.clone())
+            .vegetation_type(vegetation_type as u8)
+            .build()?;
+        Ok(())
+    }
+
+    /// Supprime les paramètres de l'utilisateur pour un type : du profil actif s'il y en
+    /// a un, sinon des paramètres généraux.
     pub fn remove_user_vegetation_params(
         &self,
         vegetation_type: i8,
     ) -> Result<Option<VegetationParams>> {
-        let conn = self.get_connection()?;
         let existing = self.get_user_vegetation_params(vegetation_type)?;
+        if self.update_active_profile(|profile| {
+            profile
+                .vegetation_params
+                .retain(|params| params.vegetation_type as i8 != vegetation_type)
+        })? {
+            return Ok(existing);
+        }
+        let conn = self.get_connection()?;
         conn.execute(
             "DELETE FROM user_vegetation_params WHERE vegetation_type = ?1",
             params![vegetation_type],
@@ -344,12 +660,19 @@ impl Settings {
         Ok(existing)
     }
 
+    /// Supprime tous les paramètres de l'utilisateur : ceux du profil actif s'il y en a
+    /// un, sinon ceux des paramètres généraux.
     pub fn reset_user_vegetation_params(&self) -> Result<()> {
+        if self.update_active_profile(|profile| profile.vegetation_params.clear())? {
+            return Ok(());
+        }
         let conn = self.get_connection()?;
         conn.execute("DELETE FROM user_vegetation_params", [])?;
         Ok(())
     }
 
+    /// Liste les types de végétation ayant des paramètres : par défaut, de l'utilisateur
+    /// ou du profil actif.
     pub fn get_available_vegetation_types(&self) -> Result<Vec<i8>> {
         let conn = self.get_connection()?;
 
@@ -366,10 +689,377 @@ impl Settings {
         for row in rows {
             types.push(row?);
         }
+        if let Some(profile) = self.get_active_profile()? {
+            types.extend(
+                self.get_profile(&profile)?
+                    .vegetation_params
+                    .iter()
+                    .map(|params| params.vegetation_type as i8),
+            );
+            types.sort_unstable();
+            types.dedup();
+        }
+
+        Ok(types)
+    }
+
+    /// Retourne le libellé d'un type de végétation : celui de l'utilisateur s'il en a
+    /// défini un, celui par défaut sinon.
+    ///
+    /// # Arguments
+    /// * `vegetation_type` - Type de végétation
+    ///
+    /// # Retours
+    /// Le libellé, ou None si le type n'en a pas
+    pub fn get_vegetation_type_label(&self, vegetation_type: i8) -> Result<Option<String>> {
+        let conn = self.get_connection()?;
+        let result = conn.query_row(
+            "SELECT label FROM user_vegetation_params
+             WHERE vegetation_type = ?1 AND label IS NOT NULL
+             UNION ALL
+             SELECT label FROM default_vegetation_params
+             WHERE vegetation_type = ?1 AND label IS NOT NULL
+             LIMIT 1",
+            params![vegetation_type],
+            |row| row.get::<_, String>(0),
+        );
+
+        match result {
+            Ok(label) => Ok(Some(label)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(SettingsError::Database(e)),
+        }
+    }
+
+    /// Définit le libellé d'un type de végétation. Les libellés sont communs à tous les
+    /// profils : ils sont enregistrés dans les paramètres généraux de l'utilisateur, créés
+    /// à partir des paramètres par défaut du type, ou de ses paramètres effectifs, s'ils
+    /// n'existent pas.
+    ///
+    /// # Arguments
+    /// * `vegetation_type` - Type de végétation, qui doit avoir des paramètres
+    /// * `label` - Libellé affiché dans l'interface
+    pub fn set_vegetation_type_label(&self, vegetation_type: i8, label: &str) -> Result<()> {
+        let params = match self.get_default_vegetation_params(vegetation_type)? {
+            Some(params) => params,
+            None => self
+                .get_vegetation_params(vegetation_type)?
+                .ok_or(SettingsError::InvalidVegetationType(vegetation_type))?,
+        };
+
+        let conn = self.get_connection()?;
+        let changed = conn.execute(
+            "INSERT INTO user_vegetation_params (vegetation_type, density, type_value, variation, label)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(vegetation_type) DO UPDATE SET label = excluded.label",
+            params![
+                vegetation_type,
+                params.density,
+                params.type_value,
+                params.variation,
+                label
+            ],
+        )?;
+        if changed == 0 {
+            return Err(SettingsError::InvalidVegetationType(vegetation_type));
+        }
+        Ok(())
+    }
 
+    /// Liste les types de végétation disponibles avec leur libellé et leurs paramètres
+    /// effectifs, pour construire la liste de choix de l'interface.
+    pub fn list_vegetation_types(&self) -> Result<Vec<VegetationTypeInfo>> {
+        let mut types = Vec::new();
+        for vegetation_type in self.get_available_vegetation_types()? {
+            let Some(params) = self.get_vegetation_params(vegetation_type)? else {
+                continue;
+            };
+            types.push(VegetationTypeInfo {
+                vegetation_type,
+                label: self.get_vegetation_type_label(vegetation_type)?,
+                density: params.density,
+                type_value: params.type_value,
+            });
+        }
         Ok(types)
     }
 
+    pub fn save_export_preset(&self, name: &str, preset: &ExportPreset) -> Result<()> {
+        let conn = self.get_connection()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO export_presets (name, preset) VALUES (?1, ?2)",
+            params![name, serde_json::to_string(preset)?],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_export_preset(&self, name: &str) -> Result<ExportPreset> {
+        let conn = self.get_connection()?;
+        let result = conn.query_row(
+            "SELECT preset FROM export_presets WHERE name = ?1",
+            params![name],
+            |row| row.get::<_, String>(0),
+        );
+
+        match result {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Err(SettingsError::PresetNotFound(name.to_string()))
+            }
+            Err(e) => Err(SettingsError::Database(e)),
+        }
+    }
+
+    pub fn list_export_presets(&self) -> Result<Vec<String>> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare("SELECT name FROM export_presets ORDER BY name")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut names = Vec::new();
+        for row in rows {
+            names.push(row?);
+        }
+
+        Ok(names)
+    }
+
+    /// Crée ou remplace un profil de paramètres, par exemple pour une région.
+    ///
+    /// # Arguments
+    /// * `name` - Nom du profil
+    /// * `profile` - Dossier d'export et paramètres de végétation du profil
+    pub fn create_profile(&self, name: &str, profile: &SettingsProfile) -> Result<()> {
+        Self::validate_export_path(&profile.export_path)?;
+        for params in &profile.vegetation_params {
+            Self::validate_vegetation_params(params.vegetation_type as i8, params)?;
+        }
+
+        let conn = self.get_connection()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO profiles (name, export_path, vegetation_params)
+             VALUES (?1, ?2, ?3)",
+            params![
+                name,
+                profile.export_path.to_string_lossy().to_string(),
+                serde_json::to_string(&profile.vegetation_params)?
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_profile(&self, name: &str) -> Result<SettingsProfile> {
+        let conn = self.get_connection()?;
+        let result = conn.query_row(
+            "SELECT export_path, vegetation_params FROM profiles WHERE name = ?1",
+            params![name],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        );
+
+        match result {
+            Ok((export_path, vegetation_params)) => Ok(SettingsProfile {
+                export_path: PathBuf::from(export_path),
+                vegetation_params: serde_json::from_str(&vegetation_params)?,
+            }),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Err(SettingsError::ProfileNotFound(name.to_string()))
+            }
+            Err(e) => Err(SettingsError::Database(e)),
+        }
+    }
+
+    /// Modifie le profil actif et l'enregistre.
+    ///
+    /// # Arguments
+    /// * `update` - Modification appliquée au profil
+    ///
+    /// # Retours
+    /// `true` si un profil était actif et a été modifié, `false` sinon
+    fn update_active_profile(&self, update: impl FnOnce(&mut SettingsProfile)) -> Result<bool> {
+        let Some(name) = self.get_active_profile()? else {
+            return Ok(false);
+        };
+        let mut profile = self.get_profile(&name)?;
+        update(&mut profile);
+        self.create_profile(&name, &profile)?;
+        Ok(true)
+    }
+
+    /// Active un profil : les lectures et écritures du dossier d'export et des
+    /// paramètres de végétation se font ensuite dans ce profil.
+    ///
+    /// # Arguments
+    /// * `name` - Nom du profil, ou None pour revenir aux paramètres généraux
+    pub fn switch_profile(&self, name: Option<&str>) -> Result<()> {
+        let conn = self.get_connection()?;
+        match name {
+            Some(name) => {
+                self.get_profile(name)?;
+                conn.execute(
+                    "INSERT OR REPLACE INTO settings (key, value) VALUES ('active_profile', ?1)",
+                    params![name],
+                )?;
+            }
+            None => {
+                conn.execute("DELETE FROM settings WHERE key = 'active_profile'", [])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Retourne le nom du profil actif, ou None si aucun ne l'est.
+    pub fn get_active_profile(&self) -> Result<Option<String>> {
+        let conn = self.get_connection()?;
+        let result = conn.query_row(
+            "SELECT value FROM settings WHERE key = 'active_profile'",
+            [],
+            |row| row.get::<_, String>(0),
+        );
+
+        match result {
+            Ok(name) => Ok(Some(name)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(SettingsError::Database(e)),
+        }
+    }
+
+    pub fn list_profiles(&self) -> Result<Vec<String>> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare("SELECT name FROM profiles ORDER BY name")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut names = Vec::new();
+        for row in rows {
+            names.push(row?);
+        }
+
+        Ok(names)
+    }
+
+    /// Supprime un profil ; s'il était actif, les paramètres généraux s'appliquent à nouveau.
+    pub fn delete_profile(&self, name: &str) -> Result<()> {
+        let conn = self.get_connection()?;
+        if conn.execute("DELETE FROM profiles WHERE name = ?1", params![name])? == 0 {
+            return Err(SettingsError::ProfileNotFound(name.to_string()));
+        }
+        conn.execute(
+            "DELETE FROM settings WHERE key = 'active_profile' AND value = ?1",
+            params![name],
+        )?;
+        Ok(())
+    }
+
+    /// Sérialise en JSON le dossier d'export général et tous les paramètres de
+    /// végétation, par défaut et de l'utilisateur, pour les importer sur un autre poste.
+    ///
+    /// # Retours
+    /// Le lot de paramètres en JSON
+    pub fn export_bundle(&self) -> Result<String> {
+        let conn = self.get_connection()?;
+        let export_path: String = conn.query_row(
+            "SELECT value FROM settings WHERE key = 'export_path'",
+            [],
+            |row| row.get(0),
+        )?;
+        let bundle = SettingsBundle {
+            version: SETTINGS_BUNDLE_VERSION,
+            export_path: PathBuf::from(export_path),
+            default_vegetation_params: Self::bundled_params(&conn, "default_vegetation_params")?,
+            user_vegetation_params: Self::bundled_params(&conn, "user_vegetation_params")?,
+        };
+        Ok(serde_json::to_string_pretty(&bundle)?)
+    }
+
+    /// Lit toutes les lignes d'une table de paramètres de végétation.
+    fn bundled_params(conn: &Connection, table: &str) -> Result<Vec<BundledVegetationParams>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT vegetation_type, density, type_value, variation, label FROM {}
+             ORDER BY vegetation_type",
+            table
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok(BundledVegetationParams {
+                vegetation_type: row.get(0)?,
+                density: row.get(1)?,
+                type_value: row.get(2)?,
+                variation: row.get(3)?,
+                label: row.get(4)?,
+            })
+        })?;
+        let params = rows.collect::<SqliteResult<Vec<_>>>()?;
+        Ok(params)
+    }
+
+    /// Vérifie puis applique un lot de paramètres écrit par `export_bundle` : le dossier
+    /// d'export est remplacé et les paramètres de végétation sont créés ou mis à jour.
+    /// Le lot est appliqué en une transaction : en cas d'erreur, rien n'est modifié.
+    ///
+    /// # Arguments
+    /// * `json` - Le lot de paramètres en JSON
+    pub fn import_bundle(&self, json: &str) -> Result<()> {
+        let bundle: SettingsBundle =
+            serde_json::from_str(json).map_err(|e| SettingsError::InvalidBundle(e.to_string()))?;
+        if bundle.version != SETTINGS_BUNDLE_VERSION {
+            return Err(SettingsError::InvalidBundle(format!(
+                "unsupported version {}",
+                bundle.version
+            )));
+        }
+        Self::validate_export_path(&bundle.export_path)?;
+        for params in bundle
+            .default_vegetation_params
+            .iter()
+            .chain(&bundle.user_vegetation_params)
+        {
+            Self::validate_vegetation_params(
+                params.vegetation_type,
+                &VegetationParams {
+                    density: params.density,
+                    type_value: params.type_value,
+                    variation: params.variation,
+                    ..Default::default()
+                },
+            )?;
+        }
+
+        let mut conn = self.get_connection()?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES ('export_path', ?1)",
+            params![bundle.export_path.to_string_lossy().to_string()],
+        )?;
+        for (table, rows) in [
+            (
+                "default_vegetation_params",
+                &bundle.default_vegetation_params,
+            ),
+            ("user_vegetation_params", &bundle.user_vegetation_params),
+        ] {
+            for params in rows {
+                tx.execute(
+                    &format!(
+                        "INSERT INTO {} (vegetation_type, density, type_value, variation, label)
+                         VALUES (?1, ?2, ?3, ?4, ?5)
+                         ON CONFLICT(vegetation_type) DO UPDATE SET
+                            density = excluded.density,
+                            type_value = excluded.type_value,
+                            variation = excluded.variation,
+                            label = excluded.label",
+                        table
+                    ),
+                    params![
+                        params.vegetation_type,
+                        params.density,
+                        params.type_value,
+                        params.variation,
+                        params.label
+                    ],
+                )?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     pub fn has_user_params(&self, vegetation_type: i8) -> Result<bool> {
         let conn = self.get_connection()?;
         let count: i64 = conn.query_row(
@@ -390,3 +1080,137 @@ pub fn get_export_path() -> String {
             .to_string()
     })
 }
+
+/// Commande Tauri pour obtenir le nombre de décimales des coordonnées exportées.
+#[tauri::command]
+pub fn get_coordinate_precision() -> u8 {
+    Settings::with_read(|s| {
+        s.get_coordinate_precision()
+            .unwrap_or(DEFAULT_COORDINATE_PRECISION)
+    })
+}
+
+/// Commande Tauri pour enregistrer le nombre de décimales des coordonnées exportées.
+///
+/// # Arguments
+/// * `precision` - Nombre de décimales, au plus `MAX_COORDINATE_PRECISION`
+///
+/// # Retours
+/// Ok(()) en cas de succès ou une erreur
+#[tauri::command]
+pub fn set_coordinate_precision(precision: u8) -> std::result::Result<(), String> {
+    Settings::with_write(|s| s.set_coordinate_precision(precision)).map_err(|e| e.to_string())
+}
+
+/// Commande Tauri pour obtenir le modèle des lignes `SdisTxt` utilisé par les exports.
+///
+/// # Retours
+/// Le modèle enregistré, ou le modèle intégré si aucun ne l'a été
+#[tauri::command]
+pub fn get_output_template() -> OutputTemplate {
+    Settings::with_read(|s| s.get_output_template().unwrap_or(None)).unwrap_or_default()
+}
+
+/// Commande Tauri pour enregistrer le modèle des lignes `SdisTxt`.
+///
+/// # Arguments
+/// * `template` - Le modèle à enregistrer, None pour revenir au modèle intégré
+///
+/// # Retours
+/// Ok(()) en cas de succès ou une erreur
+#[tauri::command]
+pub fn set_output_template(template: Option<OutputTemplate>) -> std::result::Result<(), String> {
+    Settings::with_write(|s| s.set_output_template(template.as_ref())).map_err(|e| e.to_string())
+}
+
+/// Commande Tauri pour créer ou remplacer un profil de paramètres.
+///
+/// # Arguments
+/// * `name` - Nom du profil
+/// * `profile` - Dossier d'export et paramètres de végétation du profil
+///
+/// # Retours
+/// Ok(()) en cas de succès ou une erreur
+#[tauri::command]
+pub fn create_profile(name: String, profile: SettingsProfile) -> std::result::Result<(), String> {
+    Settings::with_write(|s| s.create_profile(&name, &profile)).map_err(|e| e.to_string())
+}
+
+/// Commande Tauri pour activer un profil, ou revenir aux paramètres généraux.
+///
+/// # Arguments
+/// * `name` - Nom du profil, ou None pour désactiver le profil actif
+///
+/// # Retours
+/// Ok(()) en cas de succès ou une erreur si le profil n'existe pas
+#[tauri::command]
+pub fn switch_profile(name: Option<String>) -> std::result::Result<(), String> {
+    Settings::with_write(|s| s.switch_profile(name.as_deref())).map_err(|e| e.to_string())
+}
+
+/// Commande Tauri pour lister les profils, avec le nom du profil actif.
+///
+/// # Retours
+/// Les noms des profils par ordre alphabétique et le profil actif, ou une erreur
+#[tauri::command]
+pub fn list_profiles() -> std::result::Result<(Vec<String>, Option<String>), String> {
+    Settings::with_read(|s| -> Result<_> { Ok((s.list_profiles()?, s.get_active_profile()?)) })
+        .map_err(|e| e.to_string())
+}
+
+/// Commande Tauri pour supprimer un profil.
+///
+/// # Arguments
+/// * `name` - Nom du profil
+///
+/// # Retours
+/// Ok(()) en cas de succès ou une erreur si le profil n'existe pas
+#[tauri::command]
+pub fn delete_profile(name: String) -> std::result::Result<(), String> {
+    Settings::with_write(|s| s.delete_profile(&name)).map_err(|e| e.to_string())
+}
+
+/// Commande Tauri pour exporter les paramètres en un lot JSON.
+///
+/// # Retours
+/// Le lot de paramètres en JSON ou une erreur
+#[tauri::command]
+pub fn export_settings_bundle() -> std::result::Result<String, String> {
+    Settings::with_read(|s| s.export_bundle()).map_err(|e| e.to_string())
+}
+
+/// Commande Tauri pour importer un lot de paramètres écrit par `export_settings_bundle`.
+///
+/// # Arguments
+/// * `json` - Le lot de paramètres en JSON
+///
+/// # Retours
+/// Ok(()) en cas de succès ou une erreur, sans qu'aucun paramètre n'ait été modifié
+#[tauri::command]
+pub fn import_settings_bundle(json: String) -> std::result::Result<(), String> {
+    Settings::with_write(|s| s.import_bundle(&json)).map_err(|e| e.to_string())
+}
+
+/// Commande Tauri pour persister les paramètres sur disque.
+///
+/// # Retours
+/// Ok(()) en cas de succès ou une erreur
+#[tauri::command]
+pub fn flush_settings() -> std::result::Result<(), String> {
+    Settings::with_read(|s| s.flush()).map_err(|e| e.to_string())
+}
+
+/// Commande Tauri pour reconstruire les paramètres par défaut à partir du code.
+///
+/// # Arguments
+/// * `confirm` - Doit valoir `true` pour confirmer l'opération
+///
+/// # Retours
+/// Ok(()) en cas de succès ou une erreur
+#[tauri::command]
+pub fn rebuild_default_params(confirm: bool) -> std::result::Result<(), String> {
+    if !confirm {
+        return Err("Rebuilding default params must be explicitly confirmed".to_string());
+    }
+    Settings::with_write(|s| s.rebuild_default_params()).map_err(|e| e.to_string())
+}
@@ -1,11 +1,265 @@
+use geo::Polygon;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::models::settings::Settings;
+use crate::sampling::{DensityField, DensityGradient, SamplingStrategy};
+
+/// Interprétation du champ `density` des paramètres de végétation.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum DensityMode {
+    /// Distance minimale entre deux points, dans l'unité des coordonnées
+    #[default]
+    MinDistance,
+    /// Nombre de points visé par hectare, les coordonnées étant en mètres
+    PointsPerHectare,
+    /// Nombre exact de points à placer dans le polygone, quelle que soit sa forme ;
+    /// la distance minimale est recherchée pour l'atteindre
+    TargetCount,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct VegetationParams {
     pub vegetation_type: u8,
     pub density: f64,
+    /// Interprétation de `density`
+    #[serde(default)]
+    pub density_mode: DensityMode,
+    pub type_value: u8,
+    /// Décalage aléatoire maximal appliqué à chaque point, sur chaque axe
+    #[serde(default)]
+    pub variation: f64,
+    /// Stratégie de placement des points
+    #[serde(default)]
+    pub strategy: SamplingStrategy,
+    /// Zone dessinée par l'utilisateur : seuls les points à l'intersection
+    /// de cette zone et du polygone sont générés
+    #[serde(default)]
+    pub clip_region: Option<Polygon<f64>>,
+    /// Accepte les points situés exactement sur le bord du polygone
+    #[serde(default)]
+    pub include_boundary: bool,
+    /// Espacement minimal selon l'axe X, `density` si non défini
+    #[serde(default)]
+    pub min_distance_x: Option<f64>,
+    /// Espacement minimal selon l'axe Y, `density` si non défini
+    #[serde(default)]
+    pub min_distance_y: Option<f64>,
+    /// Graine du générateur aléatoire pour un remplissage reproductible, aléatoire si non définie
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Code EPSG des coordonnées du fichier source, pour reprojeter l'aperçu en WGS84
+    #[serde(default)]
+    pub source_epsg: Option<u32>,
+    /// Code EPSG d'une projection métrique dans laquelle les polygones sont reprojetés
+    /// avant l'échantillonnage, pour que les distances soient en mètres ; les points
+    /// reviennent ensuite dans le système de sortie. Requiert `source_epsg`
+    #[serde(default)]
+    pub sampling_epsg: Option<u32>,
+    /// Nombre d'itérations de relaxation de Lloyd appliquées à la distribution, 0 pour aucune
+    #[serde(default)]
+    pub relax_iterations: u32,
+    /// Distance minimale entre un point et les bords du polygone, trous compris, 0 pour aucun retrait
+    #[serde(default)]
+    pub edge_margin: f64,
+    /// Distance minimale entre un point et les anneaux intérieurs (trous) du polygone,
+    /// indépendante de `edge_margin`, 0 pour aucun retrait
+    #[serde(default)]
+    pub interior_buffer: f64,
+    /// Nombre maximal de cellules de la grille d'échantillonnage,
+    /// `DEFAULT_MAX_GRID_CELLS` si non défini
+    #[serde(default)]
+    pub max_grid_cells: Option<usize>,
+    /// Nombre de tentatives autour de chaque point du disque de Poisson,
+    /// `DEFAULT_MAX_ATTEMPTS` si non défini
+    #[serde(default)]
+    pub max_attempts: Option<usize>,
+    /// Nombre de tirages aléatoires du point initial, `DEFAULT_SEED_ATTEMPTS` si non défini
+    #[serde(default)]
+    pub seed_attempts: Option<usize>,
+    /// Nombre maximal de points par polygone, sans limite si non défini
+    #[serde(default)]
+    pub max_points: Option<usize>,
+    /// Découpage de l'emprise en colonnes x rangs de tuiles échantillonnées en parallèle,
+    /// pour les très grands polygones ; échantillonnage d'un seul tenant si non défini
+    #[serde(default)]
+    pub parallel_tiles: Option<(usize, usize)>,
+    /// Zones (bâtiments, routes, plans d'eau) dans lesquelles aucun point n'est placé,
+    /// sans qu'elles soient des trous du polygone
+    #[serde(default)]
+    pub exclusion_zones: Vec<Polygon<f64>>,
+    /// Gradient linéaire appliqué à la distance minimale selon la position des points,
+    /// densité uniforme si non défini
+    #[serde(default)]
+    pub density_gradient: Option<DensityGradient>,
+    /// Variation de la densité à l'intérieur de chaque polygone, uniforme par défaut
+    #[serde(default)]
+    pub density_field: DensityField,
+    /// Pas de la grille à laquelle les coordonnées sont arrondies après la variation,
+    /// sans arrondi si non défini
+    #[serde(default)]
+    pub snap_to: Option<f64>,
+    /// Répare les polygones invalides (anneaux qui se recoupent) avant l'échantillonnage ;
+    /// un polygone irréparable est signalé en erreur
+    #[serde(default)]
+    pub repair_geometry: bool,
+}
+
+impl Default for VegetationParams {
+    fn default() -> Self {
+        VegetationParams {
+            vegetation_type: 1,
+            density: 5.0,
+            density_mode: DensityMode::default(),
+            type_value: 10,
+            variation: 0.0,
+            strategy: SamplingStrategy::default(),
+            clip_region: None,
+            include_boundary: false,
+            min_distance_x: None,
+            min_distance_y: None,
+            seed: None,
+            source_epsg: None,
+            sampling_epsg: None,
+            relax_iterations: 0,
+            edge_margin: 0.0,
+            interior_buffer: 0.0,
+            max_grid_cells: None,
+            max_attempts: None,
+            seed_attempts: None,
+            max_points: None,
+            parallel_tiles: None,
+            exclusion_zones: Vec::new(),
+            density_gradient: None,
+            density_field: DensityField::default(),
+            snap_to: None,
+            repair_geometry: false,
+        }
+    }
+}
+
+impl VegetationParams {
+    /// Retourne des paramètres dont les distances minimales sont multipliées par `factor`,
+    /// pour une distribution moins dense.
+    ///
+    /// # Arguments
+    /// * `factor` - Facteur appliqué aux distances, supérieur à 1 pour réduire la densité
+    pub fn coarsened(&self, factor: f64) -> VegetationParams {
+        let mut param = self.clone();
+        param.density = match param.density_mode {
+            DensityMode::MinDistance => param.density * factor,
+            DensityMode::PointsPerHectare | DensityMode::TargetCount => {
+                param.density / (factor * factor)
+            }
+        };
+        param.min_distance_x = param.min_distance_x.map(|d| d * factor);
+        param.min_distance_y = param.min_distance_y.map(|d| d * factor);
+        match &mut param.strategy {
+            SamplingStrategy::TileableBlueNoise { tile_size } => *tile_size *= factor,
+            SamplingStrategy::Clustered { cluster_radius, .. } => *cluster_radius *= factor,
+            _ => {}
+        }
+        param
+    }
+}
+
+/// Erreur de validation des paramètres de végétation.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ParamsError {
+    #[error("Invalid vegetation type {0}: expected 1 to 127")]
+    InvalidVegetationType(u8),
+    #[error("Invalid density {0}: density must be positive")]
+    InvalidDensity(f64),
+    #[error("Invalid variation {0}: variation cannot be negative")]
+    InvalidVariation(f64),
+    #[error("Invalid type value {0}: expected 1 to 255")]
+    InvalidTypeValue(u8),
+}
+
+/// Construit des paramètres de végétation vérifiés, à partir des valeurs par défaut
+/// ou de paramètres existants.
+///
+/// ```
+/// use vegepoly_lib::models::vegetations::VegetationParams;
+///
+/// let params = VegetationParams::builder()
+///     .vegetation_type(2)
+///     .density(3.5)
+///     .type_value(20)
+///     .build()
+///     .expect("Invalid vegetation parameters");
+/// assert_eq!(params.type_value, 20);
+/// assert!(VegetationParams::builder().density(-1.0).build().is_err());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct VegetationParamsBuilder {
+    params: VegetationParams,
+}
+
+impl From<VegetationParams> for VegetationParamsBuilder {
+    fn from(params: VegetationParams) -> Self {
+        VegetationParamsBuilder { params }
+    }
+}
+
+impl VegetationParamsBuilder {
+    pub fn vegetation_type(mut self, vegetation_type: u8) -> Self {
+        self.params.vegetation_type = vegetation_type;
+        self
+    }
+
+    pub fn density(mut self, density: f64) -> Self {
+        self.params.density = density;
+        self
+    }
+
+    pub fn variation(mut self, variation: f64) -> Self {
+        self.params.variation = variation;
+        self
+    }
+
+    pub fn type_value(mut self, type_value: u8) -> Self {
+        self.params.type_value = type_value;
+        self
+    }
+
+    /// Vérifie les paramètres : type de végétation enregistrable (1 à 127), densité
+    /// strictement positive, variation positive ou nulle et valeur de type non nulle.
+    ///
+    /// # Retours
+    /// Les paramètres, ou la première valeur invalide
+    pub fn build(self) -> Result<VegetationParams, ParamsError> {
+        let params = self.params;
+        if !(1..=i8::MAX as u8).contains(&params.vegetation_type) {
+            return Err(ParamsError::InvalidVegetationType(params.vegetation_type));
+        }
+        if !(params.density.is_finite() && params.density > 0.0) {
+            return Err(ParamsError::InvalidDensity(params.density));
+        }
+        if !(params.variation.is_finite() && params.variation >= 0.0) {
+            return Err(ParamsError::InvalidVariation(params.variation));
+        }
+        if params.type_value == 0 {
+            return Err(ParamsError::InvalidTypeValue(params.type_value));
+        }
+        Ok(params)
+    }
+}
+
+impl VegetationParams {
+    /// Crée un constructeur vérifié à partir des paramètres par défaut.
+    pub fn builder() -> VegetationParamsBuilder {
+        VegetationParamsBuilder::default()
+    }
+}
+
+/// Type de végétation tel que proposé dans la liste de choix de l'interface.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct VegetationTypeInfo {
+    pub vegetation_type: i8,
+    /// Libellé affiché, None si le type n'en a pas
+    pub label: Option<String>,
+    pub density: f64,
     pub type_value: u8,
 }
 
@@ -23,8 +277,7 @@ pub fn get_default_vegetation_params(vegetation_type: u8) -> VegetationParams {
             .unwrap_or(None)
             .unwrap_or(VegetationParams {
                 vegetation_type,
-                density: 5.0,
-                type_value: 10,
+                ..Default::default()
             })
     })
 }
@@ -60,3 +313,13 @@ pub fn get_user_vegetation_params(vegetation_type: i8) -> Option<VegetationParam
             .unwrap_or(None)
     })
 }
+
+/// Commande Tauri pour lister les types de végétation, avec leur libellé et leurs
+/// paramètres effectifs, afin que l'interface construise dynamiquement sa liste de choix.
+///
+/// # Retours
+/// Les types de végétation disponibles ou une erreur
+#[tauri::command]
+pub fn get_vegetation_types() -> Result<Vec<VegetationTypeInfo>, String> {
+    Settings::with_read(|s| s.list_vegetation_types()).map_err(|e| e.to_string())
+}
@@ -0,0 +1,170 @@
+use geo::{CoordsIter, Polygon};
+use serde::Serialize;
+
+use crate::utils::{GeometryColumn, parse_csv_file};
+
+/// Valeur absolue au-delà de laquelle une coordonnée projetée est jugée aberrante (en mètres).
+const MAX_PROJECTED_COORDINATE: f64 = 2.0e7;
+
+/// Système de coordonnées probable d'un fichier, déduit de l'ordre de grandeur des coordonnées.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum CrsGuess {
+    /// Degrés de longitude/latitude (ex. WGS84)
+    Geographic,
+    /// Coordonnées métriques (ex. Lambert-93)
+    Projected,
+    /// Les polygones ne sont pas cohérents entre eux
+    Mixed,
+    /// Aucune coordonnée exploitable
+    Unknown,
+}
+
+/// Rapport de vérification d'un fichier avant export.
+#[derive(Serialize, Debug, Clone)]
+pub struct RiskReport {
+    pub polygon_count: usize,
+    pub likely_crs: CrsGuess,
+    /// Emprise (min_x, min_y, max_x, max_y) des coordonnées valides
+    pub extent: Option<(f64, f64, f64, f64)>,
+    /// Coordonnées non finies ou hors des bornes plausibles
+    pub invalid_coordinates: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+fn looks_geographic(x: f64, y: f64) -> bool {
+    x.abs() <= 180.0 && y.abs() <= 90.0
+}
+
+/// Analyse des polygones : système de coordonnées probable, coordonnées invalides
+/// et cohérence de la densité avec les unités détectées.
+///
+/// # Arguments
+/// * `polygons` - Les polygones à analyser
+/// * `density` - Distance minimale prévue pour l'export, si connue
+///
+/// # Retours
+/// Le rapport d'analyse
+pub fn analyze_polygons(polygons: &[Polygon<f64>], density: Option<f64>) -> RiskReport {
+    let mut invalid_coordinates = Vec::new();
+    let mut extent: Option<(f64, f64, f64, f64)> = None;
+    let (mut geographic, mut projected) = (0, 0);
+
+    for (index, polygon) in polygons.iter().enumerate() {
+        let mut polygon_geographic = true;
+        let mut polygon_valid = false;
+
+        for coord in polygon.coords_iter() {
+            if !coord.x.is_finite() || !coord.y.is_finite() {
+                invalid_coordinates.push(format!(
+                    "Polygon {}: non-finite coordinate ({}, {})",
+                    index + 1,
+                    coord.x,
+                    coord.y
+                ));
+                continue;
+            }
+            if coord.x.abs() > MAX_PROJECTED_COORDINATE || coord.y.abs() > MAX_PROJECTED_COORDINATE
+            {
+                invalid_coordinates.push(format!(
+                    "Polygon {}: out-of-range coordinate ({}, {})",
+                    index + 1,
+                    coord.x,
+                    coord.y
+                ));
+                continue;
+            }
+
+            polygon_valid = true;
+            polygon_geographic &= looks_geographic(coord.x, coord.y);
+            extent = Some(match extent {
+                Some((min_x, min_y, max_x, max_y)) => (
+                    min_x.min(coord.x),
+                    min_y.min(coord.y),
+                    max_x.max(coord.x),
+                    max_y.max(coord.y),
+                ),
+                None => (coord.x, coord.y, coord.x, coord.y),
+            });
+        }
+
+        if polygon_valid {
+            if polygon_geographic {
+                geographic += 1;
+            } else {
+                projected += 1;
+            }
+        }
+    }
+
+    let likely_crs = match (geographic, projected) {
+        (0, 0) => CrsGuess::Unknown,
+        (_, 0) => CrsGuess::Geographic,
+        (0, _) => CrsGuess::Projected,
+        _ => CrsGuess::Mixed,
+    };
+
+    let mut warnings = Vec::new();
+    match likely_crs {
+        CrsGuess::Geographic => warnings.push(
+            "Coordinates look geographic (degrees): density is interpreted in the same units, \
+             reproject to a metric CRS before exporting"
+                .to_string(),
+        ),
+        CrsGuess::Mixed => warnings.push(format!(
+            "{} polygon(s) look geographic and {} look projected: the file mixes coordinate systems",
+            geographic, projected
+        )),
+        CrsGuess::Projected | CrsGuess::Unknown => {}
+    }
+
+    if let (Some(density), Some((min_x, min_y, max_x, max_y))) = (density, extent) {
+        let largest_side = (max_x - min_x).max(max_y - min_y);
+        if likely_crs == CrsGuess::Geographic && density >= 0.01 {
+            warnings.push(format!(
+                "Density {} looks like metres but coordinates are in degrees: \
+                 points would be {}° apart",
+                density, density
+            ));
+        } else if likely_crs == CrsGuess::Projected && density < 0.001 {
+            warnings.push(format!(
+                "Density {} looks like degrees but coordinates are projected",
+                density
+            ));
+        }
+        if density > largest_side {
+            warnings.push(format!(
+                "Density {} is larger than the whole extent ({}): at most one point per polygon",
+                density, largest_side
+            ));
+        }
+    }
+
+    RiskReport {
+        polygon_count: polygons.len(),
+        likely_crs,
+        extent,
+        invalid_coordinates,
+        warnings,
+    }
+}
+
+/// Commande Tauri de vérification d'un fichier avant export.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV
+/// * `density` - Distance minimale prévue pour l'export, si connue
+/// * `geometry_column` - Colonne contenant la géométrie WKT, la première si non définie
+/// * `delimiter` - Délimiteur des colonnes, la tabulation si non défini
+///
+/// # Retours
+/// Le rapport d'analyse ou une erreur de lecture du fichier
+#[tauri::command]
+pub fn risk_report(
+    file_path: &str,
+    density: Option<f64>,
+    geometry_column: Option<GeometryColumn>,
+    delimiter: Option<String>,
+) -> Result<RiskReport, String> {
+    let polygons = parse_csv_file(file_path, geometry_column, delimiter)?;
+    Ok(analyze_polygons(&polygons, density))
+}
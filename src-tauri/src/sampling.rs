@@ -1,9 +1,322 @@
 use core::f64;
 
-use geo::{BoundingRect, Contains, Point, Polygon};
-use rand::Rng;
+use geo::algorithm::bool_ops::FillRule;
+use geo::{
+    Area, BooleanOps, BoundingRect, Buffer, Centroid, Contains, Distance, Euclidean, InteriorPoint,
+    MultiPolygon, Point, Polygon, Validation,
+};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::models::vegetations::VegetationParams;
+use crate::models::export::ExportOptions;
+use crate::models::vegetations::{DensityMode, VegetationParams};
+use crate::utils::{
+    EPSG_WGS84, GeometryColumn, SimplePoint, format_point_row, parse_csv_file, reproject_polygon,
+};
+
+/// Nombre de tentatives pour décaler un point sans le faire sortir du polygone.
+const VARIATION_ATTEMPTS: usize = 10;
+
+/// Distance en deçà de laquelle un point est considéré comme situé sur le bord du polygone.
+const BOUNDARY_EPSILON: f64 = 1e-9;
+
+/// Nombre de tirages pour trouver un point isolé avec `suggest_point`.
+const SUGGEST_ATTEMPTS: usize = 1000;
+
+/// Proportion de la densité d'un empilement aléatoire maximal atteinte par le disque
+/// de Poisson : une distance minimale `d` donne environ `0.7 / d²` points par unité d'aire.
+const POISSON_PACKING_DENSITY: f64 = 0.7;
+
+/// Nombre de tirages par point visé avant d'abandonner l'échantillonnage uniforme,
+/// pour les polygones qui n'occupent qu'une faible part de leur emprise.
+const UNIFORM_ATTEMPTS_PER_POINT: usize = 100;
+
+/// Nombre de mètres carrés dans un hectare.
+const SQUARE_METRES_PER_HECTARE: f64 = 10_000.0;
+
+/// Nombre maximal d'échantillonnages pour trouver la distance minimale donnant
+/// le nombre de points visé.
+const TARGET_COUNT_ITERATIONS: usize = 12;
+
+/// Graine fixe du motif répétable, pour que toutes les tuiles soient identiques d'un export à l'autre.
+const TILE_SEED: u64 = 0x7665_6765_706f_6c79;
+
+/// Nombre maximal de cellules de la grille d'un sampler, au-delà duquel la distance
+/// minimale est jugée trop petite pour l'emprise (chaque cellule occupe 24 octets).
+pub const DEFAULT_MAX_GRID_CELLS: usize = 16_000_000;
+
+/// Nombre de tentatives par défaut autour d'un point actif du disque de Poisson.
+pub const DEFAULT_MAX_ATTEMPTS: usize = 30;
+
+/// Nombre de tirages aléatoires par défaut du point initial du disque de Poisson.
+pub const DEFAULT_SEED_ATTEMPTS: usize = 100;
+
+/// Nombre d'échantillons par axe utilisés pour estimer la cellule de Voronoï d'un point
+/// lors de la relaxation de Lloyd.
+const RELAX_SAMPLES_PER_AXIS: usize = 8;
+
+/// Nombre maximal de points initiaux répartis sur l'emprise quand le nombre de points
+/// est plafonné.
+const MAX_SPREAD_SEEDS: usize = 256;
+
+/// Côté, en distances minimales, de la portion d'emprise couverte par chaque point initial
+/// quand leur nombre est déduit de l'emprise.
+const SEED_SPACING_FACTOR: f64 = 8.0;
+
+/// Nombre de points initiaux répartis sur chaque tuile d'un échantillonnage en parallèle,
+/// pour que les parties du polygone déconnectées par le découpage soient toutes remplies.
+const TILE_SPREAD_SEEDS: usize = 16;
+
+/// Part du carré de la plus grande dimension de l'emprise en deçà de laquelle l'aire
+/// d'un polygone est considérée comme nulle : des sommets presque alignés laissent
+/// une aire résiduelle due aux arrondis, dans laquelle aucun point ne peut être tiré.
+const DEGENERATE_AREA_RATIO: f64 = 1e-12;
+
+/// Erreur d'un polygone dans lequel aucun point n'a pu être placé.
+pub(crate) const NO_POINTS_MESSAGE: &str =
+    "No points could be generated for the given polygon and density.";
+
+/// Stratégie utilisée pour placer les points dans un polygone.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(tag = "kind")]
+pub enum SamplingStrategy {
+    /// Disque de Poisson classique, croissant à partir d'un point initial
+    #[default]
+    PoissonDisk,
+    /// Motif de bruit bleu déterministe répété en tuiles de `tile_size` de côté,
+    /// sans couture visible entre deux tuiles voisines
+    TileableBlueNoise { tile_size: f64 },
+    /// Tirage uniforme sans distance minimale, pour un aspect moins régulier ;
+    /// le nombre de points est celui attendu pour un disque de Poisson de même densité
+    UniformRandom,
+    /// Réseau régulier en rangs et colonnes espacés de la distance minimale, pour les
+    /// plantations de type verger ; chaque nœud est décalé d'au plus la variation
+    JitteredGrid,
+    /// Bosquets d'aspect naturel : au plus `cluster_count` centres répartis par disque
+    /// de Poisson, puis `points_per_cluster` points tirés à moins de `cluster_radius`
+    /// de chaque centre, toujours à la distance minimale les uns des autres
+    Clustered {
+        cluster_count: usize,
+        cluster_radius: f64,
+        points_per_cluster: usize,
+    },
+    /// Échantillonnage du meilleur candidat : pour chaque point, `candidates` tirages
+    /// uniformes sont évalués et le plus éloigné des points déjà placés est retenu, jusqu'au
+    /// nombre de points attendu pour la densité. Plus `candidates` est grand, plus le bruit
+    /// bleu est régulier, au prix d'un temps de calcul proportionnel
+    BestCandidate { candidates: usize },
+}
+
+/// Gradient linéaire de densité : la distance minimale est multipliée par un facteur
+/// qui varie de `start_factor` en `start` à `end_factor` en `end`, par exemple pour
+/// des arbres plus serrés en bas de pente. Au-delà des deux extrémités, le facteur
+/// reste celui de l'extrémité la plus proche.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct DensityGradient {
+    /// Origine du gradient (x, y)
+    pub start: (f64, f64),
+    /// Fin du gradient (x, y)
+    pub end: (f64, f64),
+    /// Facteur appliqué à la distance minimale à l'origine
+    pub start_factor: f64,
+    /// Facteur appliqué à la distance minimale à la fin
+    pub end_factor: f64,
+}
+
+impl DensityGradient {
+    /// Calcule le facteur appliqué à la distance minimale en un point, par projection
+    /// du point sur le segment du gradient.
+    ///
+    /// # Arguments
+    /// * `point` - Le point où évaluer le gradient
+    pub fn factor_at(&self, point: &Point<f64>) -> f64 {
+        let (dx, dy) = (self.end.0 - self.start.0, self.end.1 - self.start.1);
+        let length_squared = dx * dx + dy * dy;
+        if length_squared <= 0.0 {
+            return self.start_factor;
+        }
+        let t =
+            ((point.x() - self.start.0) * dx + (point.y() - self.start.1) * dy) / length_squared;
+        let t = t.clamp(0.0, 1.0);
+        self.start_factor + t * (self.end_factor - self.start_factor)
+    }
+
+    /// Plus grand facteur du gradient, qui fixe la portée de la recherche de voisins.
+    fn max_factor(&self) -> f64 {
+        self.start_factor.max(self.end_factor)
+    }
+}
+
+/// Variation de la densité à l'intérieur de chaque polygone, évaluée d'après sa forme.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum DensityField {
+    /// Même distance minimale dans tout le polygone
+    #[default]
+    Uniform,
+    /// Distance minimale multipliée par un facteur qui croît linéairement de 1 au
+    /// centroïde du polygone à `edge_factor` sur son sommet le plus éloigné, pour
+    /// des lisières plus clairsemées que le cœur du peuplement
+    RadialFromCentroid { edge_factor: f64 },
+}
+
+impl DensityField {
+    /// Évalue le champ pour un polygone.
+    ///
+    /// # Retours
+    /// Le champ radial du polygone, ou None pour une densité uniforme
+    fn resolve(&self, polygon: &Polygon<f64>) -> Option<RadialDensity> {
+        let DensityField::RadialFromCentroid { edge_factor } = *self else {
+            return None;
+        };
+        let center = polygon.centroid()?;
+        let radius = polygon
+            .exterior()
+            .points()
+            .map(|vertex| Euclidean.distance(center, vertex))
+            .fold(0.0, f64::max);
+        (radius > 0.0).then_some(RadialDensity {
+            center,
+            radius,
+            edge_factor,
+        })
+    }
+}
+
+/// Champ radial `DensityField::RadialFromCentroid` évalué pour un polygone.
+#[derive(Clone, Copy, Debug)]
+struct RadialDensity {
+    center: Point<f64>,
+    /// Distance entre le centroïde et le sommet le plus éloigné
+    radius: f64,
+    edge_factor: f64,
+}
+
+impl RadialDensity {
+    /// Facteur appliqué à la distance minimale en un point.
+    fn factor_at(&self, point: &Point<f64>) -> f64 {
+        let t = (Euclidean.distance(self.center, *point) / self.radius).min(1.0);
+        1.0 + t * (self.edge_factor - 1.0)
+    }
+
+    /// Plus grand facteur du champ, qui fixe la portée de la recherche de voisins.
+    fn max_factor(&self) -> f64 {
+        self.edge_factor.max(1.0)
+    }
+}
+
+/// Statistiques d'un échantillonnage, pour juger de la difficulté à placer les points :
+/// un taux de rejet élevé signale une densité irréaliste pour la taille de la parcelle.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct SamplingStats {
+    /// Nombre de points retenus
+    pub generated: usize,
+    /// Candidats rejetés car trop proches d'un point existant
+    pub rejected_distance: usize,
+    /// Candidats rejetés car hors du polygone, de l'emprise ou du retrait aux bords
+    pub rejected_outside: usize,
+    /// Nombre total de candidats évalués
+    pub total_attempts: usize,
+    /// Candidats acceptés ; avec les rejets, ils totalisent `total_attempts`
+    #[serde(default)]
+    pub accepted: usize,
+    /// Points actifs du disque de Poisson désactivés faute de voisin valide
+    #[serde(default)]
+    pub exhausted_active_points: usize,
+    /// Candidats évalués comme points initiaux du disque de Poisson
+    #[serde(default)]
+    pub seed_attempts: usize,
+}
+
+impl SamplingStats {
+    /// Ajoute les compteurs d'un autre échantillonnage, par exemple d'une autre partie
+    /// du polygone. Le nombre de points retenus n'est pas cumulé.
+    pub fn merge(&mut self, other: &SamplingStats) {
+        self.rejected_distance += other.rejected_distance;
+        self.rejected_outside += other.rejected_outside;
+        self.total_attempts += other.total_attempts;
+        self.accepted += other.accepted;
+        self.exhausted_active_points += other.exhausted_active_points;
+        self.seed_attempts += other.seed_attempts;
+    }
+
+    /// Part des candidats rejetés, entre 0 et 1.
+    pub fn rejection_ratio(&self) -> f64 {
+        match self.total_attempts {
+            0 => 0.0,
+            total => (self.rejected_distance + self.rejected_outside) as f64 / total as f64,
+        }
+    }
+}
+
+/// Configuration complète d'un sampler, indépendante des paramètres de végétation
+/// et de Tauri, pour utiliser l'échantillonnage comme une simple bibliothèque Rust
+/// (outil en ligne de commande, traitement par lots).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SamplerConfig {
+    /// Distance minimale entre deux points, selon l'axe X pour un espacement anisotrope
+    pub min_distance: f64,
+    /// Distance minimale selon l'axe Y, `min_distance` si non définie
+    pub min_distance_y: Option<f64>,
+    /// Nombre de tentatives autour de chaque point actif du disque de Poisson
+    pub max_attempts: usize,
+    /// Nombre de tirages aléatoires du point initial
+    pub seed_attempts: usize,
+    /// Graine du générateur aléatoire, générateur du thread si non définie
+    pub seed: Option<u64>,
+    /// Stratégie de placement des points
+    pub strategy: SamplingStrategy,
+    /// Accepte les points situés exactement sur le bord du polygone
+    pub include_boundary: bool,
+    /// Distance minimale entre un point et les bords du polygone, trous compris
+    pub edge_margin: f64,
+    /// Distance minimale entre un point et les trous du polygone
+    pub interior_buffer: f64,
+    /// Zones dans lesquelles aucun point n'est placé
+    pub exclusions: Vec<Polygon<f64>>,
+    /// Gradient appliqué à la distance minimale, densité uniforme si non défini
+    pub density_gradient: Option<DensityGradient>,
+    /// Variation de la distance minimale à l'intérieur du polygone
+    pub density_field: DensityField,
+    /// Décalage maximal des nœuds du réseau de la stratégie `JitteredGrid`
+    pub jitter: f64,
+    /// Nombre maximal de points de la distribution, sans limite si non défini
+    pub max_points: Option<usize>,
+    /// Nombre maximal de cellules de la grille d'échantillonnage
+    pub max_grid_cells: usize,
+    /// Nombre de points initiaux du disque de Poisson, déduit de l'emprise si non défini
+    pub initial_seeds: Option<usize>,
+}
+
+impl SamplerConfig {
+    /// Crée une configuration de disque de Poisson isotrope, sans retrait ni limite,
+    /// avec les nombres de tentatives par défaut.
+    ///
+    /// # Arguments
+    /// * `min_distance` - Distance minimale entre deux points
+    pub fn new(min_distance: f64) -> Self {
+        SamplerConfig {
+            min_distance,
+            min_distance_y: None,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            seed_attempts: DEFAULT_SEED_ATTEMPTS,
+            seed: None,
+            strategy: SamplingStrategy::default(),
+            include_boundary: false,
+            edge_margin: 0.0,
+            interior_buffer: 0.0,
+            exclusions: Vec::new(),
+            density_gradient: None,
+            density_field: DensityField::Uniform,
+            jitter: 0.0,
+            max_points: None,
+            max_grid_cells: DEFAULT_MAX_GRID_CELLS,
+            initial_seeds: None,
+        }
+    }
+}
 
 /// Structure qui implémente l'algorithme d'échantillonnage de distribution spatiale.
 /// Utilise une grille pour optimiser la détection de voisinage lors de l'échantillonnage.
@@ -12,20 +325,53 @@ pub struct SpatialDistributionSampler {
     min_distance: f64,
     /// Nombre maximum de tentatives pour trouver un nouveau point valide
     max_attempts: usize,
+    /// Nombre de tirages aléatoires pour trouver le point initial du disque de Poisson
+    seed_attempts: usize,
     /// Taille de la cellule de la grille (dérivée de la distance minimale)
     cell_size: f64,
     /// Largeur de la grille en nombre de cellules
     grid_width: usize,
     /// Hauteur de la grille en nombre de cellules
     grid_height: usize,
-    /// Grille pour optimiser la recherche de voisins (stocke les indices des points)
-    grid: Vec<Option<usize>>,
+    /// Grille pour optimiser la recherche de voisins (stocke les indices de tous les points
+    /// de chaque cellule), allouée au premier point enregistré
+    grid: Vec<Vec<usize>>,
     /// Collection des points générés
     points: Vec<Point<f64>>,
     /// Indices des points actifs pour la génération de nouveaux points
     active_indices: Vec<usize>,
     /// Limites de la zone d'échantillonnage (min_x, min_y, max_x, max_y)
     bounds: (f64, f64, f64, f64),
+    /// Stratégie de placement des points
+    strategy: SamplingStrategy,
+    /// Accepte les points situés exactement sur le bord du polygone
+    include_boundary: bool,
+    /// Espacements minimaux (x, y) ; égaux à `min_distance` pour une distribution isotrope
+    spacing: (f64, f64),
+    /// Graine du générateur aléatoire, pour une distribution reproductible ;
+    /// sans graine, le générateur du thread est utilisé
+    seed: Option<u64>,
+    /// Décalage maximal des nœuds du réseau, sur chaque axe, pour `JitteredGrid`
+    jitter: f64,
+    /// Distance minimale entre un point et les bords du polygone, trous compris
+    edge_margin: f64,
+    /// Distance minimale entre un point et les anneaux intérieurs du polygone
+    interior_buffer: f64,
+    /// Zones où aucun point ne doit être placé
+    exclusions: Vec<Polygon<f64>>,
+    /// Gradient appliqué à la distance minimale, uniforme si None
+    density_gradient: Option<DensityGradient>,
+    /// Variation de la distance minimale à l'intérieur du polygone
+    density_field: DensityField,
+    /// Champ radial évalué pour le polygone en cours d'échantillonnage
+    radial_density: Option<RadialDensity>,
+    /// Statistiques de la dernière distribution générée
+    stats: SamplingStats,
+    /// Nombre maximal de points de la distribution, sans limite si None
+    max_points: Option<usize>,
+    /// Nombre minimal de points initiaux du disque de Poisson répartis sur l'emprise,
+    /// déduit de l'emprise si non défini
+    spread_seeds: Option<usize>,
 }
 
 impl SpatialDistributionSampler {
@@ -35,188 +381,2448 @@ impl SpatialDistributionSampler {
     /// * `min_distance` - Distance minimale entre deux points quelconques
     /// * `bounds` - Tuple (min_x, min_y, max_x, max_y) définissant les limites de la zone
     pub fn new(min_distance: f64, bounds: (f64, f64, f64, f64)) -> Self {
-        let (min_x, min_y, max_x, max_y) = bounds;
-        let width = max_x - min_x;
-        let height = max_y - min_y;
+        Self::new_anisotropic(min_distance, min_distance, bounds)
+    }
 
+    /// Crée un sampler dont l'espacement minimal diffère selon les axes, par exemple
+    /// pour des rangs de vigne plus espacés entre eux que les pieds sur un même rang.
+    /// Deux points sont trop proches s'ils sont à l'intérieur de l'ellipse de demi-axes
+    /// `spacing_x` et `spacing_y` centrée sur l'un d'eux.
+    ///
+    /// # Arguments
+    /// * `spacing_x` - Espacement minimal selon l'axe X
+    /// * `spacing_y` - Espacement minimal selon l'axe Y
+    /// * `bounds` - Tuple (min_x, min_y, max_x, max_y) définissant les limites de la zone
+    pub fn new_anisotropic(spacing_x: f64, spacing_y: f64, bounds: (f64, f64, f64, f64)) -> Self {
+        let min_distance = spacing_x.min(spacing_y);
         let cell_size = min_distance / std::f64::consts::SQRT_2;
-
-        let grid_width = (width / cell_size).ceil() as usize + 1;
-        let grid_height = (height / cell_size).ceil() as usize + 1;
+        let (grid_width, grid_height) = grid_dimensions(cell_size, bounds);
 
         SpatialDistributionSampler {
             min_distance,
-            max_attempts: 30,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            seed_attempts: DEFAULT_SEED_ATTEMPTS,
             cell_size,
             grid_width,
             grid_height,
-            grid: vec![None; grid_width * grid_height],
+            grid: Vec::new(),
             points: Vec::new(),
             active_indices: Vec::new(),
             bounds,
+            strategy: SamplingStrategy::default(),
+            include_boundary: false,
+            spacing: (spacing_x, spacing_y),
+            seed: None,
+            jitter: 0.0,
+            edge_margin: 0.0,
+            interior_buffer: 0.0,
+            exclusions: Vec::new(),
+            density_gradient: None,
+            density_field: DensityField::Uniform,
+            radial_density: None,
+            stats: SamplingStats::default(),
+            max_points: None,
+            spread_seeds: None,
         }
     }
 
-    /// Génère une distribution de points à l'intérieur du polygone donné.
-    /// Utilise un algorithme de disque de Poisson modifié pour respecter la distance minimale.
+    /// Crée un sampler comme `new_anisotropic`, après avoir vérifié les espacements
+    /// et la taille de la grille, pour ne pas allouer une grille démesurée
+    /// quand la distance minimale est infime devant l'emprise.
     ///
     /// # Arguments
-    /// * `polygon` - Le polygone dans lequel générer les points
+    /// * `spacing_x` - Espacement minimal selon l'axe X
+    /// * `spacing_y` - Espacement minimal selon l'axe Y
+    /// * `bounds` - Tuple (min_x, min_y, max_x, max_y) définissant les limites de la zone
+    /// * `max_grid_cells` - Nombre maximal de cellules de la grille
     ///
     /// # Retours
-    /// Un vecteur de points respectant la distance minimale et contenus dans le polygone
-    pub fn generate_distribution(&mut self, polygon: &Polygon<f64>) -> Vec<Point<f64>> {
-        let mut rng = rand::rng();
-        let (min_x, min_y, max_x, max_y) = self.bounds;
-
-        for _ in 0..100 {
-            let x = min_x + rng.random::<f64>() * (max_x - min_x);
-            let y = min_y + rng.random::<f64>() * (max_y - min_y);
-            let point = Point::new(x, y);
+    /// Le sampler, ou une erreur si un espacement n'est pas positif ou si la grille
+    /// dépasserait `max_grid_cells` cellules
+    pub fn try_new_anisotropic(
+        spacing_x: f64,
+        spacing_y: f64,
+        bounds: (f64, f64, f64, f64),
+        max_grid_cells: usize,
+    ) -> Result<Self, String> {
+        if !(spacing_x.is_finite() && spacing_x > 0.0 && spacing_y.is_finite() && spacing_y > 0.0) {
+            return Err(format!(
+                "Minimum distance must be positive (got {} x {}).",
+                spacing_x, spacing_y
+            ));
+        }
+        let sampler = Self::new_anisotropic(spacing_x, spacing_y, bounds);
+        let cells = sampler.grid_width.saturating_mul(sampler.grid_height);
+        if cells > max_grid_cells {
+            return Err(format!(
+                "Minimum distance {} is too small for the polygon extent: \
+                 the sampling grid would need {} cells (maximum {}).",
+                sampler.min_distance, cells, max_grid_cells
+            ));
+        }
+        Ok(sampler)
+    }
 
-            if polygon.contains(&point) {
-                self.add_point(point);
-                break;
+    /// Vide le sampler pour échantillonner une nouvelle emprise avec la même configuration.
+    /// Les allocations des points, des points actifs et des cellules de la grille sont
+    /// conservées, ce qui évite de les refaire pour chacun de nombreux petits polygones.
+    /// La taille de la grille n'est pas vérifiée comme dans `try_new_anisotropic`.
+    ///
+    /// # Arguments
+    /// * `bounds` - Tuple (min_x, min_y, max_x, max_y) de la nouvelle emprise
+    pub fn reset(&mut self, bounds: (f64, f64, f64, f64)) {
+        let (grid_width, grid_height) = grid_dimensions(self.cell_size, bounds);
+        self.bounds = bounds;
+        self.grid_width = grid_width;
+        self.grid_height = grid_height;
+        self.points.clear();
+        self.active_indices.clear();
+        self.stats = SamplingStats::default();
+        // Une grille encore jamais allouée le sera au premier point enregistré
+        if !self.grid.is_empty() {
+            let cells = grid_width.saturating_mul(grid_height);
+            self.grid.truncate(cells);
+            for cell in &mut self.grid {
+                cell.clear();
             }
+            self.grid.resize(cells, Vec::new());
         }
+    }
 
-        if self.active_indices.is_empty() {
-            return Vec::new();
+    /// Reprend les allocations d'un sampler qui a déjà servi, pour un export
+    /// qui crée un sampler par polygone. La configuration du sampler est conservée.
+    ///
+    /// # Arguments
+    /// * `previous` - Le sampler dont les allocations sont reprises
+    pub fn reusing(mut self, previous: SpatialDistributionSampler) -> Self {
+        self.grid = previous.grid;
+        self.points = previous.points;
+        self.active_indices = previous.active_indices;
+        self.reset(self.bounds);
+        self
+    }
+
+    /// Crée un sampler à partir d'une configuration complète, sans passer par
+    /// les paramètres de végétation.
+    ///
+    /// # Arguments
+    /// * `config` - La configuration du sampler
+    /// * `bounds` - Tuple (min_x, min_y, max_x, max_y) définissant les limites de la zone
+    ///
+    /// # Retours
+    /// Le sampler, ou une erreur si une distance n'est pas positive ou si la grille
+    /// dépasserait `config.max_grid_cells` cellules
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use geo::{LineString, Polygon};
+    /// use vegepoly_lib::sampling::{
+    ///     SamplerConfig, SpatialDistributionSampler, calculate_polygon_bounds,
+    /// };
+    ///
+    /// let polygon = Polygon::new(
+    ///     LineString::from(vec![(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)]),
+    ///     vec![],
+    /// );
+    /// let bounds = calculate_polygon_bounds(&polygon).expect("Polygon has no bounds");
+    /// let config = SamplerConfig {
+    ///     seed: Some(42),
+    ///     edge_margin: 5.0,
+    ///     ..SamplerConfig::new(10.0)
+    /// };
+    ///
+    /// let points = SpatialDistributionSampler::from_config(config, bounds)
+    ///     .expect("Invalid sampler configuration")
+    ///     .generate_distribution(&polygon);
+    /// assert!(!points.is_empty());
+    /// assert!(points.iter().all(|point| {
+    ///     (5.0..=95.0).contains(&point.x()) && (5.0..=95.0).contains(&point.y())
+    /// }));
+    /// ```
+    pub fn from_config(
+        config: SamplerConfig,
+        bounds: (f64, f64, f64, f64),
+    ) -> Result<Self, String> {
+        let sampler = Self::try_new_anisotropic(
+            config.min_distance,
+            config.min_distance_y.unwrap_or(config.min_distance),
+            bounds,
+            config.max_grid_cells,
+        )?
+        .with_strategy(config.strategy)
+        .with_include_boundary(config.include_boundary)
+        .with_edge_margin(config.edge_margin)
+        .with_interior_buffer(config.interior_buffer)
+        .with_exclusions(config.exclusions)
+        .with_density_gradient(config.density_gradient)
+        .with_density_field(config.density_field)
+        .with_jitter(config.jitter)
+        .with_attempts(config.max_attempts, config.seed_attempts)
+        .with_max_points(config.max_points);
+        let sampler = match config.initial_seeds {
+            Some(initial_seeds) => sampler.with_spread_seeds(initial_seeds),
+            None => sampler,
+        };
+        Ok(match config.seed {
+            Some(seed) => sampler.seeded(seed),
+            None => sampler,
+        })
+    }
+
+    /// Crée un sampler dont la distribution est reproductible : la même graine
+    /// et le même polygone donnent toujours les mêmes points.
+    ///
+    /// # Arguments
+    /// * `min_distance` - Distance minimale entre deux points quelconques
+    /// * `bounds` - Tuple (min_x, min_y, max_x, max_y) définissant les limites de la zone
+    /// * `seed` - Graine du générateur aléatoire
+    pub fn with_seed(min_distance: f64, bounds: (f64, f64, f64, f64), seed: u64) -> Self {
+        Self::new(min_distance, bounds).seeded(seed)
+    }
+
+    /// Fixe la graine du générateur aléatoire utilisé par `generate_distribution`.
+    ///
+    /// # Arguments
+    /// * `seed` - Graine du générateur aléatoire
+    pub fn seeded(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Crée le générateur aléatoire d'une distribution : initialisé par la graine
+    /// si elle est définie, par le générateur du thread sinon.
+    fn make_rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_rng(&mut rand::rng()),
         }
+    }
 
-        while !self.active_indices.is_empty() {
-            let idx = rng.random_range(0..self.active_indices.len());
-            let active_idx = self.active_indices[idx];
-            let active_point = self.points[active_idx];
+    /// Statistiques de la dernière distribution générée.
+    pub fn stats(&self) -> SamplingStats {
+        self.stats
+    }
 
-            let mut found_new_point = false;
+    /// Définit la stratégie de placement des points.
+    ///
+    /// # Arguments
+    /// * `strategy` - La stratégie à utiliser
+    pub fn with_strategy(mut self, strategy: SamplingStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
 
-            for _ in 0..self.max_attempts {
-                let angle = 2.0 * std::f64::consts::PI * rng.random::<f64>();
-                let radius = self.min_distance + self.min_distance * rng.random::<f64>();
+    /// Définit si les points situés exactement sur le bord du polygone sont acceptés.
+    /// Utile pour les motifs réguliers dont des rangées entières tombent sur les bords.
+    ///
+    /// # Arguments
+    /// * `include_boundary` - `true` pour accepter les points du bord
+    pub fn with_include_boundary(mut self, include_boundary: bool) -> Self {
+        self.include_boundary = include_boundary;
+        self
+    }
 
-                let new_x = active_point.x() + radius * angle.cos();
-                let new_y = active_point.y() + radius * angle.sin();
+    /// Définit le décalage maximal des nœuds du réseau de la stratégie `JitteredGrid`.
+    ///
+    /// # Arguments
+    /// * `jitter` - Décalage maximal sur chaque axe, 0 pour un réseau parfaitement régulier
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter;
+        self
+    }
 
-                if new_x < min_x || new_x >= max_x || new_y < min_y || new_y >= max_y {
-                    continue;
-                }
+    /// Définit la distance minimale entre un point et les bords du polygone, pour
+    /// qu'aucun point ne soit placé contre une parcelle voisine ou au bord d'un trou.
+    ///
+    /// # Arguments
+    /// * `edge_margin` - Distance minimale aux bords, 0 pour aucun retrait
+    pub fn with_edge_margin(mut self, edge_margin: f64) -> Self {
+        self.edge_margin = edge_margin;
+        self
+    }
 
-                let new_point = Point::new(new_x, new_y);
+    /// Définit la distance minimale entre un point et les trous du polygone, par exemple
+    /// pour tenir les arbres à l'écart des berges d'un étang tout en remplissant
+    /// la parcelle jusqu'à son bord extérieur.
+    ///
+    /// # Arguments
+    /// * `interior_buffer` - Distance minimale aux anneaux intérieurs, 0 pour aucun retrait
+    pub fn with_interior_buffer(mut self, interior_buffer: f64) -> Self {
+        self.interior_buffer = interior_buffer;
+        self
+    }
 
-                if polygon.contains(&new_point) && self.is_point_valid(&new_point) {
-                    self.add_point(new_point);
-                    found_new_point = true;
-                    break;
-                }
-            }
+    /// Définit les zones d'exclusion (bâtiments, routes, plans d'eau) dans lesquelles
+    /// aucun point n'est placé, même si elles ne sont pas des trous du polygone.
+    ///
+    /// # Arguments
+    /// * `exclusions` - Polygones d'exclusion, vide pour aucune exclusion
+    pub fn with_exclusions(mut self, exclusions: Vec<Polygon<f64>>) -> Self {
+        self.exclusions = exclusions;
+        self
+    }
 
-            if !found_new_point {
-                self.active_indices.swap_remove(idx);
-            }
+    /// Fait varier la distance minimale selon la position du candidat. La grille reste
+    /// celle de la distance de base : une cellule peut alors contenir plusieurs points.
+    ///
+    /// # Arguments
+    /// * `density_gradient` - Gradient de la distance minimale, None pour une densité uniforme
+    pub fn with_density_gradient(mut self, density_gradient: Option<DensityGradient>) -> Self {
+        self.density_gradient = density_gradient;
+        self
+    }
+
+    /// Fait varier la distance minimale selon la position du candidat dans le polygone
+    /// échantillonné, évaluée à chaque génération d'après la forme du polygone.
+    ///
+    /// # Arguments
+    /// * `density_field` - Variation de la distance minimale à l'intérieur du polygone
+    pub fn with_density_field(mut self, density_field: DensityField) -> Self {
+        self.density_field = density_field;
+        self
+    }
+
+    /// Évalue le champ de densité pour le polygone à échantillonner.
+    fn resolve_density_field(&mut self, polygon: &Polygon<f64>) {
+        self.radial_density = self.density_field.resolve(polygon);
+    }
+
+    /// Facteur appliqué à la distance minimale en un point, 1 sans gradient ni champ.
+    fn local_factor(&self, point: &Point<f64>) -> f64 {
+        let gradient = self
+            .density_gradient
+            .as_ref()
+            .map_or(1.0, |gradient| gradient.factor_at(point));
+        let radial = self
+            .radial_density
+            .as_ref()
+            .map_or(1.0, |radial| radial.factor_at(point));
+        gradient * radial
+    }
+
+    /// Définit le nombre de tentatives autour d'un point actif avant de le désactiver,
+    /// et le nombre de tirages aléatoires du point initial.
+    ///
+    /// # Arguments
+    /// * `max_attempts` - Tentatives autour de chaque point actif
+    /// * `seed_attempts` - Tirages du point initial avant de se rabattre sur un point intérieur
+    pub fn with_attempts(mut self, max_attempts: usize, seed_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self.seed_attempts = seed_attempts;
+        self
+    }
+
+    /// Plafonne le nombre de points de la distribution : la génération s'arrête dès que
+    /// le plafond est atteint, pour qu'une grande parcelle à faible distance minimale
+    /// n'épuise pas la mémoire.
+    ///
+    /// # Arguments
+    /// * `max_points` - Nombre maximal de points, None pour aucune limite
+    pub fn with_max_points(mut self, max_points: Option<usize>) -> Self {
+        self.max_points = max_points;
+        self
+    }
+
+    /// Fait partir le disque de Poisson d'au moins `spread_seeds` points initiaux
+    /// répartis sur l'emprise, plutôt que du nombre déduit de l'emprise.
+    ///
+    /// # Arguments
+    /// * `spread_seeds` - Nombre de cellules de l'emprise où tirer un point initial,
+    ///   0 pour partir d'un seul point
+    pub fn with_spread_seeds(mut self, spread_seeds: usize) -> Self {
+        self.spread_seeds = Some(spread_seeds);
+        self
+    }
+
+    /// Nombre de points initiaux déduit de l'emprise : un par carré de
+    /// `SEED_SPACING_FACTOR` distances minimales de côté, pour que chaque lobe
+    /// d'un polygone concave reçoive le sien.
+    fn default_spread_seeds(&self) -> usize {
+        let (min_x, min_y, max_x, max_y) = self.bounds;
+        let (spacing_x, spacing_y) = self.spacing;
+        let seed_area = SEED_SPACING_FACTOR * spacing_x * SEED_SPACING_FACTOR * spacing_y;
+        let count = ((max_x - min_x) * (max_y - min_y) / seed_area).ceil();
+        if count.is_finite() {
+            (count as usize).clamp(1, MAX_SPREAD_SEEDS)
+        } else {
+            MAX_SPREAD_SEEDS
         }
+    }
 
-        self.points.clone()
+    /// Indique si la distribution a atteint le nombre maximal de points.
+    fn is_full(&self) -> bool {
+        self.max_points.is_some_and(|max| self.points.len() >= max)
     }
 
-    /// Ajoute un point à la distribution et met à jour les structures de données.
+    /// Indique si un point peut être placé dans le polygone : il doit y être contenu,
+    /// respecter les retraits aux bords et aux trous et rester hors des zones d'exclusion.
+    /// Les calculs de distance ne sont faits qu'avec un retrait non nul.
     ///
     /// # Arguments
-    /// * `point` - Le point à ajouter
-    fn add_point(&mut self, point: Point<f64>) {
-        let idx = self.points.len();
-        self.points.push(point);
+    /// * `polygon` - Le polygone à remplir
+    /// * `point` - Le point candidat
+    fn accepts(&self, polygon: &Polygon<f64>, point: &Point<f64>) -> bool {
+        point_in_polygon(polygon, point, self.include_boundary)
+            && respects_buffers(polygon, point, self.edge_margin, self.interior_buffer)
+            && !in_exclusion_zone(&self.exclusions, point)
+    }
 
-        self.active_indices.push(idx);
+    /// Évalue un point candidat en tenant à jour les statistiques de l'échantillonnage.
+    ///
+    /// # Arguments
+    /// * `polygon` - Le polygone à remplir
+    /// * `point` - Le point candidat
+    /// * `check_distance` - `true` pour vérifier aussi la distance minimale
+    ///
+    /// # Retours
+    /// `true` si le point peut être ajouté à la distribution
+    fn evaluate(
+        &mut self,
+        polygon: &Polygon<f64>,
+        point: &Point<f64>,
+        check_distance: bool,
+    ) -> bool {
+        self.stats.total_attempts += 1;
+        if !self.accepts(polygon, point) {
+            self.stats.rejected_outside += 1;
+            return false;
+        }
+        if check_distance && !self.is_point_valid(point) {
+            self.stats.rejected_distance += 1;
+            return false;
+        }
+        self.stats.accepted += 1;
+        true
+    }
 
-        let (min_x, min_y, _, _) = self.bounds;
-        let grid_x = ((point.x() - min_x) / self.cell_size) as usize;
-        let grid_y = ((point.y() - min_y) / self.cell_size) as usize;
+    /// Génère une distribution de points à l'intérieur du polygone donné,
+    /// selon la stratégie du sampler. Les tirages utilisent un générateur initialisé
+    /// par la graine du sampler, ou le générateur du thread sans graine.
+    ///
+    /// # Arguments
+    /// * `polygon` - Le polygone dans lequel générer les points
+    ///
+    /// # Retours
+    /// Un vecteur de points respectant la distance minimale et contenus dans le polygone
+    pub fn generate_distribution(&mut self, polygon: &Polygon<f64>) -> Vec<Point<f64>> {
+        self.generate_distribution_with_stats(polygon).0
+    }
 
-        if grid_x < self.grid_width && grid_y < self.grid_height {
-            let grid_idx = grid_y * self.grid_width + grid_x;
-            if grid_idx < self.grid.len() {
-                self.grid[grid_idx] = Some(idx);
-            }
+    /// Génère une distribution comme `generate_distribution` et retourne les statistiques
+    /// des candidats évalués.
+    ///
+    /// # Arguments
+    /// * `polygon` - Le polygone dans lequel générer les points
+    ///
+    /// # Retours
+    /// Les points générés et les statistiques de l'échantillonnage
+    pub fn generate_distribution_with_stats(
+        &mut self,
+        polygon: &Polygon<f64>,
+    ) -> (Vec<Point<f64>>, SamplingStats) {
+        let mut rng = self.make_rng();
+        let points = self.generate_distribution_with_rng(polygon, &mut rng);
+        (points, self.stats)
+    }
+
+    /// Génère une distribution avec un générateur aléatoire fourni par l'appelant,
+    /// par exemple pour rejouer un placement précis dans un test. La graine du sampler
+    /// est ignorée ; les statistiques restent disponibles par `stats`.
+    ///
+    /// # Arguments
+    /// * `polygon` - Le polygone dans lequel générer les points
+    /// * `rng` - Générateur aléatoire des tirages
+    ///
+    /// # Retours
+    /// Un vecteur de points respectant la distance minimale et contenus dans le polygone
+    pub fn generate_distribution_with_rng(
+        &mut self,
+        polygon: &Polygon<f64>,
+        rng: &mut impl Rng,
+    ) -> Vec<Point<f64>> {
+        self.stats = SamplingStats::default();
+        self.resolve_density_field(polygon);
+        if !self.has_area() {
+            eprintln!(
+                "Warning: sampling bounds {:?} have no area, no point generated",
+                self.bounds
+            );
+            return Vec::new();
         }
+        let points = match self.strategy {
+            SamplingStrategy::PoissonDisk => self.generate_poisson_disk(polygon, rng),
+            SamplingStrategy::TileableBlueNoise { tile_size } => {
+                self.generate_tileable(polygon, tile_size)
+            }
+            SamplingStrategy::UniformRandom => self.generate_uniform(polygon, rng),
+            SamplingStrategy::JitteredGrid => self.generate_jittered_grid(polygon, rng),
+            SamplingStrategy::Clustered {
+                cluster_count,
+                cluster_radius,
+                points_per_cluster,
+            } => self.generate_clustered(
+                polygon,
+                cluster_count,
+                cluster_radius,
+                points_per_cluster,
+                rng,
+            ),
+            SamplingStrategy::BestCandidate { candidates } => {
+                self.generate_best_candidate(polygon, candidates, rng)
+            }
+        };
+        self.stats.generated = points.len();
+        points
     }
 
-    /// Vérifie si un point est valide en termes de distance minimale avec les points existants.
+    /// Retourne les points de la distribution au fil de leur génération, pour les écrire
+    /// sans attendre la fin du remplissage. Le disque de Poisson est généré pas à pas ;
+    /// les autres stratégies génèrent leur distribution au premier point demandé.
+    /// Les points produits sont ceux que retournerait `generate_distribution`.
     ///
     /// # Arguments
-    /// * `point` - Le point à vérifier
+    /// * `polygon` - Le polygone dans lequel générer les points
     ///
     /// # Retours
-    /// `true` si le point respecte la distance minimale par rapport à tous les points existants
-    fn is_point_valid(&self, point: &Point<f64>) -> bool {
-        let (min_x, min_y, _, _) = self.bounds;
+    /// Un itérateur sur les points générés
+    pub fn iter_points<'a>(
+        &'a mut self,
+        polygon: &'a Polygon<f64>,
+    ) -> impl Iterator<Item = Point<f64>> + 'a {
+        let rng = self.make_rng();
+        self.resolve_density_field(polygon);
+        DistributionPoints {
+            sampler: self,
+            polygon,
+            rng,
+            next_index: 0,
+            stage: DistributionStage::Start,
+        }
+    }
 
-        let grid_x = ((point.x() - min_x) / self.cell_size) as usize;
-        let grid_y = ((point.y() - min_y) / self.cell_size) as usize;
+    /// Indique si l'emprise du sampler a une aire : une emprise plate, réduite à un point
+    /// ou inversée ne peut contenir aucun point.
+    fn has_area(&self) -> bool {
+        let (min_x, min_y, max_x, max_y) = self.bounds;
+        max_x - min_x > 0.0 && max_y - min_y > 0.0
+    }
+
+    /// Génère une distribution par disque de Poisson modifié à partir d'un point initial.
+    fn generate_poisson_disk(
+        &mut self,
+        polygon: &Polygon<f64>,
+        rng: &mut impl Rng,
+    ) -> Vec<Point<f64>> {
+        if self.is_full() {
+            return self.points.clone();
+        }
+        if !self.seed_poisson_disk(polygon, rng) {
+            return Vec::new();
+        }
+        while self.grow_poisson_disk(polygon, rng) {}
 
-        let start_x = grid_x.saturating_sub(1);
-        let start_y = grid_y.saturating_sub(1);
-        let end_x = (grid_x + 1).min(self.grid_width - 1);
-        let end_y = (grid_y + 1).min(self.grid_height - 1);
+        self.points.clone()
+    }
 
-        for y in start_y..=end_y {
-            for x in start_x..=end_x {
-                let idx = y * self.grid_width + x;
-                if idx < self.grid.len()
-                    && let Some(point_idx) = self.grid[idx]
-                {
-                    let other = &self.points[point_idx];
-                    let dx = point.x() - other.x();
-                    let dy = point.y() - other.y();
-                    let dist_sq = dx * dx + dy * dy;
+    /// Place le ou les points initiaux du disque de Poisson.
+    ///
+    /// # Retours
+    /// `true` si au moins un point initial a pu être placé
+    fn seed_poisson_disk(&mut self, polygon: &Polygon<f64>, rng: &mut impl Rng) -> bool {
+        let (min_x, min_y, max_x, max_y) = self.bounds;
+
+        // Autour d'un seul point initial, la croissance peut ne jamais atteindre un lobe
+        // d'un polygone concave, et s'arrêterait dans un coin du polygone une fois
+        // plafonnée : elle part alors de points répartis sur toute l'emprise
+        let spread_seeds = self
+            .max_points
+            .map_or(0, |max_points| max_points.min(MAX_SPREAD_SEEDS))
+            .max(
+                self.spread_seeds
+                    .unwrap_or_else(|| self.default_spread_seeds()),
+            );
+        if spread_seeds > 0 {
+            self.seed_spread(polygon, spread_seeds, rng);
+        }
+
+        if self.active_indices.is_empty() {
+            for _ in 0..self.seed_attempts {
+                let x = min_x + rng.random::<f64>() * (max_x - min_x);
+                let y = min_y + rng.random::<f64>() * (max_y - min_y);
+                let point = Point::new(x, y);
+
+                self.stats.seed_attempts += 1;
+                if self.evaluate(polygon, &point, false) {
+                    self.add_point(point);
+                    break;
+                }
+            }
+        }
 
-                    if dist_sq < self.min_distance * self.min_distance {
+        // Un polygone concave qui n'occupe qu'une faible part de son emprise peut
+        // échapper aux tirages : le point initial est alors un point intérieur garanti
+        if self.active_indices.is_empty() {
+            match polygon.interior_point() {
+                Some(point) if self.in_bounds(&point) => {
+                    self.stats.seed_attempts += 1;
+                    if !self.evaluate(polygon, &point, false) {
                         return false;
                     }
+                    self.add_point(point)
                 }
+                _ => return false,
             }
         }
-
         true
     }
-}
 
-#[tauri::command]
-pub fn fill_polygon(data: Polygon<f64>, param: VegetationParams) -> Result<Vec<String>, String> {
-    if param.density <= 0.0 {
-        return Err("Density must be positive.".to_string());
+    /// Indique si un point est dans l'emprise du sampler, qui peut ne couvrir
+    /// qu'une partie du polygone.
+    fn in_bounds(&self, point: &Point<f64>) -> bool {
+        let (min_x, min_y, max_x, max_y) = self.bounds;
+        (min_x..=max_x).contains(&point.x()) && (min_y..=max_y).contains(&point.y())
     }
-    let bounding_rect = data
-        .bounding_rect()
-        .ok_or("Polygon has no bounding rectangle.")?;
-    let bounds = (
-        bounding_rect.min().x,
-        bounding_rect.min().y,
-        bounding_rect.max().x,
-        bounding_rect.max().y,
-    );
-    let mut sampler = SpatialDistributionSampler::new(param.density, bounds);
-    let points = sampler.generate_distribution(&data);
 
-    println!(
-        "Generated {} points using spatial distribution algorithm",
+    /// Fusionne des points échantillonnés séparément, en écartant ceux qui ne respectent
+    /// pas la distance minimale avec les points déjà retenus, dans l'ordre donné.
+    ///
+    /// # Arguments
+    /// * `points` - Les points à fusionner
+    ///
+    /// # Retours
+    /// Les points retenus ; les statistiques comptent chaque point comme un nouveau candidat
+    fn reconcile(&mut self, points: Vec<Point<f64>>) -> Vec<Point<f64>> {
+        self.stats = SamplingStats::default();
+        for point in points {
+            if self.is_full() {
+                break;
+            }
+            self.stats.total_attempts += 1;
+            if self.is_point_valid(&point) {
+                self.insert_point(point);
+                self.stats.accepted += 1;
+            } else {
+                self.stats.rejected_distance += 1;
+            }
+        }
+        self.stats.generated = self.points.len();
+        self.points.clone()
+    }
+
+    /// Fait croître le disque de Poisson autour d'un point actif tiré au hasard :
+    /// un nouveau point est placé, ou le point actif est désactivé.
+    ///
+    /// # Retours
+    /// `false` une fois la distribution terminée
+    fn grow_poisson_disk(&mut self, polygon: &Polygon<f64>, rng: &mut impl Rng) -> bool {
+        let (min_x, min_y, max_x, max_y) = self.bounds;
+        if self.active_indices.is_empty() || self.is_full() {
+            return false;
+        }
+
+        let idx = rng.random_range(0..self.active_indices.len());
+        let active_idx = self.active_indices[idx];
+        let active_point = self.points[active_idx];
+        // L'anneau de tirage suit la distance minimale locale
+        let factor = self.local_factor(&active_point);
+
+        let mut found_new_point = false;
+
+        for _ in 0..self.max_attempts {
+            let angle = 2.0 * std::f64::consts::PI * rng.random::<f64>();
+            let radius = factor * (1.0 + rng.random::<f64>());
+            let (spacing_x, spacing_y) = self.spacing;
+
+            let new_x = active_point.x() + radius * spacing_x * angle.cos();
+            let new_y = active_point.y() + radius * spacing_y * angle.sin();
+
+            if new_x < min_x || new_x > max_x || new_y < min_y || new_y > max_y {
+                self.stats.total_attempts += 1;
+                self.stats.rejected_outside += 1;
+                continue;
+            }
+
+            let new_point = Point::new(new_x, new_y);
+
+            if self.evaluate(polygon, &new_point, true) {
+                self.add_point(new_point);
+                found_new_point = true;
+                break;
+            }
+        }
+
+        if !found_new_point {
+            self.active_indices.swap_remove(idx);
+            self.stats.exhausted_active_points += 1;
+        }
+        true
+    }
+
+    /// Tire un point dans chacune de `count` cellules réparties sur l'emprise, parcourues
+    /// dans un ordre aléatoire, et active ceux qui sont acceptés.
+    ///
+    /// # Arguments
+    /// * `polygon` - Le polygone à remplir
+    /// * `count` - Nombre de cellules, et donc nombre maximal de points initiaux
+    /// * `rng` - Générateur aléatoire des tirages
+    fn seed_spread(&mut self, polygon: &Polygon<f64>, count: usize, rng: &mut impl Rng) {
+        let (min_x, min_y, max_x, max_y) = self.bounds;
+        let width = max_x - min_x;
+        let height = max_y - min_y;
+        let columns = ((count as f64 * width / height).sqrt().ceil() as usize).clamp(1, count);
+        let rows = count.div_ceil(columns);
+
+        let mut cells: Vec<usize> = (0..columns * rows).collect();
+        cells.shuffle(rng);
+        for cell in cells {
+            if self.is_full() {
+                break;
+            }
+            let x =
+                min_x + ((cell % columns) as f64 + rng.random::<f64>()) * width / columns as f64;
+            let y = min_y + ((cell / columns) as f64 + rng.random::<f64>()) * height / rows as f64;
+            let point = Point::new(x, y);
+
+            self.stats.seed_attempts += 1;
+            if self.evaluate(polygon, &point, true) {
+                self.add_point(point);
+            }
+        }
+    }
+
+    /// Tire des points uniformément dans l'emprise et garde ceux contenus dans le polygone,
+    /// jusqu'à atteindre le nombre de points estimé pour la densité.
+    fn generate_uniform(&mut self, polygon: &Polygon<f64>, rng: &mut impl Rng) -> Vec<Point<f64>> {
+        let target_count = self
+            .estimate_count(polygon)
+            .min(self.max_points.unwrap_or(usize::MAX));
+        let (min_x, min_y, max_x, max_y) = self.bounds;
+
+        for _ in 0..target_count * UNIFORM_ATTEMPTS_PER_POINT {
+            if self.points.len() >= target_count {
+                break;
+            }
+            let x = min_x + rng.random::<f64>() * (max_x - min_x);
+            let y = min_y + rng.random::<f64>() * (max_y - min_y);
+            let point = Point::new(x, y);
+
+            if self.evaluate(polygon, &point, false) {
+                self.points.push(point);
+            }
+        }
+
+        self.points.clone()
+    }
+
+    /// Place les points un à un en retenant, parmi `candidates` tirages uniformes contenus
+    /// dans le polygone, celui dont le plus proche voisin est le plus éloigné, jusqu'à
+    /// atteindre le nombre de points estimé pour la densité.
+    ///
+    /// # Arguments
+    /// * `polygon` - Le polygone à remplir
+    /// * `candidates` - Nombre de candidats évalués pour chaque point
+    /// * `rng` - Générateur aléatoire des tirages
+    fn generate_best_candidate(
+        &mut self,
+        polygon: &Polygon<f64>,
+        candidates: usize,
+        rng: &mut impl Rng,
+    ) -> Vec<Point<f64>> {
+        let target_count = self
+            .estimate_count(polygon)
+            .min(self.max_points.unwrap_or(usize::MAX));
+        let (min_x, min_y, max_x, max_y) = self.bounds;
+
+        while self.points.len() < target_count {
+            let mut best: Option<(Point<f64>, f64)> = None;
+            let mut evaluated = 0;
+            for _ in 0..candidates * UNIFORM_ATTEMPTS_PER_POINT {
+                if evaluated >= candidates {
+                    break;
+                }
+                let x = min_x + rng.random::<f64>() * (max_x - min_x);
+                let y = min_y + rng.random::<f64>() * (max_y - min_y);
+                let point = Point::new(x, y);
+
+                if !self.evaluate(polygon, &point, false) {
+                    continue;
+                }
+                evaluated += 1;
+                let distance = self.nearest_distance(&point);
+                if best.is_none_or(|(_, best_distance)| distance > best_distance) {
+                    best = Some((point, distance));
+                }
+            }
+            // Aucun tirage n'a atteint le polygone : il occupe une part infime de l'emprise
+            let Some((point, _)) = best else {
+                break;
+            };
+            self.insert_point(point);
+        }
+
+        self.points.clone()
+    }
+
+    /// Place un réseau régulier d'espacement `spacing` sur l'emprise, décale chaque nœud
+    /// d'au plus `jitter` et garde les nœuds décalés contenus dans le polygone. Les nœuds
+    /// sont au centre des cellules du réseau, pour qu'aucun rang ne tombe sur le bord de l'emprise.
+    fn generate_jittered_grid(
+        &mut self,
+        polygon: &Polygon<f64>,
+        rng: &mut impl Rng,
+    ) -> Vec<Point<f64>> {
+        let (spacing_x, spacing_y) = self.spacing;
+        let (min_x, min_y, max_x, max_y) = self.bounds;
+        let columns = ((max_x - min_x) / spacing_x).ceil() as usize;
+        let rows = ((max_y - min_y) / spacing_y).ceil() as usize;
+
+        for row in 0..rows {
+            for column in 0..columns {
+                if self.is_full() {
+                    return self.points.clone();
+                }
+                let mut x = min_x + (column as f64 + 0.5) * spacing_x;
+                let mut y = min_y + (row as f64 + 0.5) * spacing_y;
+                if self.jitter > 0.0 {
+                    x += rng.random_range(-self.jitter..=self.jitter);
+                    y += rng.random_range(-self.jitter..=self.jitter);
+                }
+                let point = Point::new(x, y);
+
+                if self.evaluate(polygon, &point, false) {
+                    self.points.push(point);
+                }
+            }
+        }
+
+        self.points.clone()
+    }
+
+    /// Répartit les centres des bosquets par disque de Poisson, espacés de deux rayons
+    /// pour que les bosquets ne se confondent pas, puis tire les points de chaque bosquet
+    /// uniformément dans son disque. Les points hors du polygone ou trop proches d'un
+    /// point déjà placé sont rejetés ; un bosquet peut donc compter moins de points.
+    ///
+    /// # Arguments
+    /// * `polygon` - Le polygone à remplir
+    /// * `cluster_count` - Nombre maximal de bosquets
+    /// * `cluster_radius` - Rayon de chaque bosquet
+    /// * `points_per_cluster` - Nombre de points visé par bosquet
+    /// * `rng` - Générateur aléatoire des tirages
+    fn generate_clustered(
+        &mut self,
+        polygon: &Polygon<f64>,
+        cluster_count: usize,
+        cluster_radius: f64,
+        points_per_cluster: usize,
+        rng: &mut impl Rng,
+    ) -> Vec<Point<f64>> {
+        if !(cluster_radius.is_finite() && cluster_radius > 0.0) {
+            return Vec::new();
+        }
+
+        let mut center_sampler = SpatialDistributionSampler::new(2.0 * cluster_radius, self.bounds)
+            .with_include_boundary(self.include_boundary)
+            .with_edge_margin(self.edge_margin)
+            .with_interior_buffer(self.interior_buffer)
+            .with_exclusions(self.exclusions.clone())
+            .with_density_gradient(self.density_gradient)
+            .with_density_field(self.density_field)
+            .with_attempts(self.max_attempts, self.seed_attempts)
+            .seeded(rng.random());
+        let (mut centers, center_stats) = center_sampler.generate_distribution_with_stats(polygon);
+        self.stats.merge(&center_stats);
+        centers.shuffle(rng);
+        centers.truncate(cluster_count);
+
+        for center in centers {
+            let mut placed = 0;
+            for _ in 0..points_per_cluster * self.max_attempts {
+                if placed >= points_per_cluster || self.is_full() {
+                    break;
+                }
+                let angle = 2.0 * std::f64::consts::PI * rng.random::<f64>();
+                let radius = cluster_radius * rng.random::<f64>().sqrt();
+                let point = Point::new(
+                    center.x() + radius * angle.cos(),
+                    center.y() + radius * angle.sin(),
+                );
+
+                if self.evaluate(polygon, &point, true) {
+                    self.insert_point(point);
+                    placed += 1;
+                }
+            }
+        }
+
+        self.points.clone()
+    }
+
+    /// Répète le motif de bruit bleu sur toutes les tuiles couvrant l'emprise
+    /// et ne garde que les points contenus dans le polygone. Les tuiles sont alignées
+    /// sur l'origine des coordonnées, si bien que deux polygones voisins partagent le même motif.
+    fn generate_tileable(&mut self, polygon: &Polygon<f64>, tile_size: f64) -> Vec<Point<f64>> {
+        let pattern = generate_tileable_pattern(self.min_distance, tile_size);
+        let (min_x, min_y, max_x, max_y) = self.bounds;
+
+        let first_column = (min_x / tile_size).floor() as i64;
+        let last_column = (max_x / tile_size).floor() as i64;
+        let first_row = (min_y / tile_size).floor() as i64;
+        let last_row = (max_y / tile_size).floor() as i64;
+
+        for row in first_row..=last_row {
+            for column in first_column..=last_column {
+                let origin_x = column as f64 * tile_size;
+                let origin_y = row as f64 * tile_size;
+                for offset in &pattern {
+                    if self.is_full() {
+                        return self.points.clone();
+                    }
+                    let point = Point::new(origin_x + offset.x(), origin_y + offset.y());
+                    if self.evaluate(polygon, &point, false) {
+                        self.points.push(point);
+                    }
+                }
+            }
+        }
+
+        self.points.clone()
+    }
+
+    /// Uniformise la distribution par relaxation de Lloyd : à chaque itération, chaque point
+    /// est déplacé au centroïde de sa cellule de Voronoï, estimée par échantillonnage
+    /// de l'ellipse d'espacement qui l'entoure parmi ses voisins de la grille.
+    /// Un déplacement qui sortirait le point du polygone, ou le placerait dans un trou,
+    /// est refusé. La distance minimale n'est plus strictement garantie après relaxation.
+    ///
+    /// # Arguments
+    /// * `iterations` - Nombre d'itérations de relaxation
+    /// * `polygon` - Le polygone dans lequel les points doivent rester
+    ///
+    /// # Retours
+    /// Les points relaxés, tous contenus dans le polygone
+    pub fn relax(&mut self, iterations: usize, polygon: &Polygon<f64>) -> Vec<Point<f64>> {
+        self.resolve_density_field(polygon);
+        for _ in 0..iterations {
+            self.rebuild_grid();
+            let relaxed = self
+                .points
+                .iter()
+                .enumerate()
+                .map(
+                    |(index, point)| match self.voronoi_centroid(index, polygon) {
+                        Some(centroid) if self.accepts(polygon, &centroid) => centroid,
+                        _ => *point,
+                    },
+                )
+                .collect();
+            self.points = relaxed;
+        }
+        self.rebuild_grid();
+
+        self.points.clone()
+    }
+
+    /// Estime le centroïde de la cellule de Voronoï d'un point, limitée à son ellipse
+    /// d'espacement et au polygone, à partir d'un réseau d'échantillons.
+    ///
+    /// # Arguments
+    /// * `index` - L'indice du point
+    /// * `polygon` - Le polygone qui borne la cellule
+    ///
+    /// # Retours
+    /// Le centroïde, ou None si aucun échantillon n'appartient à la cellule
+    fn voronoi_centroid(&self, index: usize, polygon: &Polygon<f64>) -> Option<Point<f64>> {
+        let point = self.points[index];
+        let factor = self.local_factor(&point);
+        let (spacing_x, spacing_y) = (self.spacing.0 * factor, self.spacing.1 * factor);
+        // Tout échantillon de l'ellipse a son point le plus proche à moins de deux espacements
+        let neighbors = self.neighbors(&point, 2.0 * factor);
+
+        let (mut sum_x, mut sum_y, mut count) = (0.0, 0.0, 0usize);
+        for row in 0..RELAX_SAMPLES_PER_AXIS {
+            for column in 0..RELAX_SAMPLES_PER_AXIS {
+                let u = 2.0 * (column as f64 + 0.5) / RELAX_SAMPLES_PER_AXIS as f64 - 1.0;
+                let v = 2.0 * (row as f64 + 0.5) / RELAX_SAMPLES_PER_AXIS as f64 - 1.0;
+                if u * u + v * v > 1.0 {
+                    continue;
+                }
+                let sample = Point::new(point.x() + u * spacing_x, point.y() + v * spacing_y);
+                if !polygon.contains(&sample) {
+                    continue;
+                }
+                let own_distance = u * u + v * v;
+                let closer_neighbor = neighbors.iter().any(|&other_index| {
+                    let other = self.points[other_index];
+                    let dx = (sample.x() - other.x()) / spacing_x;
+                    let dy = (sample.y() - other.y()) / spacing_y;
+                    other_index != index && dx * dx + dy * dy < own_distance
+                });
+                if !closer_neighbor {
+                    sum_x += sample.x();
+                    sum_y += sample.y();
+                    count += 1;
+                }
+            }
+        }
+
+        (count > 0).then(|| Point::new(sum_x / count as f64, sum_y / count as f64))
+    }
+
+    /// Liste les indices des points enregistrés dans la grille à l'intérieur
+    /// de l'ellipse d'espacement agrandie de `reach` autour d'un point.
+    ///
+    /// # Arguments
+    /// * `point` - Le point autour duquel chercher
+    /// * `reach` - Facteur appliqué aux espacements
+    fn neighbors(&self, point: &Point<f64>, reach: f64) -> Vec<usize> {
+        let (min_x, min_y, _, _) = self.bounds;
+        let (spacing_x, spacing_y) = self.spacing;
+
+        let grid_x = ((point.x() - min_x) / self.cell_size) as usize;
+        let grid_y = ((point.y() - min_y) / self.cell_size) as usize;
+        let cells = (reach * spacing_x.max(spacing_y) / self.cell_size).ceil() as usize;
+
+        let start_x = grid_x.saturating_sub(cells);
+        let start_y = grid_y.saturating_sub(cells);
+        let end_x = (grid_x + cells).min(self.grid_width - 1);
+        let end_y = (grid_y + cells).min(self.grid_height - 1);
+
+        let mut neighbors = Vec::new();
+        for y in start_y..=end_y {
+            for x in start_x..=end_x {
+                let Some(cell) = self.grid.get(y * self.grid_width + x) else {
+                    continue;
+                };
+                for &point_idx in cell {
+                    let other = &self.points[point_idx];
+                    let dx = (point.x() - other.x()) / spacing_x;
+                    let dy = (point.y() - other.y()) / spacing_y;
+                    if dx * dx + dy * dy < reach * reach {
+                        neighbors.push(point_idx);
+                    }
+                }
+            }
+        }
+
+        neighbors
+    }
+
+    /// Reconstruit la grille à partir des positions actuelles des points. Les stratégies
+    /// régulières n'enregistrent pas leurs points dans la grille et la relaxation les déplace.
+    fn rebuild_grid(&mut self) {
+        self.grid = vec![Vec::new(); self.grid_width * self.grid_height];
+        for idx in 0..self.points.len() {
+            if let Some(grid_idx) = self.grid_index(&self.points[idx]) {
+                self.grid[grid_idx].push(idx);
+            }
+        }
+    }
+
+    /// Remplace les points du sampler, par exemple pour relaxer une distribution
+    /// produite par un autre sampler.
+    ///
+    /// # Arguments
+    /// * `points` - Les points de la distribution
+    fn with_points(mut self, points: Vec<Point<f64>>) -> Self {
+        self.points = points;
+        self
+    }
+
+    /// Estime le nombre de points que la distribution placerait dans le polygone,
+    /// à partir de son aire et de l'espacement, sans exécuter l'échantillonnage.
+    ///
+    /// # Arguments
+    /// * `polygon` - Le polygone à remplir
+    ///
+    /// # Retours
+    /// Le nombre de points attendu, arrondi à l'entier le plus proche
+    pub fn estimate_count(&self, polygon: &Polygon<f64>) -> usize {
+        let (spacing_x, spacing_y) = self.spacing;
+        estimate_point_count(polygon, spacing_x, spacing_y)
+    }
+
+    /// Cherche un point du polygone respectant la distance minimale avec les points
+    /// `avoid`, sans générer de distribution complète.
+    ///
+    /// # Arguments
+    /// * `polygon` - Le polygone dans lequel chercher le point
+    /// * `avoid` - Les points existants à tenir à distance
+    ///
+    /// # Retours
+    /// Le point trouvé, ou None si aucun tirage n'a abouti
+    pub fn suggest_point(
+        &mut self,
+        polygon: &Polygon<f64>,
+        avoid: &[Point<f64>],
+    ) -> Option<Point<f64>> {
+        for point in avoid {
+            self.insert_point(*point);
+        }
+
+        let mut rng = self.make_rng();
+        let (min_x, min_y, max_x, max_y) = self.bounds;
+        for _ in 0..SUGGEST_ATTEMPTS {
+            let x = min_x + rng.random::<f64>() * (max_x - min_x);
+            let y = min_y + rng.random::<f64>() * (max_y - min_y);
+            let point = Point::new(x, y);
+
+            if self.accepts(polygon, &point) && self.is_point_valid(&point) {
+                return Some(point);
+            }
+        }
+
+        None
+    }
+
+    /// Ajoute un point à la distribution et met à jour les structures de données.
+    ///
+    /// # Arguments
+    /// * `point` - Le point à ajouter
+    fn add_point(&mut self, point: Point<f64>) {
+        let idx = self.insert_point(point);
+        self.active_indices.push(idx);
+    }
+
+    /// Enregistre un point dans la grille sans l'activer pour la génération.
+    ///
+    /// # Arguments
+    /// * `point` - Le point à enregistrer
+    ///
+    /// # Retours
+    /// L'indice du point
+    fn insert_point(&mut self, point: Point<f64>) -> usize {
+        let idx = self.points.len();
+        self.points.push(point);
+
+        if self.grid.is_empty() {
+            self.grid = vec![Vec::new(); self.grid_width * self.grid_height];
+        }
+
+        if let Some(grid_idx) = self.grid_index(&point) {
+            self.grid[grid_idx].push(idx);
+        }
+
+        idx
+    }
+
+    /// Calcule la distance entre un point et le point enregistré le plus proche, rapportée
+    /// aux espacements : 1 correspond au bord de l'ellipse d'espacement. Les cellules sont
+    /// parcourues par anneaux croissants, jusqu'à ce qu'aucune cellule plus lointaine
+    /// ne puisse contenir de point plus proche.
+    ///
+    /// # Arguments
+    /// * `point` - Le point dont on cherche le plus proche voisin
+    ///
+    /// # Retours
+    /// La distance au plus proche voisin, infinie si aucun point n'est enregistré
+    fn nearest_distance(&self, point: &Point<f64>) -> f64 {
+        if self.grid.is_empty() {
+            return f64::INFINITY;
+        }
+        let (min_x, min_y, _, _) = self.bounds;
+        let (spacing_x, spacing_y) = self.spacing;
+        let grid_x = (((point.x() - min_x) / self.cell_size) as usize).min(self.grid_width - 1);
+        let grid_y = (((point.y() - min_y) / self.cell_size) as usize).min(self.grid_height - 1);
+        let max_spacing = spacing_x.max(spacing_y);
+
+        let mut nearest = f64::INFINITY;
+        for ring in 0..self.grid_width.max(self.grid_height) {
+            // Les cellules de l'anneau sont à au moins `ring - 1` cellules du point
+            if ring > 0 && (ring - 1) as f64 * self.cell_size / max_spacing >= nearest {
+                break;
+            }
+            let start_x = grid_x.saturating_sub(ring);
+            let start_y = grid_y.saturating_sub(ring);
+            let end_x = (grid_x + ring).min(self.grid_width - 1);
+            let end_y = (grid_y + ring).min(self.grid_height - 1);
+            for y in start_y..=end_y {
+                for x in start_x..=end_x {
+                    if x.abs_diff(grid_x) != ring && y.abs_diff(grid_y) != ring {
+                        continue;
+                    }
+                    for &point_idx in &self.grid[y * self.grid_width + x] {
+                        let other = &self.points[point_idx];
+                        let dx = (point.x() - other.x()) / spacing_x;
+                        let dy = (point.y() - other.y()) / spacing_y;
+                        nearest = nearest.min((dx * dx + dy * dy).sqrt());
+                    }
+                }
+            }
+        }
+
+        nearest
+    }
+
+    /// Calcule l'indice de la cellule de la grille contenant un point.
+    ///
+    /// # Arguments
+    /// * `point` - Le point à situer
+    ///
+    /// # Retours
+    /// L'indice de la cellule, ou None si le point est hors de la grille
+    fn grid_index(&self, point: &Point<f64>) -> Option<usize> {
+        let (min_x, min_y, _, _) = self.bounds;
+        let grid_x = ((point.x() - min_x) / self.cell_size) as usize;
+        let grid_y = ((point.y() - min_y) / self.cell_size) as usize;
+
+        (grid_x < self.grid_width && grid_y < self.grid_height)
+            .then_some(grid_y * self.grid_width + grid_x)
+            .filter(|&grid_idx| grid_idx < self.grid.len())
+    }
+
+    /// Vérifie si un point est valide en termes de distance minimale avec les points existants.
+    /// Les cellules mesurant `min_distance / √2`, un point en conflit peut se trouver
+    /// jusqu'à deux cellules plus loin : la recherche couvre ±2 cellules en isotrope
+    /// (rayon de Bridson), davantage selon le plus grand axe en anisotrope.
+    ///
+    /// # Arguments
+    /// * `point` - Le point à vérifier
+    ///
+    /// # Retours
+    /// `true` si le point respecte la distance minimale par rapport à tous les points existants
+    fn is_point_valid(&self, point: &Point<f64>) -> bool {
+        let (min_x, min_y, _, _) = self.bounds;
+
+        let grid_x = ((point.x() - min_x) / self.cell_size) as usize;
+        let grid_y = ((point.y() - min_y) / self.cell_size) as usize;
+
+        let max_factor = self
+            .density_gradient
+            .as_ref()
+            .map_or(1.0, DensityGradient::max_factor)
+            * self
+                .radial_density
+                .as_ref()
+                .map_or(1.0, RadialDensity::max_factor);
+        let factor = self.local_factor(point);
+        let (spacing_x, spacing_y) = (self.spacing.0 * factor, self.spacing.1 * factor);
+        // Nombre de cellules à parcourir de part et d'autre pour couvrir le plus grand axe
+        let reach =
+            (self.spacing.0.max(self.spacing.1) * max_factor / self.cell_size).ceil() as usize;
+
+        let start_x = grid_x.saturating_sub(reach);
+        let start_y = grid_y.saturating_sub(reach);
+        let end_x = (grid_x + reach).min(self.grid_width - 1);
+        let end_y = (grid_y + reach).min(self.grid_height - 1);
+
+        for y in start_y..=end_y {
+            for x in start_x..=end_x {
+                let idx = y * self.grid_width + x;
+                let Some(cell) = self.grid.get(idx) else {
+                    continue;
+                };
+                for &point_idx in cell {
+                    let other = &self.points[point_idx];
+                    let dx = (point.x() - other.x()) / spacing_x;
+                    let dy = (point.y() - other.y()) / spacing_y;
+
+                    if dx * dx + dy * dy < 1.0 {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// Étape de la génération d'une distribution par `iter_points`.
+enum DistributionStage {
+    /// Aucun point n'a encore été demandé
+    Start,
+    /// Le disque de Poisson croît d'un point à chaque pas
+    Growing,
+    /// Distribution générée d'un bloc, dont il reste à produire les points
+    Eager(std::vec::IntoIter<Point<f64>>),
+    /// Tous les points ont été produits
+    Done,
+}
+
+/// Itérateur sur les points d'une distribution, produits au fil de leur génération.
+struct DistributionPoints<'a, R> {
+    sampler: &'a mut SpatialDistributionSampler,
+    polygon: &'a Polygon<f64>,
+    rng: R,
+    /// Indice, parmi les points du sampler, du prochain point à produire
+    next_index: usize,
+    stage: DistributionStage,
+}
+
+impl<R: Rng> Iterator for DistributionPoints<'_, R> {
+    type Item = Point<f64>;
+
+    fn next(&mut self) -> Option<Point<f64>> {
+        loop {
+            match &mut self.stage {
+                DistributionStage::Start => {
+                    let sampler = &mut *self.sampler;
+                    if sampler.strategy == SamplingStrategy::PoissonDisk
+                        && sampler.has_area()
+                        && !sampler.is_full()
+                    {
+                        sampler.stats = SamplingStats::default();
+                        self.stage = if sampler.seed_poisson_disk(self.polygon, &mut self.rng) {
+                            DistributionStage::Growing
+                        } else {
+                            DistributionStage::Done
+                        };
+                    } else {
+                        let points =
+                            sampler.generate_distribution_with_rng(self.polygon, &mut self.rng);
+                        self.stage = DistributionStage::Eager(points.into_iter());
+                    }
+                }
+                DistributionStage::Growing => {
+                    if let Some(point) = self.sampler.points.get(self.next_index) {
+                        self.next_index += 1;
+                        return Some(*point);
+                    }
+                    if !self.sampler.grow_poisson_disk(self.polygon, &mut self.rng) {
+                        self.sampler.stats.generated = self.sampler.points.len();
+                        self.stage = DistributionStage::Done;
+                    }
+                }
+                DistributionStage::Eager(points) => return points.next(),
+                DistributionStage::Done => return None,
+            }
+        }
+    }
+}
+
+/// Génère un motif de bruit bleu dans une tuile carrée `[0, tile_size)²` dont les bords
+/// se raccordent : la distance minimale est respectée en tenant compte du repliement
+/// torique, de sorte que le motif peut être répété sans couture.
+/// Le motif est déterministe pour une même distance minimale et une même taille de tuile.
+///
+/// # Arguments
+/// * `min_distance` - Distance minimale entre deux points
+/// * `tile_size` - Côté de la tuile, supérieur à la distance minimale
+///
+/// # Retours
+/// Les points du motif, en coordonnées relatives à l'origine de la tuile
+pub fn generate_tileable_pattern(min_distance: f64, tile_size: f64) -> Vec<Point<f64>> {
+    let mut rng = StdRng::seed_from_u64(TILE_SEED);
+    let cells = (tile_size / (min_distance / std::f64::consts::SQRT_2)).ceil() as usize;
+    let cell_size = tile_size / cells as f64;
+    let mut grid: Vec<Option<usize>> = vec![None; cells * cells];
+    let mut points: Vec<Point<f64>> = Vec::new();
+    let mut active_indices = Vec::new();
+
+    let cell_of = |point: &Point<f64>| {
+        let x = ((point.x() / cell_size) as usize).min(cells - 1);
+        let y = ((point.y() / cell_size) as usize).min(cells - 1);
+        (x, y)
+    };
+    let wrapped_distance_sq = |a: &Point<f64>, b: &Point<f64>| {
+        let dx = (a.x() - b.x()).abs();
+        let dy = (a.y() - b.y()).abs();
+        let dx = dx.min(tile_size - dx);
+        let dy = dy.min(tile_size - dy);
+        dx * dx + dy * dy
+    };
+
+    let first = Point::new(
+        rng.random::<f64>() * tile_size,
+        rng.random::<f64>() * tile_size,
+    );
+    let (x, y) = cell_of(&first);
+    grid[y * cells + x] = Some(0);
+    points.push(first);
+    active_indices.push(0);
+
+    while !active_indices.is_empty() {
+        let idx = rng.random_range(0..active_indices.len());
+        let active_point = points[active_indices[idx]];
+        let mut found_new_point = false;
+
+        for _ in 0..30 {
+            let angle = 2.0 * std::f64::consts::PI * rng.random::<f64>();
+            let radius = min_distance + min_distance * rng.random::<f64>();
+            let candidate = Point::new(
+                (active_point.x() + radius * angle.cos()).rem_euclid(tile_size),
+                (active_point.y() + radius * angle.sin()).rem_euclid(tile_size),
+            );
+
+            let (grid_x, grid_y) = cell_of(&candidate);
+            let is_valid = (-2..=2_i64).all(|dy| {
+                (-2..=2_i64).all(|dx| {
+                    let x = (grid_x as i64 + dx).rem_euclid(cells as i64) as usize;
+                    let y = (grid_y as i64 + dy).rem_euclid(cells as i64) as usize;
+                    grid[y * cells + x].is_none_or(|other| {
+                        wrapped_distance_sq(&candidate, &points[other])
+                            >= min_distance * min_distance
+                    })
+                })
+            });
+
+            if is_valid {
+                grid[grid_y * cells + grid_x] = Some(points.len());
+                active_indices.push(points.len());
+                points.push(candidate);
+                found_new_point = true;
+                break;
+            }
+        }
+
+        if !found_new_point {
+            active_indices.swap_remove(idx);
+        }
+    }
+
+    points
+}
+
+/// Estime le nombre de points d'une distribution d'espacements `spacing_x` et `spacing_y`
+/// dans le polygone. Ne nécessite pas de construire la grille d'un sampler.
+pub fn estimate_point_count(polygon: &Polygon<f64>, spacing_x: f64, spacing_y: f64) -> usize {
+    (POISSON_PACKING_DENSITY * polygon.unsigned_area() / (spacing_x * spacing_y)).round() as usize
+}
+
+/// Calcule les espacements (x, y) du sampler pour un polygone selon le mode de densité.
+/// En points par hectare ou en nombre de points, la distance minimale est celle dont
+/// la densité de disque de Poisson donne le nombre de points visé sur l'aire du polygone.
+///
+/// # Arguments
+/// * `polygon` - Le polygone à remplir
+/// * `param` - Les paramètres de végétation
+///
+/// # Retours
+/// Les espacements selon X et Y, ou une erreur si le polygone n'a pas d'aire
+pub fn target_spacing(
+    polygon: &Polygon<f64>,
+    param: &VegetationParams,
+) -> Result<(f64, f64), String> {
+    let area = || match polygon.unsigned_area() {
+        area if area > 0.0 => Ok(area),
+        _ => Err("Polygon has no area.".to_string()),
+    };
+    let spacing_for_count = |area: f64, count: f64| (POISSON_PACKING_DENSITY * area / count).sqrt();
+
+    let min_distance = match param.density_mode {
+        DensityMode::MinDistance => param.density,
+        DensityMode::PointsPerHectare => {
+            let area = area()?;
+            spacing_for_count(area, param.density * area / SQUARE_METRES_PER_HECTARE)
+        }
+        DensityMode::TargetCount => spacing_for_count(area()?, param.density),
+    };
+    Ok((
+        param.min_distance_x.unwrap_or(min_distance),
+        param.min_distance_y.unwrap_or(min_distance),
+    ))
+}
+
+/// Remplit un polygone avec exactement `param.density` points lorsque c'est possible.
+/// La distance minimale est recherchée par dichotomie autour de l'estimation
+/// de `target_spacing`, puis les points en trop de la distribution la plus proche
+/// sont retirés au hasard. Si aucune distribution n'atteint le nombre visé,
+/// celle qui s'en approche le plus est retournée.
+///
+/// # Arguments
+/// * `polygon` - Le polygone à remplir
+/// * `param` - Les paramètres de végétation, `density` étant le nombre de points visé
+/// * `bounds` - L'emprise du polygone
+/// * `rng` - Générateur des graines des samplers et du tirage des points retirés
+/// * `stats` - Statistiques auxquelles ajouter celles de chaque échantillonnage
+fn sample_target_count(
+    polygon: &Polygon<f64>,
+    param: &VegetationParams,
+    bounds: (f64, f64, f64, f64),
+    rng: &mut StdRng,
+    stats: &mut SamplingStats,
+) -> Result<Vec<Point<f64>>, String> {
+    let target_count = param.density.round() as usize;
+    let (spacing_x, spacing_y) = target_spacing(polygon, param)?;
+    let (mut low, mut high) = (0.5, 2.0);
+    let mut closest_over: Option<Vec<Point<f64>>> = None;
+    let mut closest_under: Vec<Point<f64>> = Vec::new();
+
+    for _ in 0..TARGET_COUNT_ITERATIONS {
+        let scale = (low + high) / 2.0;
+        let (points, sampling_stats) = SpatialDistributionSampler::try_new_anisotropic(
+            spacing_x * scale,
+            spacing_y * scale,
+            bounds,
+            max_grid_cells(param),
+        )?
+        .with_include_boundary(param.include_boundary)
+        .with_edge_margin(param.edge_margin)
+        .with_interior_buffer(param.interior_buffer)
+        .with_exclusions(param.exclusion_zones.clone())
+        .with_density_gradient(param.density_gradient)
+        .with_density_field(param.density_field)
+        .with_attempts(max_attempts(param), seed_attempts(param))
+        .seeded(rng.random())
+        .generate_distribution_with_stats(polygon);
+        stats.merge(&sampling_stats);
+
+        match points.len().cmp(&target_count) {
+            std::cmp::Ordering::Equal => return Ok(points),
+            std::cmp::Ordering::Greater => {
+                low = scale;
+                if closest_over
+                    .as_ref()
+                    .is_none_or(|closest| points.len() < closest.len())
+                {
+                    closest_over = Some(points);
+                }
+            }
+            std::cmp::Ordering::Less => {
+                high = scale;
+                if points.len() > closest_under.len() {
+                    closest_under = points;
+                }
+            }
+        }
+    }
+
+    Ok(match closest_over {
+        Some(mut points) => {
+            points.shuffle(rng);
+            points.truncate(target_count);
+            points
+        }
+        None => closest_under,
+    })
+}
+
+/// Calcule la distance d'un point au bord le plus proche du polygone,
+/// anneau extérieur ou anneaux intérieurs.
+pub fn distance_to_boundary(polygon: &Polygon<f64>, point: &Point<f64>) -> f64 {
+    std::iter::once(polygon.exterior())
+        .chain(polygon.interiors())
+        .map(|ring| Euclidean.distance(point, ring))
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Calcule la distance d'un point à l'anneau intérieur le plus proche du polygone,
+/// infinie pour un polygone sans trou.
+pub fn distance_to_holes(polygon: &Polygon<f64>, point: &Point<f64>) -> f64 {
+    polygon
+        .interiors()
+        .iter()
+        .map(|ring| Euclidean.distance(point, ring))
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Calcule les dimensions de la grille couvrant une emprise.
+///
+/// # Arguments
+/// * `cell_size` - Côté d'une cellule
+/// * `bounds` - Tuple (min_x, min_y, max_x, max_y) de l'emprise
+///
+/// # Retours
+/// Le nombre de colonnes et de rangs de la grille
+fn grid_dimensions(cell_size: f64, bounds: (f64, f64, f64, f64)) -> (usize, usize) {
+    let (min_x, min_y, max_x, max_y) = bounds;
+    // Les conversions saturent : une distance nulle donne une grille démesurée
+    // sans débordement, refusée par `try_new_anisotropic`
+    (
+        (((max_x - min_x) / cell_size).ceil() as usize).saturating_add(1),
+        (((max_y - min_y) / cell_size).ceil() as usize).saturating_add(1),
+    )
+}
+
+/// Indique si un point respecte le retrait aux bords et le retrait aux trous.
+///
+/// # Arguments
+/// * `polygon` - Le polygone contenant le point
+/// * `point` - Le point à tester
+/// * `edge_margin` - Distance minimale à tous les bords, 0 pour aucun retrait
+/// * `interior_buffer` - Distance minimale aux anneaux intérieurs, 0 pour aucun retrait
+fn respects_buffers(
+    polygon: &Polygon<f64>,
+    point: &Point<f64>,
+    edge_margin: f64,
+    interior_buffer: f64,
+) -> bool {
+    (edge_margin <= 0.0 || distance_to_boundary(polygon, point) >= edge_margin)
+        && (interior_buffer <= 0.0 || distance_to_holes(polygon, point) >= interior_buffer)
+}
+
+/// Indique si un point se trouve dans l'une des zones d'exclusion.
+///
+/// # Arguments
+/// * `exclusions` - Les zones d'exclusion
+/// * `point` - Le point à tester
+pub fn in_exclusion_zone(exclusions: &[Polygon<f64>], point: &Point<f64>) -> bool {
+    exclusions.iter().any(|zone| zone.contains(point))
+}
+
+/// Indique si un point appartient au polygone. `Contains` exclut les points du bord ;
+/// avec `include_boundary`, un point à moins de `BOUNDARY_EPSILON` du bord est accepté.
+///
+/// # Arguments
+/// * `polygon` - Le polygone à tester
+/// * `point` - Le point à tester
+/// * `include_boundary` - `true` pour accepter les points situés sur le bord
+pub fn point_in_polygon(
+    polygon: &Polygon<f64>,
+    point: &Point<f64>,
+    include_boundary: bool,
+) -> bool {
+    polygon.contains(point)
+        || (include_boundary && distance_to_boundary(polygon, point) <= BOUNDARY_EPSILON)
+}
+
+/// Calcule l'emprise d'un polygone.
+///
+/// # Arguments
+/// * `polygon` - Le polygone dont on veut l'emprise
+///
+/// # Retours
+/// Tuple (min_x, min_y, max_x, max_y), ou None si le polygone est vide
+pub fn calculate_polygon_bounds(polygon: &Polygon<f64>) -> Option<(f64, f64, f64, f64)> {
+    polygon
+        .bounding_rect()
+        .map(|rect| (rect.min().x, rect.min().y, rect.max().x, rect.max().y))
+}
+
+/// Répare un polygone invalide (anneau qui se recoupe, pointe aller-retour) en
+/// recalculant son contour par une union avec la règle de remplissage non nulle, qui
+/// garde les deux lobes d'un anneau qui se recoupe quel que soit leur sens de parcours.
+/// Un polygone valide est retourné tel quel.
+///
+/// # Arguments
+/// * `polygon` - Le polygone à réparer
+///
+/// # Retours
+/// Le polygone valide, ou une erreur si la réparation ne donne pas un seul polygone valide
+pub fn repair_polygon(polygon: &Polygon<f64>) -> Result<Polygon<f64>, String> {
+    if polygon.is_valid() {
+        return Ok(polygon.clone());
+    }
+    let mut parts = polygon
+        .union_with_fill_rule(&MultiPolygon::<f64>::new(Vec::new()), FillRule::NonZero)
+        .0;
+    match parts.len() {
+        0 => Err("Invalid polygon has no area left once repaired.".to_string()),
+        1 => {
+            let repaired = parts.remove(0);
+            if repaired.is_valid() {
+                Ok(repaired)
+            } else {
+                Err("Invalid polygon could not be repaired.".to_string())
+            }
+        }
+        count => Err(format!(
+            "Invalid polygon splits into {} parts once repaired: split it in the source file.",
+            count
+        )),
+    }
+}
+
+/// Applique à chaque point un décalage aléatoire d'au plus `variation` sur chaque axe.
+/// Un point pour lequel aucun décalage ne reste dans le polygone, à `edge_margin` des bords,
+/// à `interior_buffer` des trous et hors des zones d'exclusion, garde sa position.
+///
+/// # Arguments
+/// * `points` - Les points à décaler
+/// * `polygon` - Le polygone dans lequel les points doivent rester
+/// * `variation` - Décalage maximal sur chaque axe
+/// * `edge_margin` - Distance minimale entre un point décalé et les bords du polygone
+/// * `interior_buffer` - Distance minimale entre un point décalé et les trous du polygone
+/// * `exclusions` - Zones dans lesquelles un point décalé ne doit pas tomber
+/// * `rng` - Générateur aléatoire des décalages
+///
+/// # Retours
+/// Les points décalés, tous contenus dans le polygone
+pub fn apply_variation(
+    points: Vec<Point<f64>>,
+    polygon: &Polygon<f64>,
+    variation: f64,
+    edge_margin: f64,
+    interior_buffer: f64,
+    exclusions: &[Polygon<f64>],
+    rng: &mut impl Rng,
+) -> Vec<Point<f64>> {
+    if variation <= 0.0 {
+        return points;
+    }
+
+    points
+        .into_iter()
+        .map(|point| {
+            vary_point(
+                point,
+                polygon,
+                variation,
+                edge_margin,
+                interior_buffer,
+                exclusions,
+                &mut *rng,
+            )
+        })
+        .collect()
+}
+
+/// Applique à un point le décalage aléatoire de `apply_variation`.
+///
+/// # Arguments
+/// * `point` - Le point à décaler
+/// * `polygon` - Le polygone dans lequel le point doit rester
+/// * `variation` - Décalage maximal sur chaque axe
+/// * `edge_margin` - Distance minimale entre le point décalé et les bords du polygone
+/// * `interior_buffer` - Distance minimale entre le point décalé et les trous du polygone
+/// * `exclusions` - Zones dans lesquelles le point décalé ne doit pas tomber
+/// * `rng` - Générateur aléatoire du décalage
+///
+/// # Retours
+/// Le point décalé, ou le point d'origine si aucun décalage ne convient
+pub fn vary_point(
+    point: Point<f64>,
+    polygon: &Polygon<f64>,
+    variation: f64,
+    edge_margin: f64,
+    interior_buffer: f64,
+    exclusions: &[Polygon<f64>],
+    rng: &mut impl Rng,
+) -> Point<f64> {
+    if variation <= 0.0 {
+        return point;
+    }
+    for _ in 0..VARIATION_ATTEMPTS {
+        let candidate = Point::new(
+            point.x() + rng.random_range(-variation..=variation),
+            point.y() + rng.random_range(-variation..=variation),
+        );
+        if polygon.contains(&candidate)
+            && respects_buffers(polygon, &candidate, edge_margin, interior_buffer)
+            && !in_exclusion_zone(exclusions, &candidate)
+        {
+            return candidate;
+        }
+    }
+    point
+}
+
+/// Arrondit les coordonnées d'un point au multiple de `step` le plus proche.
+///
+/// # Arguments
+/// * `point` - Le point à arrondir
+/// * `step` - Pas de la grille d'arrondi
+pub fn snap_point(point: Point<f64>, step: f64) -> Point<f64> {
+    Point::new(
+        (point.x() / step).round() * step,
+        (point.y() / step).round() * step,
+    )
+}
+
+/// Nombre de tentatives autour d'un point actif pour des paramètres donnés.
+fn max_attempts(param: &VegetationParams) -> usize {
+    param.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS)
+}
+
+/// Nombre de tirages du point initial pour des paramètres donnés.
+fn seed_attempts(param: &VegetationParams) -> usize {
+    param.seed_attempts.unwrap_or(DEFAULT_SEED_ATTEMPTS)
+}
+
+/// Nombre maximal de cellules de la grille d'un sampler pour des paramètres donnés.
+fn max_grid_cells(param: &VegetationParams) -> usize {
+    param.max_grid_cells.unwrap_or(DEFAULT_MAX_GRID_CELLS)
+}
+
+/// Vérifie la cohérence des paramètres de végétation avant le remplissage.
+///
+/// # Retours
+/// Ok(()) si les paramètres sont valides, ou l'erreur décrivant le premier problème
+fn validate_params(param: &VegetationParams) -> Result<(), String> {
+    if !(param.density.is_finite() && param.density > 0.0) {
+        return Err("Density must be positive.".to_string());
+    }
+    if !(param.variation.is_finite() && param.variation >= 0.0) {
+        return Err("Variation cannot be negative.".to_string());
+    }
+    if param.edge_margin < 0.0 {
+        return Err("Edge margin cannot be negative.".to_string());
+    }
+    if param.interior_buffer < 0.0 {
+        return Err("Interior buffer cannot be negative.".to_string());
+    }
+    if param.max_points == Some(0) {
+        return Err("Maximum point count must be positive.".to_string());
+    }
+    if let Some(epsg) = param.sampling_epsg {
+        if param.source_epsg.is_none() {
+            return Err("A sampling EPSG code requires the source EPSG code.".to_string());
+        }
+        if epsg == EPSG_WGS84 {
+            return Err("The sampling EPSG code must be a metric projection.".to_string());
+        }
+    }
+    if let Some((columns, rows)) = param.parallel_tiles {
+        if columns == 0 || rows == 0 {
+            return Err("Parallel tiles need at least one column and one row.".to_string());
+        }
+        if param.strategy != SamplingStrategy::PoissonDisk
+            || param.density_mode == DensityMode::TargetCount
+        {
+            return Err(
+                "Parallel tiles require the Poisson disk strategy without a target count."
+                    .to_string(),
+            );
+        }
+    }
+    if param.density_mode == DensityMode::TargetCount
+        && param.strategy != SamplingStrategy::PoissonDisk
+    {
+        return Err("Target count mode requires the Poisson disk strategy.".to_string());
+    }
+    if let SamplingStrategy::Clustered {
+        cluster_count,
+        cluster_radius,
+        points_per_cluster,
+    } = param.strategy
+        && (cluster_count == 0 || points_per_cluster == 0 || cluster_radius <= 0.0)
+    {
+        return Err(
+            "Clusters need a positive count, radius and number of points per cluster.".to_string(),
+        );
+    }
+    if let Some(gradient) = param.density_gradient {
+        let valid_factor = |factor: f64| factor.is_finite() && factor > 0.0;
+        if !valid_factor(gradient.start_factor) || !valid_factor(gradient.end_factor) {
+            return Err("Density gradient factors must be positive.".to_string());
+        }
+        if !matches!(
+            param.strategy,
+            SamplingStrategy::PoissonDisk | SamplingStrategy::Clustered { .. }
+        ) {
+            return Err(
+                "Density gradients require the Poisson disk or clustered strategy.".to_string(),
+            );
+        }
+    }
+    if let DensityField::RadialFromCentroid { edge_factor } = param.density_field {
+        if !(edge_factor.is_finite() && edge_factor > 0.0) {
+            return Err("Density field edge factor must be positive.".to_string());
+        }
+        if !matches!(
+            param.strategy,
+            SamplingStrategy::PoissonDisk | SamplingStrategy::Clustered { .. }
+        ) {
+            return Err(
+                "Density fields require the Poisson disk or clustered strategy.".to_string(),
+            );
+        }
+    }
+    if param
+        .snap_to
+        .is_some_and(|step| !(step.is_finite() && step > 0.0))
+    {
+        return Err("Snap grid step must be positive.".to_string());
+    }
+    if param.strategy == (SamplingStrategy::BestCandidate { candidates: 0 }) {
+        return Err("Best candidate sampling needs at least one candidate per point.".to_string());
+    }
+    if param.min_distance_x.is_some_and(|d| d <= 0.0)
+        || param.min_distance_y.is_some_and(|d| d <= 0.0)
+    {
+        return Err("Anisotropic spacing must be positive.".to_string());
+    }
+    Ok(())
+}
+
+/// Crée le générateur des graines des samplers et des décalages d'un remplissage.
+fn params_rng(param: &VegetationParams) -> StdRng {
+    match param.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_rng(&mut rand::rng()),
+    }
+}
+
+/// Échantillonne un polygone tuile par tuile en parallèle : l'emprise est découpée en
+/// `columns` x `rows` tuiles, chacune remplie par son propre sampler. Les points voisins
+/// de part et d'autre d'une limite de tuile peuvent être trop proches ; ils sont écartés
+/// lors de la fusion par `reconcile`.
+///
+/// # Arguments
+/// * `polygon` - Le polygone à remplir
+/// * `param` - Les paramètres de végétation
+/// * `spacing` - Espacements minimaux (x, y)
+/// * `bounds` - Emprise du polygone
+/// * `(columns, rows)` - Nombre de tuiles sur chaque axe
+/// * `rng` - Générateur des graines des samplers de tuile
+///
+/// # Retours
+/// Les points de toutes les tuiles, dans l'ordre des tuiles, et leurs statistiques cumulées
+fn sample_tiles(
+    polygon: &Polygon<f64>,
+    param: &VegetationParams,
+    spacing: (f64, f64),
+    bounds: (f64, f64, f64, f64),
+    (columns, rows): (usize, usize),
+    rng: &mut StdRng,
+) -> Result<(Vec<Point<f64>>, SamplingStats), String> {
+    let (min_x, min_y, max_x, max_y) = bounds;
+    let tile_width = (max_x - min_x) / columns as f64;
+    let tile_height = (max_y - min_y) / rows as f64;
+    let tiles: Vec<((f64, f64, f64, f64), u64)> = (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (column, row)))
+        .map(|(column, row)| {
+            let tile_bounds = (
+                min_x + column as f64 * tile_width,
+                min_y + row as f64 * tile_height,
+                min_x + (column + 1) as f64 * tile_width,
+                min_y + (row + 1) as f64 * tile_height,
+            );
+            (tile_bounds, rng.random())
+        })
+        .collect();
+
+    let sampled = tiles
+        .into_par_iter()
+        .map(|(tile_bounds, seed)| {
+            Ok(configured_sampler(param, spacing, tile_bounds)?
+                .with_spread_seeds(TILE_SPREAD_SEEDS)
+                .seeded(seed)
+                .generate_distribution_with_stats(polygon))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut points = Vec::new();
+    let mut stats = SamplingStats::default();
+    for (tile_points, tile_stats) in sampled {
+        stats.merge(&tile_stats);
+        points.extend(tile_points);
+    }
+    Ok((points, stats))
+}
+
+/// Crée le sampler d'une partie de polygone à partir des paramètres de végétation,
+/// sans graine ni plafond de points.
+///
+/// # Arguments
+/// * `param` - Les paramètres de végétation
+/// * `spacing` - Espacements minimaux (x, y) de la partie
+/// * `bounds` - Emprise de la partie
+fn configured_sampler(
+    param: &VegetationParams,
+    (spacing_x, spacing_y): (f64, f64),
+    bounds: (f64, f64, f64, f64),
+) -> Result<SpatialDistributionSampler, String> {
+    if let SamplingStrategy::TileableBlueNoise { tile_size } = param.strategy {
+        if tile_size <= spacing_x.min(spacing_y) {
+            return Err("Tile size must be greater than the minimum distance.".to_string());
+        }
+        // Le motif est généré sur sa propre grille, couvrant une tuile entière
+        let cells_per_side =
+            (tile_size / (spacing_x.min(spacing_y) / std::f64::consts::SQRT_2)).ceil() as usize;
+        let cells = cells_per_side.saturating_mul(cells_per_side);
+        if cells > max_grid_cells(param) {
+            return Err(format!(
+                "Tile size {} is too large for the minimum distance: \
+                 the pattern grid would need {} cells (maximum {}).",
+                tile_size,
+                cells,
+                max_grid_cells(param)
+            ));
+        }
+    }
+    // Les centres des bosquets sont répartis par un second sampler, dont la grille
+    // couvre toute l'emprise
+    if let SamplingStrategy::Clustered { cluster_radius, .. } = param.strategy {
+        SpatialDistributionSampler::try_new_anisotropic(
+            2.0 * cluster_radius,
+            2.0 * cluster_radius,
+            bounds,
+            max_grid_cells(param),
+        )
+        .map_err(|e| format!("Cluster radius {} is too small: {}", cluster_radius, e))?;
+    }
+    // La graine et le plafond de points sont fixés par l'appelant, pour chaque cible
+    let config = SamplerConfig {
+        min_distance: spacing_x,
+        min_distance_y: Some(spacing_y),
+        max_attempts: max_attempts(param),
+        seed_attempts: seed_attempts(param),
+        strategy: param.strategy.clone(),
+        include_boundary: param.include_boundary,
+        edge_margin: param.edge_margin,
+        interior_buffer: param.interior_buffer,
+        exclusions: param.exclusion_zones.clone(),
+        density_gradient: param.density_gradient,
+        density_field: param.density_field,
+        // Le réseau applique lui-même la variation à ses nœuds
+        jitter: match param.strategy {
+            SamplingStrategy::JitteredGrid => param.variation,
+            _ => 0.0,
+        },
+        max_grid_cells: max_grid_cells(param),
+        ..SamplerConfig::new(spacing_x)
+    };
+    SpatialDistributionSampler::from_config(config, bounds)
+}
+
+/// Prépare le remplissage au fil de l'eau d'un polygone, quand `generate_points`
+/// se résume à un disque de Poisson suivi des décalages : sans découpage, relaxation,
+/// nombre de points visé, tuiles ni arrondi à une grille, sur un polygone d'aire non nulle. Les points produits
+/// par `iter_points`, décalés avec `vary_point` et le générateur retourné, sont alors
+/// ceux que retournerait `generate_points`.
+///
+/// # Arguments
+/// * `data` - Le polygone à remplir
+/// * `param` - Les paramètres de végétation
+///
+/// # Retours
+/// Le sampler et le générateur des décalages, ou None si le polygone doit être
+/// rempli par `generate_points`
+pub fn streaming_sampler(
+    data: &Polygon<f64>,
+    param: &VegetationParams,
+) -> Option<(SpatialDistributionSampler, StdRng)> {
+    if validate_params(param).is_err()
+        || param.strategy != SamplingStrategy::PoissonDisk
+        || param.density_mode == DensityMode::TargetCount
+        || param.relax_iterations > 0
+        || param.clip_region.is_some()
+        || param.parallel_tiles.is_some()
+        || param.snap_to.is_some()
+        || has_negligible_area(data)
+    {
+        return None;
+    }
+    let bounds = calculate_polygon_bounds(data)?;
+    let spacing = target_spacing(data, param).ok()?;
+    let mut rng = params_rng(param);
+    let sampler = configured_sampler(param, spacing, bounds)
+        .ok()?
+        .with_max_points(param.max_points)
+        .seeded(rng.random());
+    Some((sampler, rng))
+}
+
+/// Résultat d'un remplissage qui n'a placé aucun point : vide si le retrait aux bords
+/// ne laisse aucune place, plus large que le rayon inscrit de chaque polygone rempli,
+/// sinon l'erreur `NO_POINTS_MESSAGE`.
+///
+/// # Arguments
+/// * `targets` - Les polygones remplis
+/// * `edge_margin` - Distance minimale entre un point et les bords
+pub(crate) fn empty_fill(
+    targets: &[Polygon<f64>],
+    edge_margin: f64,
+) -> Result<Vec<Point<f64>>, String> {
+    let no_room = edge_margin > 0.0
+        && targets
+            .iter()
+            .all(|target| target.buffer(-edge_margin).unsigned_area() == 0.0);
+    if no_room {
+        Ok(Vec::new())
+    } else {
+        Err(NO_POINTS_MESSAGE.to_string())
+    }
+}
+
+/// Indique si l'aire d'un polygone est nulle ou négligeable devant son emprise,
+/// comme celle d'un polygone aux sommets alignés.
+fn has_negligible_area(polygon: &Polygon<f64>) -> bool {
+    let Some((min_x, min_y, max_x, max_y)) = calculate_polygon_bounds(polygon) else {
+        return true;
+    };
+    let extent = (max_x - min_x).max(max_y - min_y);
+    polygon.unsigned_area() <= DEGENERATE_AREA_RATIO * extent * extent
+}
+
+/// Génère les points de végétation d'un polygone, sans mise en forme.
+///
+/// # Arguments
+/// * `data` - Le polygone à remplir
+/// * `param` - Les paramètres de végétation
+///
+/// # Retours
+/// Les points générés ou une erreur si aucun point n'a pu être placé
+pub fn generate_points(
+    data: Polygon<f64>,
+    param: &VegetationParams,
+) -> Result<Vec<Point<f64>>, String> {
+    generate_points_with_stats(data, param).map(|(points, _)| points)
+}
+
+/// Génère les points de végétation d'un polygone comme `generate_points`, avec les
+/// statistiques cumulées de tous les échantillonnages effectués.
+///
+/// # Arguments
+/// * `data` - Le polygone à remplir
+/// * `param` - Les paramètres de végétation
+///
+/// # Retours
+/// Les points générés et les statistiques, ou une erreur si aucun point n'a pu être placé
+pub fn generate_points_with_stats(
+    data: Polygon<f64>,
+    param: &VegetationParams,
+) -> Result<(Vec<Point<f64>>, SamplingStats), String> {
+    validate_params(param)?;
+    // Un polygone réduit à un point est rempli par ce point ; sans aire, aucun point
+    // ne peut être tiré à l'intérieur
+    let (min_x, min_y, max_x, max_y) =
+        calculate_polygon_bounds(&data).ok_or("Polygon has no bounding rectangle.")?;
+    if min_x == max_x && min_y == max_y {
+        let point = Point::new(min_x, min_y);
+        if param
+            .clip_region
+            .as_ref()
+            .is_some_and(|clip_region| !point_in_polygon(clip_region, &point, true))
+        {
+            return Err("Clip region does not intersect the polygon.".to_string());
+        }
+        let stats = SamplingStats {
+            generated: 1,
+            ..Default::default()
+        };
+        return Ok((vec![point], stats));
+    }
+    let data = if param.repair_geometry {
+        repair_polygon(&data)?
+    } else {
+        data
+    };
+    if has_negligible_area(&data) {
+        return Err(format!(
+            "Degenerate polygon (area ~0): zero area spanning {} x {}, no points can be placed.",
+            max_x - min_x,
+            max_y - min_y
+        ));
+    }
+    let targets = match &param.clip_region {
+        Some(clip_region) => {
+            let parts = data.intersection(clip_region).0;
+            if parts.is_empty() {
+                return Err("Clip region does not intersect the polygon.".to_string());
+            }
+            parts
+        }
+        None => vec![data],
+    };
+
+    // Les graines des samplers et les décalages sont tirés d'un même générateur,
+    // pour que tout le remplissage dépende de la seule graine des paramètres
+    let mut rng = params_rng(param);
+
+    // En nombre de points visé, chaque partie découpée reçoit une part proportionnelle à son aire
+    let total_area: f64 = targets.iter().map(|target| target.unsigned_area()).sum();
+
+    let mut points = Vec::new();
+    let mut stats = SamplingStats::default();
+    for target in &targets {
+        let bounds =
+            calculate_polygon_bounds(target).ok_or("Polygon has no bounding rectangle.")?;
+        if param.density_mode == DensityMode::TargetCount {
+            let share = (param.density * target.unsigned_area() / total_area).round();
+            if share >= 1.0 {
+                let target_param = VegetationParams {
+                    density: share,
+                    ..param.clone()
+                };
+                let mut target_points =
+                    sample_target_count(target, &target_param, bounds, &mut rng, &mut stats)?;
+                if param.relax_iterations > 0 {
+                    let (spacing_x, spacing_y) = target_spacing(target, &target_param)?;
+                    target_points = SpatialDistributionSampler::try_new_anisotropic(
+                        spacing_x,
+                        spacing_y,
+                        bounds,
+                        max_grid_cells(param),
+                    )?
+                    .with_include_boundary(param.include_boundary)
+                    .with_edge_margin(param.edge_margin)
+                    .with_interior_buffer(param.interior_buffer)
+                    .with_exclusions(param.exclusion_zones.clone())
+                    .with_density_gradient(param.density_gradient)
+                    .with_density_field(param.density_field)
+                    .with_points(target_points)
+                    .relax(param.relax_iterations as usize, target);
+                }
+                points.extend(apply_variation(
+                    target_points,
+                    target,
+                    param.variation,
+                    param.edge_margin,
+                    param.interior_buffer,
+                    &param.exclusion_zones,
+                    &mut rng,
+                ));
+            }
+            continue;
+        }
+
+        let spacing = target_spacing(target, param)?;
+        let mut sampler = configured_sampler(param, spacing, bounds)?
+            .with_max_points(
+                param
+                    .max_points
+                    .map(|max_points| max_points.saturating_sub(points.len())),
+            )
+            .seeded(rng.random());
+        let (mut target_points, target_stats) = match param.parallel_tiles {
+            Some(tiles) => {
+                let (tile_points, mut tile_stats) =
+                    sample_tiles(target, param, spacing, bounds, tiles, &mut rng)?;
+                let target_points = sampler.reconcile(tile_points);
+                tile_stats.merge(&sampler.stats());
+                (target_points, tile_stats)
+            }
+            None => sampler.generate_distribution_with_stats(target),
+        };
+        stats.merge(&target_stats);
+        if param.relax_iterations > 0 {
+            target_points = sampler.relax(param.relax_iterations as usize, target);
+        }
+        if param.strategy == SamplingStrategy::JitteredGrid {
+            points.extend(target_points);
+            continue;
+        }
+        points.extend(apply_variation(
+            target_points,
+            target,
+            param.variation,
+            param.edge_margin,
+            param.interior_buffer,
+            &param.exclusion_zones,
+            &mut rng,
+        ));
+    }
+
+    // Arrondis à la grille, les points sortis d'une cible ou entrés dans un retrait
+    // ou une zone d'exclusion sont écartés
+    if let Some(step) = param.snap_to {
+        points = points
+            .into_iter()
+            .map(|point| snap_point(point, step))
+            .filter(|point| {
+                targets.iter().any(|target| {
+                    point_in_polygon(target, point, param.include_boundary)
+                        && respects_buffers(target, point, param.edge_margin, param.interior_buffer)
+                }) && !in_exclusion_zone(&param.exclusion_zones, point)
+            })
+            .collect();
+    }
+
+    // Le mode TargetCount choisit lui-même son nombre de points : le plafond s'y applique ici
+    if let Some(max_points) = param.max_points {
+        points.truncate(max_points);
+    }
+
+    println!(
+        "Generated {} points using spatial distribution algorithm",
         points.len()
     );
 
     if points.is_empty() {
-        return Err("No points could be generated for the given polygon and density.".to_string());
+        points = empty_fill(&targets, param.edge_margin)?;
     }
 
-    let mut result = Vec::new();
+    stats.generated = points.len();
+    Ok((points, stats))
+}
 
-    for point in &points {
-        let end_row = format!(
-            "									20				20096																		0	{}	",
-            param.type_value
-        );
-        let wkt_string = format!("       {}\t       {}{}\n", point.x(), point.y(), end_row);
-        result.push(wkt_string);
+#[tauri::command]
+pub fn fill_polygon(data: Polygon<f64>, param: VegetationParams) -> Result<Vec<String>, String> {
+    fill_polygon_with_stats(data, param).map(|filled| filled.rows)
+}
+
+/// Lignes d'un polygone rempli, accompagnées des statistiques de l'échantillonnage.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FilledPolygon {
+    pub rows: Vec<String>,
+    pub stats: SamplingStats,
+}
+
+/// Commande Tauri pour remplir un polygone comme `fill_polygon` en retournant aussi
+/// les statistiques, par exemple pour afficher le taux d'acceptation des candidats.
+///
+/// # Arguments
+/// * `data` - Le polygone à remplir
+/// * `param` - Les paramètres de végétation
+///
+/// # Retours
+/// Les lignes générées et leurs statistiques, ou une erreur si aucun point n'a pu être placé
+#[tauri::command]
+pub fn fill_polygon_with_stats(
+    data: Polygon<f64>,
+    param: VegetationParams,
+) -> Result<FilledPolygon, String> {
+    let options = ExportOptions::default();
+    let (points, stats) = generate_points_with_stats(data, &param)?;
+    Ok(FilledPolygon {
+        rows: points
+            .iter()
+            .map(|point| format_point_row(point, &param, &options))
+            .collect(),
+        stats,
+    })
+}
+
+/// Commande Tauri pour obtenir les statistiques du remplissage d'un polygone,
+/// par exemple pour afficher le taux de rejet dans le panneau d'aperçu.
+///
+/// # Arguments
+/// * `data` - Le polygone à remplir
+/// * `param` - Les paramètres de végétation
+///
+/// # Retours
+/// Les statistiques de l'échantillonnage ou une erreur si aucun point n'a pu être placé
+#[tauri::command]
+pub fn get_sampling_stats(
+    data: Polygon<f64>,
+    param: VegetationParams,
+) -> Result<SamplingStats, String> {
+    generate_points_with_stats(data, &param).map(|(_, stats)| stats)
+}
+
+/// Commande Tauri pour proposer un point isolé dans un polygone, par exemple
+/// l'emplacement d'un repère placé à la main.
+///
+/// # Arguments
+/// * `polygon` - Le polygone dans lequel placer le point
+/// * `avoid` - Les points existants à tenir à distance
+/// * `min_distance` - Distance minimale entre le point proposé et les points `avoid`
+///
+/// # Retours
+/// Le point proposé, ou None si aucun emplacement n'a été trouvé
+#[tauri::command]
+pub fn suggest_point(
+    polygon: Polygon<f64>,
+    avoid: Vec<SimplePoint>,
+    min_distance: f64,
+) -> Option<SimplePoint> {
+    if min_distance.is_nan() || min_distance <= 0.0 {
+        return None;
     }
+    // L'emprise est élargie pour que les points `avoid` proches du polygone,
+    // mais à l'extérieur, soient aussi enregistrés dans la grille ; les autres sont ignorés
+    let (min_x, min_y, max_x, max_y) = calculate_polygon_bounds(&polygon)?;
+    let bounds = (
+        min_x - min_distance,
+        min_y - min_distance,
+        max_x + min_distance,
+        max_y + min_distance,
+    );
+    let avoid: Vec<Point<f64>> = avoid
+        .iter()
+        .filter(|p| p.x >= bounds.0 && p.x <= bounds.2 && p.y >= bounds.1 && p.y <= bounds.3)
+        .map(|p| Point::new(p.x, p.y))
+        .collect();
+
+    SpatialDistributionSampler::try_new_anisotropic(
+        min_distance,
+        min_distance,
+        bounds,
+        DEFAULT_MAX_GRID_CELLS,
+    )
+    .ok()?
+    .suggest_point(&polygon, &avoid)
+    .map(|point| SimplePoint {
+        x: point.x(),
+        y: point.y(),
+    })
+}
+
+/// Reprojette un polygone dans la projection d'échantillonnage des paramètres, comme
+/// le fait l'export, pour que ses aires et ses distances soient dans l'unité de `density`.
+///
+/// # Retours
+/// Le polygone reprojeté, ou inchangé sans projection d'échantillonnage
+fn to_sampling_projection(
+    polygon: &Polygon<f64>,
+    param: &VegetationParams,
+) -> Result<Polygon<f64>, String> {
+    match (param.source_epsg, param.sampling_epsg) {
+        (Some(source), Some(sampling)) => reproject_polygon(polygon, source, sampling),
+        _ => Ok(polygon.clone()),
+    }
+}
+
+/// Commande Tauri pour estimer le nombre total de points d'un export avant de le lancer.
+/// L'estimation se fait à partir de l'aire des polygones, mesurée dans la projection
+/// d'échantillonnage s'il y en a une, et reste rapide même pour des dizaines de milliers
+/// de polygones.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV
+/// * `param` - Les paramètres de végétation de l'export
+/// * `geometry_column` - Colonne contenant la géométrie WKT, la première si non définie
+/// * `delimiter` - Délimiteur des colonnes, la tabulation si non défini
+///
+/// # Retours
+/// Le nombre de points attendu ou une erreur
+#[tauri::command]
+pub fn estimate_total_points(
+    file_path: &str,
+    param: VegetationParams,
+    geometry_column: Option<GeometryColumn>,
+    delimiter: Option<String>,
+) -> Result<usize, String> {
+    validate_params(&param)?;
+
+    let estimate = |polygon: &Polygon<f64>| match target_spacing(polygon, &param) {
+        Ok((spacing_x, spacing_y)) => estimate_point_count(polygon, spacing_x, spacing_y),
+        Err(_) => 0,
+    };
+
+    Ok(parse_csv_file(file_path, geometry_column, delimiter)?
+        .iter()
+        .map(|polygon| to_sampling_projection(polygon, &param))
+        .collect::<Result<Vec<_>, _>>()?
+        .iter()
+        .map(|polygon| match &param.clip_region {
+            Some(clip_region) => polygon
+                .intersection(clip_region)
+                .0
+                .iter()
+                .map(&estimate)
+                .sum(),
+            None => estimate(polygon),
+        })
+        .sum())
+}
+
+/// Commande Tauri pour connaître la distance minimale effectivement utilisée par le sampler
+/// pour un polygone, une fois appliqués le mode de densité, l'espacement anisotrope
+/// et la région de découpe, dans la projection d'échantillonnage s'il y en a une.
+/// En nombre de points visé, c'est la distance initiale de la recherche par dichotomie.
+///
+/// # Arguments
+/// * `polygon` - Le polygone à remplir
+/// * `param` - Les paramètres de végétation
+///
+/// # Retours
+/// La plus petite des distances minimales selon X et Y, ou une erreur si les paramètres
+/// sont invalides ou si le polygone n'a pas d'aire
+#[tauri::command]
+pub fn resolve_effective_min_distance(
+    polygon: Polygon<f64>,
+    param: VegetationParams,
+) -> Result<f64, String> {
+    validate_params(&param)?;
+    let polygon = to_sampling_projection(&polygon, &param)?;
+
+    let (spacing_x, spacing_y) = match &param.clip_region {
+        Some(clip_region) => {
+            let parts = polygon.intersection(clip_region).0;
+            let total_area: f64 = parts.iter().map(|part| part.unsigned_area()).sum();
+            let first = parts
+                .first()
+                .ok_or("Clip region does not intersect the polygon.")?;
+            // En nombre de points visé, la part de chaque morceau est proportionnelle à son aire
+            let density = match param.density_mode {
+                DensityMode::TargetCount => param.density * first.unsigned_area() / total_area,
+                DensityMode::MinDistance | DensityMode::PointsPerHectare => param.density,
+            };
+            target_spacing(
+                first,
+                &VegetationParams {
+                    density,
+                    ..param.clone()
+                },
+            )?
+        }
+        None => target_spacing(&polygon, &param)?,
+    };
 
-    Ok(result)
+    Ok(spacing_x.min(spacing_y))
 }
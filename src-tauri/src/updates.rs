@@ -0,0 +1,117 @@
+use serde::Serialize;
+use tauri::AppHandle;
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+/// Durée maximale de la recherche d'une mise à jour.
+const CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Durée maximale du téléchargement et de l'installation d'une mise à jour.
+const DOWNLOAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Résultat d'une recherche de mise à jour, transmis à l'interface.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct UpdateStatus {
+    pub available: bool,
+    /// Version installée
+    pub current: String,
+    /// Dernière version publiée, la version installée si aucune mise à jour n'existe
+    pub latest: String,
+}
+
+/// Recherche une mise à jour publiée.
+///
+/// # Retours
+/// La mise à jour disponible, None si l'application est à jour, ou une erreur
+/// si la recherche a échoué ou dépassé `CHECK_TIMEOUT`
+async fn fetch_update(app: &AppHandle) -> Result<Option<Update>, String> {
+    let updater = app
+        .updater_builder()
+        .build()
+        .map_err(|e| format!("Failed to build updater: {}", e))?;
+    match tokio::time::timeout(CHECK_TIMEOUT, updater.check()).await {
+        Ok(result) => result.map_err(|e| format!("Update check failed: {}", e)),
+        Err(_) => Err(format!(
+            "Update check timed out after {} seconds",
+            CHECK_TIMEOUT.as_secs()
+        )),
+    }
+}
+
+/// Télécharge et installe une mise à jour, puis redémarre l'application.
+async fn download_and_install(app: &AppHandle, update: Update) -> Result<(), String> {
+    println!("Downloading update {}...", update.version);
+    match tokio::time::timeout(
+        DOWNLOAD_TIMEOUT,
+        update.download_and_install(
+            |chunk_length, content_length| {
+                if let Some(total) = content_length {
+                    let progress = (chunk_length as f64 / total as f64) * 100.0;
+                    println!("Download progress: {:.1}%", progress);
+                } else {
+                    println!("Downloaded: {} bytes", chunk_length);
+                }
+            },
+            || println!("Download finished"),
+        ),
+    )
+    .await
+    {
+        Ok(Ok(())) => {
+            println!("Update installed successfully\nRestarting application...");
+            app.restart();
+        }
+        Ok(Err(e)) => Err(format!("Failed to download/install update: {}", e)),
+        Err(_) => Err("Update download timed out after 5 minutes".to_string()),
+    }
+}
+
+/// Recherche et installe une mise à jour au démarrage. Les erreurs sont affichées
+/// sans interrompre le démarrage.
+pub async fn check_for_updates(app: AppHandle) {
+    match fetch_update(&app).await {
+        Ok(Some(update)) => {
+            println!(
+                "Update available: {} -> {}",
+                update.current_version, update.version
+            );
+            if let Err(e) = download_and_install(&app, update).await {
+                eprintln!("{}", e);
+            }
+        }
+        Ok(None) => println!("No updates available - you're on the latest version"),
+        Err(e) => eprintln!("{} - continuing startup", e),
+    }
+}
+
+/// Commande Tauri pour rechercher une mise à jour à la demande, sans l'installer.
+///
+/// # Retours
+/// Les versions installée et publiée, ou une erreur si la recherche a échoué
+#[tauri::command]
+pub async fn check_for_updates_now(app: AppHandle) -> Result<UpdateStatus, String> {
+    let current = app.package_info().version.to_string();
+    Ok(match fetch_update(&app).await? {
+        Some(update) => UpdateStatus {
+            available: true,
+            current,
+            latest: update.version,
+        },
+        None => UpdateStatus {
+            available: false,
+            latest: current.clone(),
+            current,
+        },
+    })
+}
+
+/// Commande Tauri pour installer la mise à jour disponible, puis redémarrer l'application.
+///
+/// # Retours
+/// Une erreur si aucune mise à jour n'est disponible ou si l'installation a échoué
+#[tauri::command]
+pub async fn install_update(app: AppHandle) -> Result<(), String> {
+    let update = fetch_update(&app)
+        .await?
+        .ok_or_else(|| "No update available".to_string())?;
+    download_and_install(&app, update).await
+}
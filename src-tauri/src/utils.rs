@@ -1,20 +1,40 @@
-use csv::ReaderBuilder;
+use chrono::{DateTime, Local};
+use csv::{ReaderBuilder, StringRecord};
+use flate2::Compression;
+use flate2::write::GzEncoder;
 use geo::Geometry;
+use geo::Point;
 use geo::Polygon;
+use geo::{Coord, LineString, MapCoords};
+use rand::rngs::StdRng;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::error::Error;
-use std::fs::File;
-use std::io::BufWriter;
-use std::io::Write;
+use std::io::{BufRead, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::Instant;
 use tauri::Emitter;
 
 use tauri::{AppHandle, State};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::UnboundedReceiverStream;
 use wkt::Wkt;
 
-use crate::get_export_path;
-use crate::models::processing::VegetationProcessingState;
+use crate::models::export::{ExportOptions, ExportSummary, OutputFormat, OutputTemplate, ZSource};
+use crate::models::processing::{
+    CallbackReporter, DensityAdjustment, ProgressReporter, VegetationProcessingState,
+    VegetationProgressInfo,
+};
+use crate::models::settings::Settings;
 use crate::models::vegetations::VegetationParams;
-use crate::sampling::fill_polygon;
+use crate::sampling::{
+    SpatialDistributionSampler, calculate_polygon_bounds, empty_fill, generate_points,
+    repair_polygon, streaming_sampler, vary_point,
+};
+use crate::{get_coordinate_precision, get_export_path};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SimplePoint {
@@ -28,182 +48,2961 @@ pub struct SimplePolygon {
     pub interiors: Vec<Vec<SimplePoint>>,
 }
 
+/// Paramètres lus sur une ligne du fichier CSV, qui remplacent les paramètres globaux
+/// pour le polygone de cette ligne.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PolygonOverrides {
+    pub variation: Option<f64>,
+    /// Numéro de la ligne du fichier source, cité dans les messages d'erreur de l'export
+    #[serde(default)]
+    pub source_line: Option<usize>,
+    /// Valeurs non vides des autres colonnes de la ligne, par nom de colonne ; elles
+    /// remplacent les valeurs par défaut des colonnes de même nom du fichier de sortie
+    #[serde(default)]
+    pub attributes: BTreeMap<String, String>,
+    /// Altitude moyenne du premier anneau d'une géométrie `POLYGON Z`
+    #[serde(default)]
+    pub elevation: Option<f64>,
+}
+
+impl PolygonOverrides {
+    /// Retourne les paramètres globaux complétés par les valeurs propres au polygone.
+    pub fn apply(&self, param: &VegetationParams) -> VegetationParams {
+        let mut param = param.clone();
+        if let Some(variation) = self.variation {
+            param.variation = variation;
+        }
+        param
+    }
+}
+
+/// Colonne du fichier CSV contenant la géométrie WKT, désignée par son indice
+/// (à partir de 0) ou par son nom dans l'en-tête.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum GeometryColumn {
+    Index(usize),
+    Name(String),
+}
+
+impl GeometryColumn {
+    /// Retourne l'indice de la colonne dans l'en-tête du fichier.
+    ///
+    /// # Arguments
+    /// * `headers` - L'en-tête du fichier CSV
+    ///
+    /// # Retours
+    /// L'indice de la colonne ou une erreur si elle n'existe pas
+    fn resolve(&self, headers: &StringRecord) -> Result<usize, String> {
+        match self {
+            GeometryColumn::Index(index) if *index < headers.len() => Ok(*index),
+            GeometryColumn::Index(index) => Err(format!(
+                "Geometry column {} is out of range: the file has {} columns",
+                index,
+                headers.len()
+            )),
+            GeometryColumn::Name(name) => headers
+                .iter()
+                .position(|header| header.trim().eq_ignore_ascii_case(name.trim()))
+                .ok_or_else(|| format!("Geometry column '{}' not found in CSV header", name)),
+        }
+    }
+}
+
+/// Délimiteurs essayés par la détection automatique, par ordre de préférence.
+const SNIFFED_DELIMITERS: [u8; 4] = [b'\t', b';', b',', b'|'];
+
+/// Nombre de lignes lues pour détecter le délimiteur d'un fichier.
+const SNIFFED_LINES: usize = 20;
+
+/// Détecte le délimiteur d'un fichier CSV : le premier délimiteur qui découpe
+/// les premières lignes en un même nombre de colonnes, supérieur à un.
+/// Retourne la tabulation si aucun ne convient.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV
+pub fn sniff_delimiter(file_path: &str) -> Result<u8, String> {
+    for delimiter in SNIFFED_DELIMITERS {
+        let mut reader = ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_path(file_path)
+            .map_err(|e| format!("Failed to open file: {}", e))?;
+        let counts: Result<Vec<usize>, _> = reader
+            .records()
+            .take(SNIFFED_LINES)
+            .map(|record| record.map(|r| r.len()))
+            .collect();
+        if let Ok(counts) = counts
+            && let Some(&first) = counts.first()
+            && first > 1
+            && counts.iter().all(|&count| count == first)
+        {
+            return Ok(delimiter);
+        }
+    }
+    Ok(b'\t')
+}
+
+/// Retourne le délimiteur à utiliser pour lire un fichier CSV.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV
+/// * `delimiter` - Un caractère ASCII, `tab`, `auto` pour le détecter, ou la tabulation si non défini
+fn resolve_delimiter(file_path: &str, delimiter: Option<&str>) -> Result<u8, String> {
+    match delimiter {
+        None => Ok(b'\t'),
+        Some(value) if value.eq_ignore_ascii_case("auto") => sniff_delimiter(file_path),
+        Some(value) if value.eq_ignore_ascii_case("tab") => Ok(b'\t'),
+        Some(value) if value.len() == 1 && value.is_ascii() => Ok(value.as_bytes()[0]),
+        Some(value) => Err(format!("Invalid CSV delimiter: {:?}", value)),
+    }
+}
+
+/// Ligne ignorée par la lecture tolérante d'un fichier CSV : numéro de ligne et raison.
+pub type SkippedRow = (usize, String);
+
+/// Résultat de la lecture tolérante d'un fichier CSV.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LenientCsv {
+    /// Les polygones lus, dans l'ordre du fichier
+    pub polygons: Vec<Polygon<f64>>,
+    /// Les lignes ignorées, avec la raison de leur rejet
+    pub skipped_rows: Vec<SkippedRow>,
+}
+
+/// Rôle des colonnes d'un fichier CSV.
+struct CsvColumns {
+    /// Indice de la colonne contenant la géométrie WKT
+    geometry: usize,
+    /// Indice de la colonne `variation`, si présente
+    variation: Option<usize>,
+    /// Indices et noms des autres colonnes, recopiées dans les attributs du polygone
+    attributes: Vec<(usize, String)>,
+}
+
+/// Numérotation des lignes d'un fichier CSV.
+///
+/// La position de début d'un enregistrement donnée par `csv` précède les lignes vides
+/// ignorées et, avec des fins de ligne CRLF, le `\n` de la ligne précédente : la ligne
+/// d'un enregistrement est donc déduite de la position atteinte à sa fin.
+struct CsvLineCounter {
+    /// Taille du fichier en octets
+    length: u64,
+    /// Indique si les lignes se terminent par CRLF, le `\n` étant alors lu avec l'enregistrement suivant
+    crlf: bool,
+    /// Indique si le fichier se termine par un saut de ligne
+    ends_with_newline: bool,
+}
+
+impl CsvLineCounter {
+    fn new(file_path: &str) -> Result<Self, String> {
+        let mut file =
+            std::fs::File::open(file_path).map_err(|e| format!("Failed to open file: {}", e))?;
+        let length = file
+            .metadata()
+            .map_err(|e| format!("Failed to open file: {}", e))?
+            .len();
+        let mut first_line = Vec::new();
+        std::io::BufReader::new(Read::by_ref(&mut file).take(64 * 1024))
+            .read_until(b'\n', &mut first_line)
+            .map_err(|e| format!("CSV read error: {}", e))?;
+        let mut last_byte = [0u8];
+        if length > 0 {
+            file.seek(SeekFrom::End(-1))
+                .and_then(|_| file.read_exact(&mut last_byte))
+                .map_err(|e| format!("CSV read error: {}", e))?;
+        }
+        Ok(Self {
+            length,
+            crlf: first_line.ends_with(b"\r\n"),
+            ends_with_newline: last_byte[0] == b'\n',
+        })
+    }
+
+    /// Ligne du fichier où commence un enregistrement.
+    ///
+    /// # Arguments
+    /// * `record` - Enregistrement venant d'être lu
+    /// * `end` - Position du lecteur après la lecture de l'enregistrement
+    ///
+    /// # Retours
+    /// Le numéro de la ligne, à partir de 1
+    fn line(&self, record: &StringRecord, end: &csv::Position) -> u64 {
+        let inner_newlines: u64 = record
+            .iter()
+            .map(|field| field.matches('\n').count() as u64)
+            .sum();
+        let terminated = !self.crlf && (end.byte() < self.length || self.ends_with_newline);
+        end.line() - inner_newlines - u64::from(terminated)
+    }
+}
+
+/// Retire l'altitude d'une géométrie WKT en trois dimensions (`POLYGON Z`), que la
+/// conversion en géométrie geo ne lit pas. Une géométrie en deux dimensions est
+/// retournée telle quelle.
+///
+/// # Arguments
+/// * `wkt` - La géométrie WKT
+///
+/// # Retours
+/// La géométrie en deux dimensions et l'altitude moyenne des sommets de son premier
+/// anneau (sommet de fermeture exclu), None pour une géométrie en deux dimensions
+fn strip_elevation(wkt: &str) -> Result<(String, Option<f64>), String> {
+    let wkt = wkt.trim();
+    let Some(start) = wkt.find('(') else {
+        return Ok((wkt.to_string(), None));
+    };
+    let tag: Vec<&str> = wkt[..start].split_whitespace().collect();
+    let [kind, dimension] = tag[..] else {
+        return Ok((wkt.to_string(), None));
+    };
+    if !dimension.eq_ignore_ascii_case("z") {
+        return Ok((wkt.to_string(), None));
+    }
+
+    let mut flat = kind.to_string();
+    let mut first_ring: Vec<f64> = Vec::new();
+    let mut first_ring_closed = false;
+    let mut coordinate = String::new();
+    for c in wkt[start..].chars() {
+        if !matches!(c, '(' | ')' | ',') {
+            coordinate.push(c);
+            continue;
+        }
+        let values: Vec<&str> = coordinate.split_whitespace().collect();
+        match values[..] {
+            [] => {}
+            [x, y, z] => {
+                let z: f64 = z
+                    .parse()
+                    .map_err(|_| format!("Invalid Z coordinate '{}'", z))?;
+                if !first_ring_closed {
+                    first_ring.push(z);
+                }
+                flat.push_str(x);
+                flat.push(' ');
+                flat.push_str(y);
+            }
+            _ => {
+                return Err(format!(
+                    "Expected X Y Z coordinates, got '{}'",
+                    coordinate.trim()
+                ));
+            }
+        }
+        coordinate.clear();
+        first_ring_closed |= c == ')';
+        flat.push(c);
+    }
+
+    if first_ring.len() > 1 && first_ring.first() == first_ring.last() {
+        first_ring.pop();
+    }
+    let elevation =
+        (!first_ring.is_empty()).then(|| first_ring.iter().sum::<f64>() / first_ring.len() as f64);
+    Ok((flat, elevation))
+}
+
+/// Lit une ligne du fichier CSV.
+///
+/// # Arguments
+/// * `record` - La ligne lue
+/// * `line` - Numéro de la ligne dans le fichier
+/// * `columns` - Rôle des colonnes du fichier
+///
+/// # Retours
+/// Le polygone et ses paramètres propres, None si la géométrie est vide,
+/// ou une erreur citant le numéro de ligne
+fn parse_csv_record(
+    record: &StringRecord,
+    line: u64,
+    columns: &CsvColumns,
+) -> Result<Option<(Polygon<f64>, PolygonOverrides)>, String> {
+    let Some(geometry_field) = record.get(columns.geometry) else {
+        return Err(format!("Missing geometry field on line {}", line));
+    };
+    if geometry_field.trim().is_empty() {
+        return Ok(None);
+    }
+    let (flat_geometry, elevation) = strip_elevation(geometry_field)
+        .map_err(|e| format!("{} on line {}: {}", e, line, geometry_field))?;
+    let wkt: Wkt<f64> = flat_geometry
+        .parse()
+        .map_err(|_| format!("Invalid WKT format on line {}: {}", line, geometry_field))?;
+    let geometry: Geometry<f64> = wkt.try_into().map_err(|_| {
+        format!(
+            "Cannot convert WKT to geo geometry on line {}: {}",
+            line, geometry_field
+        )
+    })?;
+    let Geometry::Polygon(polygon) = geometry else {
+        return Err(format!(
+            "WKT is not a Polygon on line {}: {}",
+            line, geometry_field
+        ));
+    };
+
+    let variation = match columns
+        .variation
+        .and_then(|column| record.get(column))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        Some(value) => {
+            let variation: f64 = value
+                .parse()
+                .map_err(|_| format!("Invalid variation on line {}: {}", line, value))?;
+            if variation.is_nan() || variation < 0.0 {
+                return Err(format!(
+                    "Variation cannot be negative on line {}: {}",
+                    line, value
+                ));
+            }
+            Some(variation)
+        }
+        None => None,
+    };
+    let attributes = columns
+        .attributes
+        .iter()
+        .filter_map(|(index, name)| {
+            let value = record.get(*index)?.trim();
+            (!value.is_empty()).then(|| (name.clone(), value.to_string()))
+        })
+        .collect();
+
+    Ok(Some((
+        polygon,
+        PolygonOverrides {
+            variation,
+            source_line: Some(line as usize),
+            attributes,
+            elevation,
+        },
+    )))
+}
+
+/// Lit les lignes d'un fichier CSV. En mode strict (`skipped` à None), la première ligne
+/// invalide interrompt la lecture ; sinon elle est ajoutée à `skipped` et la lecture continue.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV
+/// * `geometry_column` - Colonne contenant la géométrie WKT, la première si non définie
+/// * `delimiter` - Délimiteur des colonnes (voir `resolve_delimiter`), la tabulation si non défini
+/// * `skipped` - Destination des lignes ignorées, None pour une lecture stricte
+///
+/// # Retours
+/// Les polygones et leurs paramètres propres, dans l'ordre du fichier
+fn read_csv_rows(
+    file_path: &str,
+    geometry_column: Option<&GeometryColumn>,
+    delimiter: Option<&str>,
+    skipped: Option<&mut Vec<SkippedRow>>,
+) -> Result<Vec<(Polygon<f64>, PolygonOverrides)>, String> {
+    let delimiter = resolve_delimiter(file_path, delimiter)?;
+    read_csv_records(file_path, delimiter, geometry_column, true, skipped)
+}
+
+/// Lit les lignes d'un fichier CSV comme `read_csv_rows`, avec un délimiteur déjà résolu.
+/// Sans en-tête, la colonne de géométrie ne peut être désignée que par son indice et
+/// aucune colonne `variation` n'est lue.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV
+/// * `delimiter` - Délimiteur des colonnes
+/// * `geometry_column` - Colonne contenant la géométrie WKT, la première si non définie
+/// * `has_headers` - Indique si la première ligne du fichier est un en-tête
+/// * `skipped` - Destination des lignes ignorées, None pour une lecture stricte
+///
+/// # Retours
+/// Les polygones et leurs paramètres propres, dans l'ordre du fichier
+fn read_csv_records(
+    file_path: &str,
+    delimiter: u8,
+    geometry_column: Option<&GeometryColumn>,
+    has_headers: bool,
+    mut skipped: Option<&mut Vec<SkippedRow>>,
+) -> Result<Vec<(Polygon<f64>, PolygonOverrides)>, String> {
+    let (mut reader, columns) =
+        open_csv_reader(file_path, delimiter, geometry_column, has_headers)?;
+    let lines = CsvLineCounter::new(file_path)?;
+    let mut polygons = Vec::new();
+    let mut record = StringRecord::new();
+
+    loop {
+        let row = match reader.read_record(&mut record) {
+            Ok(false) => break,
+            Ok(true) => {
+                let line = lines.line(&record, reader.position());
+                parse_csv_record(&record, line, &columns).map_err(|e| (line, e))
+            }
+            // Une erreur d'entrée/sortie rend la suite du fichier illisible
+            Err(e) if e.is_io_error() => return Err(format!("CSV read error: {}", e)),
+            Err(e) => Err((
+                e.position().map_or(0, |p| p.line()),
+                format!("CSV read error: {}", e),
+            )),
+        };
+        match (row, skipped.as_deref_mut()) {
+            (Ok(Some(row)), _) => polygons.push(row),
+            (Ok(None), _) => {}
+            (Err((line, reason)), Some(skipped)) => skipped.push((line as usize, reason)),
+            (Err((_, reason)), None) => return Err(reason),
+        }
+    }
+    Ok(polygons)
+}
+
+/// Ouvre un fichier CSV et détermine le rôle de ses colonnes d'après l'en-tête.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV
+/// * `delimiter` - Délimiteur des colonnes
+/// * `geometry_column` - Colonne contenant la géométrie WKT, la première si non définie
+/// * `has_headers` - Indique si la première ligne du fichier est un en-tête
+///
+/// # Retours
+/// Le lecteur, positionné sur la première ligne de données, et le rôle des colonnes
+fn open_csv_reader(
+    file_path: &str,
+    delimiter: u8,
+    geometry_column: Option<&GeometryColumn>,
+    has_headers: bool,
+) -> Result<(csv::Reader<std::fs::File>, CsvColumns), String> {
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(has_headers)
+        .from_path(file_path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    let columns = if has_headers {
+        // L'en-tête peut être encodé en Latin-1 : seuls les noms de colonnes en dépendent
+        let headers: StringRecord = reader
+            .byte_headers()
+            .map_err(|e| format!("CSV read error: {}", e))?
+            .iter()
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .collect();
+        let geometry = match geometry_column {
+            Some(column) => column.resolve(&headers)?,
+            None => 0,
+        };
+        let variation = headers
+            .iter()
+            .position(|name| name.trim().eq_ignore_ascii_case("variation"));
+        let attributes = headers
+            .iter()
+            .enumerate()
+            .filter(|(index, name)| {
+                *index != geometry && Some(*index) != variation && !name.trim().is_empty()
+            })
+            .map(|(index, name)| (index, name.trim().to_string()))
+            .collect();
+        CsvColumns {
+            geometry,
+            variation,
+            attributes,
+        }
+    } else {
+        // Sans en-tête, une colonne hors limites est signalée à la première ligne lue
+        let geometry_index = match geometry_column {
+            Some(GeometryColumn::Index(index)) => *index,
+            Some(GeometryColumn::Name(name)) => {
+                return Err(format!(
+                    "Geometry column '{}' cannot be found in a CSV file without header",
+                    name
+                ));
+            }
+            None => 0,
+        };
+        CsvColumns {
+            geometry: geometry_index,
+            variation: None,
+            attributes: Vec::new(),
+        }
+    };
+    Ok((reader, columns))
+}
+
+/// Polygone lu dans le fichier source, avec ses paramètres propres.
+pub type PolygonRow = (Polygon<f64>, PolygonOverrides);
+
+/// Lit les lignes d'un fichier CSV une à une, sans charger le fichier en mémoire.
+/// Les lignes dont la géométrie est vide sont ignorées.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV
+/// * `geometry_column` - Colonne contenant la géométrie WKT, la première si non définie
+/// * `delimiter` - Délimiteur des colonnes (voir `resolve_delimiter`), la tabulation si non défini
+///
+/// # Retours
+/// Les polygones et leurs paramètres propres dans l'ordre du fichier, chaque ligne
+/// invalide donnant une erreur citant son numéro, ou une erreur si le fichier est illisible
+pub fn iter_csv_rows(
+    file_path: &str,
+    geometry_column: Option<&GeometryColumn>,
+    delimiter: Option<&str>,
+) -> Result<impl Iterator<Item = Result<PolygonRow, String>> + use<>, String> {
+    let delimiter = resolve_delimiter(file_path, delimiter)?;
+    let (mut reader, columns) = open_csv_reader(file_path, delimiter, geometry_column, true)?;
+    let lines = CsvLineCounter::new(file_path)?;
+    let mut record = StringRecord::new();
+    let mut done = false;
+    Ok(std::iter::from_fn(move || {
+        loop {
+            if done {
+                return None;
+            }
+            return match reader.read_record(&mut record) {
+                Ok(false) => None,
+                Ok(true) => {
+                    let line = lines.line(&record, reader.position());
+                    match parse_csv_record(&record, line, &columns).transpose() {
+                        Some(row) => Some(row),
+                        None => continue,
+                    }
+                }
+                Err(e) => {
+                    // Une erreur d'entrée/sortie rend la suite du fichier illisible
+                    done = e.is_io_error();
+                    Some(Err(format!("CSV read error: {}", e)))
+                }
+            };
+        }
+    }))
+}
+
+/// Compte les polygones d'un fichier CSV sans lire leur géométrie, pour connaître
+/// d'avance le nombre de lignes d'un export lu au fil de l'eau. Les lignes dont la
+/// géométrie est vide ne sont pas comptées.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV
+/// * `geometry_column` - Colonne contenant la géométrie WKT, la première si non définie
+/// * `delimiter` - Délimiteur des colonnes (voir `resolve_delimiter`), la tabulation si non défini
+///
+/// # Retours
+/// Le nombre de lignes de données, ou une erreur si le fichier est illisible
+pub fn count_csv_rows(
+    file_path: &str,
+    geometry_column: Option<&GeometryColumn>,
+    delimiter: Option<&str>,
+) -> Result<usize, String> {
+    let delimiter = resolve_delimiter(file_path, delimiter)?;
+    let (mut reader, columns) = open_csv_reader(file_path, delimiter, geometry_column, true)?;
+    let mut count = 0;
+    for result in reader.byte_records() {
+        match result {
+            Ok(record) => {
+                if record
+                    .get(columns.geometry)
+                    .is_none_or(|field| !field.trim_ascii().is_empty())
+                {
+                    count += 1;
+                }
+            }
+            Err(e) if e.is_io_error() => return Err(format!("CSV read error: {}", e)),
+            // Une ligne mal formée sera signalée par la lecture elle-même
+            Err(_) => count += 1,
+        }
+    }
+    Ok(count)
+}
+
+/// Lit les polygones d'un fichier CSV un à un, avec le format de `parse_csv_file`
+/// (tabulation, géométrie dans la première colonne). Une erreur d'ouverture du fichier
+/// est rendue comme premier élément.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV
+///
+/// # Retours
+/// Les polygones dans l'ordre du fichier, ou l'erreur de chaque ligne invalide
+pub fn iter_csv_polygons(
+    file_path: &str,
+) -> impl Iterator<Item = Result<Polygon<f64>, String>> + use<> {
+    let (rows, error) = match iter_csv_rows(file_path, None, None) {
+        Ok(rows) => (Some(rows), None),
+        Err(e) => (None, Some(Err(e))),
+    };
+    error.into_iter().chain(
+        rows.into_iter()
+            .flatten()
+            .map(|row| row.map(|(polygon, _)| polygon)),
+    )
+}
+
+/// Lit les polygones d'un fichier CSV ainsi que les paramètres propres à chaque ligne.
+/// Une colonne nommée `variation` (casse ignorée) remplace la variation globale.
+/// Les lignes dont la géométrie est vide sont ignorées.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV
+/// * `geometry_column` - Colonne contenant la géométrie WKT, la première si non définie
+/// * `delimiter` - Délimiteur des colonnes (voir `resolve_delimiter`), la tabulation si non défini
+///
+/// # Retours
+/// Les polygones et leurs paramètres propres, dans l'ordre du fichier, ou une erreur
+/// à la première ligne invalide
+pub fn parse_csv_file_with_overrides(
+    file_path: &str,
+    geometry_column: Option<&GeometryColumn>,
+    delimiter: Option<&str>,
+) -> Result<Vec<(Polygon<f64>, PolygonOverrides)>, String> {
+    read_csv_rows(file_path, geometry_column, delimiter, None)
+}
+
+/// Lit les polygones d'un fichier CSV dont le délimiteur et la colonne de géométrie
+/// sont connus, avec ou sans en-tête.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV
+/// * `delimiter` - Délimiteur des colonnes, par exemple `b';'`
+/// * `geometry_column` - Indice (à partir de 0) de la colonne contenant la géométrie WKT
+/// * `has_headers` - Indique si la première ligne du fichier est un en-tête
+///
+/// # Retours
+/// Les polygones, dans l'ordre du fichier, ou une erreur citant le numéro de la première
+/// ligne invalide, y compris si elle n'a pas de colonne `geometry_column`
+pub fn parse_csv_file_with(
+    file_path: &str,
+    delimiter: u8,
+    geometry_column: usize,
+    has_headers: bool,
+) -> Result<Vec<Polygon<f64>>, String> {
+    Ok(read_csv_records(
+        file_path,
+        delimiter,
+        Some(&GeometryColumn::Index(geometry_column)),
+        has_headers,
+        None,
+    )?
+    .into_iter()
+    .map(|(polygon, _)| polygon)
+    .collect())
+}
+
+/// Commande Tauri de lecture d'un fichier CSV. Sans option, la géométrie est lue dans
+/// la première colonne d'un fichier délimité par des tabulations, comme
+/// `parse_csv_file_with(file_path, b'\t', 0, true)`.
+#[tauri::command]
+pub fn parse_csv_file(
+    file_path: &str,
+    geometry_column: Option<GeometryColumn>,
+    delimiter: Option<String>,
+) -> Result<Vec<Polygon<f64>>, String> {
+    Ok(
+        parse_csv_file_with_overrides(file_path, geometry_column.as_ref(), delimiter.as_deref())?
+            .into_iter()
+            .map(|(polygon, _)| polygon)
+            .collect(),
+    )
+}
+
+/// Commande Tauri de lecture tolérante d'un fichier CSV : les lignes invalides
+/// (WKT mal formé, géométrie autre qu'un polygone, variation invalide) sont ignorées
+/// et signalées au lieu d'interrompre la lecture.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV
+/// * `geometry_column` - Colonne contenant la géométrie WKT, la première si non définie
+/// * `delimiter` - Délimiteur des colonnes, la tabulation si non défini
+///
+/// # Retours
+/// Les polygones lus et les lignes ignorées, ou une erreur si le fichier est illisible
+#[tauri::command]
+pub fn parse_csv_file_lenient(
+    file_path: &str,
+    geometry_column: Option<GeometryColumn>,
+    delimiter: Option<String>,
+) -> Result<LenientCsv, String> {
+    let mut skipped_rows = Vec::new();
+    let polygons = read_csv_rows(
+        file_path,
+        geometry_column.as_ref(),
+        delimiter.as_deref(),
+        Some(&mut skipped_rows),
+    )?;
+    Ok(LenientCsv {
+        polygons: polygons.into_iter().map(|(polygon, _)| polygon).collect(),
+        skipped_rows,
+    })
+}
+
+/// Convertit un tableau de positions GeoJSON `[x, y]` en anneau.
+fn geojson_ring(value: &serde_json::Value) -> Result<LineString<f64>, String> {
+    value
+        .as_array()
+        .ok_or("GeoJSON ring is not an array")?
+        .iter()
+        .map(|position| match position.as_array().map(Vec::as_slice) {
+            Some([x, y, ..]) => match (x.as_f64(), y.as_f64()) {
+                (Some(x), Some(y)) => Ok(Coord { x, y }),
+                _ => Err(format!("Invalid GeoJSON position: {}", position)),
+            },
+            _ => Err(format!("Invalid GeoJSON position: {}", position)),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(LineString::new)
+}
+
+/// Convertit les coordonnées GeoJSON d'un polygone (anneau extérieur puis trous) en polygone.
+fn geojson_polygon(value: &serde_json::Value) -> Result<Polygon<f64>, String> {
+    let mut rings = value
+        .as_array()
+        .ok_or("GeoJSON polygon is not an array of rings")?
+        .iter()
+        .map(geojson_ring);
+    let exterior = rings.next().ok_or("GeoJSON polygon has no ring")??;
+    Ok(Polygon::new(exterior, rings.collect::<Result<_, _>>()?))
+}
+
+/// Lit les polygones d'une FeatureCollection GeoJSON. Les multipolygones sont séparés
+/// en autant de polygones ; les entités sans géométrie ou d'un autre type sont ignorées,
+/// et signalées par `parse_geometry_file` ou `parse_geojson_file_lenient`.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier GeoJSON
+///
+/// # Retours
+/// Les polygones, dans l'ordre des entités
+#[tauri::command]
+pub fn parse_geojson_file(file_path: &str) -> Result<Vec<Polygon<f64>>, String> {
+    read_geojson_features(file_path, &mut Vec::new(), false)
+}
+
+/// Résultat de la lecture tolérante d'un fichier GeoJSON.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LenientGeoJson {
+    /// Les polygones lus, dans l'ordre des entités
+    pub polygons: Vec<Polygon<f64>>,
+    /// Les entités ignorées (numéro à partir de 1), avec la raison de leur rejet
+    pub skipped_features: Vec<SkippedRow>,
+}
+
+/// Commande Tauri de lecture tolérante d'un fichier GeoJSON : les entités qui ne sont pas
+/// des polygones, ou dont la géométrie est invalide, sont ignorées et signalées au lieu
+/// d'interrompre la lecture.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier GeoJSON
+///
+/// # Retours
+/// Les polygones lus et les entités ignorées, ou une erreur si le fichier est illisible
+#[tauri::command]
+pub fn parse_geojson_file_lenient(file_path: &str) -> Result<LenientGeoJson, String> {
+    let mut skipped_features = Vec::new();
+    let polygons = read_geojson_features(file_path, &mut skipped_features, true)?;
+    Ok(LenientGeoJson {
+        polygons,
+        skipped_features,
+    })
+}
+
+/// Lit les polygones d'une FeatureCollection GeoJSON.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier GeoJSON
+/// * `skipped_features` - Reçoit les entités ignorées, faute de géométrie polygonale
+/// * `lenient` - Ignore aussi les polygones invalides au lieu de retourner une erreur
+///
+/// # Retours
+/// Les polygones, dans l'ordre des entités
+fn read_geojson_features(
+    file_path: &str,
+    skipped_features: &mut Vec<SkippedRow>,
+    lenient: bool,
+) -> Result<Vec<Polygon<f64>>, String> {
+    let content =
+        std::fs::read_to_string(file_path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let collection: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid GeoJSON: {}", e))?;
+    if collection["type"] != "FeatureCollection" {
+        return Err("GeoJSON file is not a FeatureCollection".to_string());
+    }
+    let features = collection["features"]
+        .as_array()
+        .ok_or("GeoJSON FeatureCollection has no features array")?;
+
+    let mut polygons = Vec::new();
+    for (index, feature) in features.iter().enumerate() {
+        let geometry = &feature["geometry"];
+        let coordinates = &geometry["coordinates"];
+        let feature_polygons = match geometry["type"].as_str() {
+            Some("Polygon") => geojson_polygon(coordinates).map(|polygon| vec![polygon]),
+            Some("MultiPolygon") => coordinates
+                .as_array()
+                .ok_or_else(|| "MultiPolygon is not an array".to_string())
+                .and_then(|parts| parts.iter().map(geojson_polygon).collect()),
+            Some(other) => {
+                skipped_features.push((index + 1, format!("{} is not a polygon", other)));
+                continue;
+            }
+            None => {
+                skipped_features.push((index + 1, "Feature has no geometry".to_string()));
+                continue;
+            }
+        };
+        match feature_polygons {
+            Ok(feature_polygons) => polygons.extend(feature_polygons),
+            Err(e) if lenient => skipped_features.push((index + 1, e)),
+            Err(e) => return Err(format!("Feature {}: {}", index + 1, e)),
+        }
+    }
+    Ok(polygons)
+}
+
+/// Lit les polygones d'un fichier WKT, une géométrie `POLYGON` ou `MULTIPOLYGON`
+/// par ligne. Les lignes vides sont ignorées.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier WKT
+///
+/// # Retours
+/// Les polygones, dans l'ordre du fichier, ou une erreur à la première ligne invalide
+pub fn parse_wkt_file(file_path: &str) -> Result<Vec<Polygon<f64>>, String> {
+    let contents = std::fs::read_to_string(file_path)
+        .map_err(|e| format!("Failed to read WKT file: {}", e))?;
+    let mut polygons = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        polygons
+            .extend(parse_wkt_polygons(line).map_err(|e| format!("Line {}: {}", index + 1, e))?);
+    }
+    Ok(polygons)
+}
+
+/// Commande Tauri de lecture d'un fichier de géométries, dont le format est déduit
+/// de l'extension (voir `parse_input_file`). Un CSV est lu avec la première colonne
+/// et la tabulation comme délimiteur. Les entités GeoJSON qui ne sont pas des polygones
+/// sont ignorées et signalées ; un CSV ou un fichier WKT n'en ignore aucune.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier
+///
+/// # Retours
+/// Les polygones, dans l'ordre du fichier, et les entités ignorées, ou une erreur
+/// listant les formats pris en charge si l'extension est inconnue
+#[tauri::command]
+pub fn parse_geometry_file(file_path: &str) -> Result<LenientGeoJson, String> {
+    match input_format(file_path)? {
+        InputFormat::GeoJson => {
+            let mut skipped_features = Vec::new();
+            let polygons = read_geojson_features(file_path, &mut skipped_features, false)?;
+            Ok(LenientGeoJson {
+                polygons,
+                skipped_features,
+            })
+        }
+        _ => Ok(LenientGeoJson {
+            polygons: parse_input_file(file_path, None, None)?,
+            skipped_features: Vec::new(),
+        }),
+    }
+}
+
+/// Lit les polygones d'un fichier CSV (`.csv`, `.txt`), GeoJSON (`.geojson`, `.json`)
+/// ou WKT (`.wkt`), selon son extension.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier
+/// * `geometry_column` - Pour un CSV, colonne contenant la géométrie WKT
+/// * `delimiter` - Pour un CSV, délimiteur des colonnes
+///
+/// # Retours
+/// Les polygones, dans l'ordre du fichier
+#[tauri::command]
+pub fn parse_input_file(
+    file_path: &str,
+    geometry_column: Option<GeometryColumn>,
+    delimiter: Option<String>,
+) -> Result<Vec<Polygon<f64>>, String> {
+    match input_format(file_path)? {
+        InputFormat::Csv => parse_csv_file(file_path, geometry_column, delimiter),
+        InputFormat::GeoJson => parse_geojson_file(file_path),
+        InputFormat::Wkt => parse_wkt_file(file_path),
+    }
+}
+
+/// Lit les polygones d'un fichier comme `parse_input_file`, avec les paramètres
+/// propres à chaque polygone ; seul un CSV en définit.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier
+/// * `geometry_column` - Pour un CSV, colonne contenant la géométrie WKT
+/// * `delimiter` - Pour un CSV, délimiteur des colonnes
+///
+/// # Retours
+/// Les polygones et leurs paramètres propres, dans l'ordre du fichier
+pub fn parse_input_file_with_overrides(
+    file_path: &str,
+    geometry_column: Option<&GeometryColumn>,
+    delimiter: Option<&str>,
+) -> Result<Vec<(Polygon<f64>, PolygonOverrides)>, String> {
+    let polygons = match input_format(file_path)? {
+        InputFormat::Csv => {
+            return parse_csv_file_with_overrides(file_path, geometry_column, delimiter);
+        }
+        InputFormat::GeoJson => parse_geojson_file(file_path)?,
+        InputFormat::Wkt => parse_wkt_file(file_path)?,
+    };
+    Ok(polygons
+        .into_iter()
+        .map(|polygon| (polygon, PolygonOverrides::default()))
+        .collect())
+}
+
+/// Formats de fichier d'entrée pris en charge
+enum InputFormat {
+    Csv,
+    GeoJson,
+    Wkt,
+}
+
+/// Déduit le format d'un fichier d'entrée de son extension, casse ignorée.
+///
+/// # Retours
+/// Le format, ou une erreur listant les extensions prises en charge
+fn input_format(file_path: &str) -> Result<InputFormat, String> {
+    let extension = Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "csv" | "txt" => Ok(InputFormat::Csv),
+        "geojson" | "json" => Ok(InputFormat::GeoJson),
+        "wkt" => Ok(InputFormat::Wkt),
+        _ => Err(format!(
+            "Unsupported input file '{}': expected .csv, .txt, .geojson, .json or .wkt",
+            file_path
+        )),
+    }
+}
+
+/// Lit les polygones d'un fichier CSV avec le numéro de la ligne dont chacun provient.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV
+/// * `geometry_column` - Colonne contenant la géométrie WKT, la première si non définie
+/// * `delimiter` - Délimiteur des colonnes, la tabulation si non défini
+///
+/// # Retours
+/// Les numéros de ligne (l'en-tête étant la ligne 1) et les polygones, dans l'ordre du fichier
+pub fn parse_csv_file_with_lines(
+    file_path: &str,
+    geometry_column: Option<&GeometryColumn>,
+    delimiter: Option<&str>,
+) -> Result<Vec<(usize, Polygon<f64>)>, String> {
+    Ok(
+        parse_csv_file_with_overrides(file_path, geometry_column, delimiter)?
+            .into_iter()
+            .map(|(polygon, overrides)| (overrides.source_line.unwrap_or_default(), polygon))
+            .collect(),
+    )
+}
+
+/// Commande Tauri pour obtenir les paramètres propres à chaque ligne d'un fichier CSV,
+/// dans le même ordre que les polygones retournés par `parse_input_file`. Les polygones
+/// d'un fichier GeoJSON n'ont aucun paramètre propre.
+#[tauri::command]
+pub fn parse_csv_overrides(
+    file_path: &str,
+    geometry_column: Option<GeometryColumn>,
+    delimiter: Option<String>,
+) -> Result<Vec<PolygonOverrides>, String> {
+    Ok(
+        parse_input_file_with_overrides(file_path, geometry_column.as_ref(), delimiter.as_deref())?
+            .into_iter()
+            .map(|(_, overrides)| overrides)
+            .collect(),
+    )
+}
+
+#[tauri::command]
+pub fn get_preview_data(
+    file_path: &str,
+    param: VegetationParams,
+    geometry_column: Option<GeometryColumn>,
+    delimiter: Option<String>,
+) -> Result<(SimplePolygon, Vec<SimplePoint>), String> {
+    // Seul le premier polygone est affiché : un CSV n'est pas lu au-delà
+    let first = match input_format(file_path)? {
+        InputFormat::Csv => {
+            iter_csv_rows(file_path, geometry_column.as_ref(), delimiter.as_deref())?
+                .next()
+                .transpose()?
+        }
+        _ => parse_input_file_with_overrides(file_path, None, None)?
+            .into_iter()
+            .next(),
+    };
+
+    let Some((first_polygon, overrides)) = &first else {
+        return Err("No polygons found in file".to_string());
+    };
+
+    // L'aperçu cartographique attend des coordonnées WGS84
+    let to_preview = match param.source_epsg {
+        Some(epsg) => coordinate_transform(epsg, EPSG_WGS84)?,
+        None => same_coordinates,
+    };
+    let preview_polygon = first_polygon.map_coords(to_preview);
+    // Les points sont générés dans la projection d'échantillonnage, si elle est définie
+    let (sampled_polygon, points_to_preview) = match (param.source_epsg, param.sampling_epsg) {
+        (Some(source), Some(sampling)) => (
+            reproject_polygon(first_polygon, source, sampling)?,
+            coordinate_transform(sampling, EPSG_WGS84)?,
+        ),
+        _ => (first_polygon.clone(), to_preview),
+    };
+
+    let exterior: Vec<SimplePoint> = preview_polygon
+        .exterior()
+        .coords()
+        .map(|coord| SimplePoint {
+            x: coord.x,
+            y: coord.y,
+        })
+        .collect();
+
+    let interiors: Vec<Vec<SimplePoint>> = preview_polygon
+        .interiors()
+        .iter()
+        .map(|interior| {
+            interior
+                .coords()
+                .map(|coord| SimplePoint {
+                    x: coord.x,
+                    y: coord.y,
+                })
+                .collect()
+        })
+        .collect();
+
+    let simple_polygon = SimplePolygon {
+        exterior,
+        interiors,
+    };
+
+    // Les points sont reprojetés directement, sans passer par les lignes du fichier de sortie
+    let preview_points: Vec<SimplePoint> =
+        generate_points(sampled_polygon, &overrides.apply(&param))?
+            .into_iter()
+            .map(|point| {
+                let coord = points_to_preview(point.0);
+                SimplePoint {
+                    x: coord.x,
+                    y: coord.y,
+                }
+            })
+            .collect();
+
+    Ok((simple_polygon, preview_points))
+}
+
+/// Code EPSG du système géographique WGS84 (longitude, latitude en degrés).
+pub const EPSG_WGS84: u32 = 4326;
+
+/// Code EPSG de la projection Lambert-93 (RGF93), en mètres.
+pub const EPSG_LAMBERT_93: u32 = 2154;
+
+/// Constantes de la projection conique conforme Lambert-93 (IGN, ellipsoïde GRS80).
+/// L'écart entre RGF93 et WGS84 est inférieur au mètre et n'est pas corrigé.
+const LAMBERT_93_N: f64 = 0.725_607_765_053_267;
+const LAMBERT_93_C: f64 = 11_754_255.426_096;
+const LAMBERT_93_XS: f64 = 700_000.0;
+const LAMBERT_93_YS: f64 = 12_655_612.049_876;
+const LAMBERT_93_LON_0: f64 = 3.0;
+const GRS80_E: f64 = 0.081_819_191_042_815_8;
+
+/// Nombre d'itérations du calcul de la latitude à partir de la latitude isométrique.
+const LATITUDE_ITERATIONS: usize = 10;
+
+fn same_coordinates(coord: Coord<f64>) -> Coord<f64> {
+    coord
+}
+
+/// Convertit des coordonnées Lambert-93 en longitude/latitude WGS84.
+fn lambert93_to_wgs84(coord: Coord<f64>) -> Coord<f64> {
+    let dx = coord.x - LAMBERT_93_XS;
+    let dy = coord.y - LAMBERT_93_YS;
+    let radius = dx.hypot(dy);
+    let gamma = dx.atan2(-dy);
+    let isometric_latitude = -(radius / LAMBERT_93_C).ln() / LAMBERT_93_N;
+
+    let mut latitude = 2.0 * isometric_latitude.exp().atan() - std::f64::consts::FRAC_PI_2;
+    for _ in 0..LATITUDE_ITERATIONS {
+        let e_sin = GRS80_E * latitude.sin();
+        latitude = 2.0
+            * (((1.0 + e_sin) / (1.0 - e_sin)).powf(GRS80_E / 2.0) * isometric_latitude.exp())
+                .atan()
+            - std::f64::consts::FRAC_PI_2;
+    }
+
+    Coord {
+        x: LAMBERT_93_LON_0 + (gamma / LAMBERT_93_N).to_degrees(),
+        y: latitude.to_degrees(),
+    }
+}
+
+/// Convertit une longitude/latitude WGS84 en coordonnées Lambert-93.
+fn wgs84_to_lambert93(coord: Coord<f64>) -> Coord<f64> {
+    let latitude = coord.y.to_radians();
+    let e_sin = GRS80_E * latitude.sin();
+    let isometric_latitude = ((std::f64::consts::FRAC_PI_4 + latitude / 2.0).tan()
+        * ((1.0 - e_sin) / (1.0 + e_sin)).powf(GRS80_E / 2.0))
+    .ln();
+    let radius = LAMBERT_93_C * (-LAMBERT_93_N * isometric_latitude).exp();
+    let gamma = LAMBERT_93_N * (coord.x - LAMBERT_93_LON_0).to_radians();
+
+    Coord {
+        x: LAMBERT_93_XS + radius * gamma.sin(),
+        y: LAMBERT_93_YS - radius * gamma.cos(),
+    }
+}
+
+/// Conversion des coordonnées d'un système à un autre.
+type CoordinateTransform = fn(Coord<f64>) -> Coord<f64>;
+
+/// Retourne la fonction de conversion des coordonnées d'un système à un autre.
+/// Seules les conversions entre Lambert-93 et WGS84 sont prises en charge.
+///
+/// # Arguments
+/// * `from_epsg` - Code EPSG des coordonnées d'origine
+/// * `to_epsg` - Code EPSG des coordonnées voulues
+fn coordinate_transform(from_epsg: u32, to_epsg: u32) -> Result<CoordinateTransform, String> {
+    match (from_epsg, to_epsg) {
+        (from, to) if from == to => Ok(same_coordinates),
+        (EPSG_LAMBERT_93, EPSG_WGS84) => Ok(lambert93_to_wgs84),
+        (EPSG_WGS84, EPSG_LAMBERT_93) => Ok(wgs84_to_lambert93),
+        _ => Err(format!(
+            "Unsupported reprojection from EPSG:{} to EPSG:{}",
+            from_epsg, to_epsg
+        )),
+    }
+}
+
+/// Reprojette un point d'un système de coordonnées à un autre.
+///
+/// # Arguments
+/// * `point` - Le point à reprojeter
+/// * `from_epsg` - Code EPSG des coordonnées du point
+/// * `to_epsg` - Code EPSG des coordonnées voulues
+///
+/// # Retours
+/// Le point reprojeté ou une erreur si la conversion n'est pas prise en charge
+pub fn reproject_point(
+    point: Point<f64>,
+    from_epsg: u32,
+    to_epsg: u32,
+) -> Result<Point<f64>, String> {
+    Ok(Point::from(coordinate_transform(from_epsg, to_epsg)?(
+        point.0,
+    )))
+}
+
+/// Reprojette un polygone d'un système de coordonnées à un autre.
+///
+/// # Arguments
+/// * `polygon` - Le polygone à reprojeter
+/// * `from_epsg` - Code EPSG des coordonnées du polygone
+/// * `to_epsg` - Code EPSG des coordonnées voulues
+///
+/// # Retours
+/// Le polygone reprojeté ou une erreur si la conversion n'est pas prise en charge
+pub fn reproject_polygon(
+    polygon: &Polygon<f64>,
+    from_epsg: u32,
+    to_epsg: u32,
+) -> Result<Polygon<f64>, String> {
+    Ok(polygon.map_coords(coordinate_transform(from_epsg, to_epsg)?))
+}
+
+/// Reprojette des polygones d'un système de coordonnées à un autre.
+///
+/// # Arguments
+/// * `polygons` - Les polygones à reprojeter
+/// * `from_epsg` - Code EPSG des coordonnées des polygones
+/// * `to_epsg` - Code EPSG des coordonnées voulues
+///
+/// # Retours
+/// Les polygones reprojetés, dans le même ordre, ou une erreur si la conversion
+/// n'est pas prise en charge
+pub fn reproject_polygons(
+    polygons: &[Polygon<f64>],
+    from_epsg: u32,
+    to_epsg: u32,
+) -> Result<Vec<Polygon<f64>>, String> {
+    let transform = coordinate_transform(from_epsg, to_epsg)?;
+    Ok(polygons
+        .iter()
+        .map(|polygon| polygon.map_coords(transform))
+        .collect())
+}
+
+/// Facteur maximal appliqué aux distances minimales en une seule réduction de densité.
+pub const MAX_DENSITY_STEP: f64 = 4.0;
+
+/// En-tête du fichier de sortie, colonnes séparées par des tabulations.
+pub const OUTPUT_HEADER: &str = "X\tY\tNom\tNUMERO_DEPARTEMENT\tCODE_BASS\tCODE_INSEE\tIDIndexDATA\tCLEGCES\tNOM_PLAN_DEPLOIEMENT\tCODE_REGION\tCODE_INSEE_SGA\tchamp_graphe\tlongueur_specifique\tvitesse_specifique\tNUMERO_INSEE\tGROUPEMENT\tNOM_ZONE_OP\tSECTEUR_SINISTRE\tOBSERVATIONS\tDFCI_ID_MOT\tAUTRE_APPELATION\tAUTRE_APPELATION_1\tAUTRE_APPELATION_2\tAUTRE_APPELATION_3\tTYPE_AUTRE_APPELATION\tTYPE_AUTRE_APPELATION_1\tTYPE_AUTRE_APPELATION_2\tTYPE_AUTRE_APPELATION_3\tADRESSE\tLongueur specifique\tVitesse specifique\tIdZoneGeo\tz\ttype\tID";
+
+/// En-tête du fichier de sortie au format `WktMultipoint`.
+pub const MULTIPOINT_HEADER: &str = "WKT\ttype";
+
+/// En-tête du fichier de sortie au format `Csv`.
+pub const CSV_HEADER: &str = "X,Y,type,z";
+
+/// Écrit l'en-tête dans le fichier de sortie.
+///
+/// # Arguments
+/// * `writer` - Writer pour écrire dans le fichier
+///
+/// # Retours
+/// Ok(()) en cas de succès ou une erreur
+pub fn write_header<W: Write>(writer: &mut W) -> Result<(), Box<dyn Error>> {
+    write_header_with(writer, &ExportOptions::default())
+}
+
+/// Écrit l'en-tête dans le fichier de sortie, avec la fin de ligne des options d'export.
+/// En GeoJSON, l'en-tête ouvre la FeatureCollection.
+///
+/// # Arguments
+/// * `writer` - Writer pour écrire dans le fichier
+/// * `options` - Options de mise en forme de l'export
+///
+/// # Retours
+/// Ok(()) en cas de succès ou une erreur
+pub fn write_header_with<W: Write>(
+    writer: &mut W,
+    options: &ExportOptions,
+) -> Result<(), Box<dyn Error>> {
+    match options.format {
+        OutputFormat::SdisTxt => writer.write_all(output_template(options).header().as_bytes())?,
+        OutputFormat::GeoJson => {
+            writer.write_all(br#"{"type":"FeatureCollection","features":["#)?
+        }
+        OutputFormat::WktMultipoint => writer.write_all(MULTIPOINT_HEADER.as_bytes())?,
+        OutputFormat::Csv => writer.write_all(CSV_HEADER.as_bytes())?,
+    }
+    writer.write_all(options.line_ending.as_str().as_bytes())?;
+    Ok(())
+}
+
+/// Écrit la fin du fichier de sortie après le dernier point. En GeoJSON, ferme la FeatureCollection.
+///
+/// # Arguments
+/// * `writer` - Writer pour écrire dans le fichier
+/// * `options` - Options de mise en forme de l'export
+///
+/// # Retours
+/// Ok(()) en cas de succès ou une erreur
+pub fn write_footer<W: Write>(
+    writer: &mut W,
+    options: &ExportOptions,
+) -> Result<(), Box<dyn Error>> {
+    match options.format {
+        OutputFormat::SdisTxt | OutputFormat::WktMultipoint | OutputFormat::Csv => {}
+        OutputFormat::GeoJson => {
+            writer.write_all(b"]}")?;
+            writer.write_all(options.line_ending.as_str().as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Met en forme la ligne du fichier de sortie correspondant à un point.
+///
+/// # Arguments
+/// * `point` - Le point à écrire
+/// * `param` - Les paramètres de végétation du point
+/// * `options` - Options de mise en forme de l'export
+///
+/// # Retours
+/// La ligne, fin de ligne comprise
+pub fn format_point_row(
+    point: &Point<f64>,
+    param: &VegetationParams,
+    options: &ExportOptions,
+) -> String {
+    format_point_row_with_attributes(point, param, &BTreeMap::new(), options)
+}
+
+/// Met en forme la ligne d'un point comme `format_point_row`, en reprenant les attributs
+/// du polygone source : au format `SdisTxt`, ils remplacent les valeurs des colonnes
+/// de même nom, sauf `X`, `Y` et `type` ; en GeoJSON, ils complètent les propriétés.
+///
+/// # Arguments
+/// * `point` - Le point à écrire
+/// * `param` - Les paramètres de végétation du point
+/// * `attributes` - Les attributs du polygone, par nom de colonne
+/// * `options` - Options de mise en forme de l'export
+///
+/// # Retours
+/// La ligne, fin de ligne comprise
+pub fn format_point_row_with_attributes(
+    point: &Point<f64>,
+    param: &VegetationParams,
+    attributes: &BTreeMap<String, String>,
+    options: &ExportOptions,
+) -> String {
+    if options.format == OutputFormat::GeoJson {
+        return format_point_feature(point, param, attributes, options);
+    }
+    let (x, y) = format_coordinates(point, options);
+    let (x, y) = match options.decimal_separator {
+        '.' => (x, y),
+        separator => (
+            x.replace('.', &separator.to_string()),
+            y.replace('.', &separator.to_string()),
+        ),
+    };
+    if options.format == OutputFormat::Csv {
+        return format!(
+            "{},{},{},{}{}",
+            x,
+            y,
+            param.type_value,
+            attributes.get("z").map_or(DEFAULT_Z_VALUE, String::as_str),
+            options.line_ending.as_str()
+        );
+    }
+    let row = output_template(options).render(
+        &x,
+        &y,
+        attributes.get("z").map_or(DEFAULT_Z_VALUE, String::as_str),
+        &param.type_value.to_string(),
+        attributes,
+    );
+    format!("{}{}", row, options.line_ending.as_str())
+}
+
+/// Valeurs par défaut des colonnes de l'en-tête `OUTPUT_HEADER` qui en ont une,
+/// hors `z` et `type`, tirées du point et des paramètres de végétation.
+pub(crate) const OUTPUT_DEFAULT_VALUES: [(&str, &str); 2] =
+    [("CODE_INSEE_SGA", "20"), ("NUMERO_INSEE", "20096")];
+
+/// Valeur de la colonne `z` d'un point sans altitude.
+const DEFAULT_Z_VALUE: &str = "0";
+
+/// Modèle intégré, reprenant l'en-tête `OUTPUT_HEADER`.
+static DEFAULT_OUTPUT_TEMPLATE: LazyLock<OutputTemplate> = LazyLock::new(OutputTemplate::default);
+
+/// Modèle des lignes `SdisTxt` d'un export : celui des options, sinon le modèle intégré.
+fn output_template(options: &ExportOptions) -> &OutputTemplate {
+    options
+        .template
+        .as_ref()
+        .unwrap_or(&DEFAULT_OUTPUT_TEMPLATE)
+}
+
+/// Met en forme les coordonnées d'un point à la précision des options, avec le point
+/// comme séparateur décimal.
+///
+/// # Arguments
+/// * `point` - Le point à écrire
+/// * `options` - Options de mise en forme de l'export
+///
+/// # Retours
+/// Les coordonnées X et Y
+fn format_coordinates(point: &Point<f64>, options: &ExportOptions) -> (String, String) {
+    match options.coordinate_precision {
+        Some(precision) => (
+            format!("{:.*}", precision as usize, point.x()),
+            format!("{:.*}", precision as usize, point.y()),
+        ),
+        None => (point.x().to_string(), point.y().to_string()),
+    }
+}
+
+/// Met en forme la ligne du fichier de sortie regroupant tous les points d'un polygone
+/// en une géométrie WKT `MULTIPOINT`, suivie de la valeur de type.
+///
+/// # Arguments
+/// * `points` - Les points du polygone
+/// * `param` - Les paramètres de végétation du polygone
+/// * `options` - Options de mise en forme de l'export
+///
+/// # Retours
+/// La ligne, fin de ligne comprise
+pub fn format_multipoint_row(
+    points: &[Point<f64>],
+    param: &VegetationParams,
+    options: &ExportOptions,
+) -> String {
+    let coordinates = points
+        .iter()
+        .map(|point| {
+            let (x, y) = format_coordinates(point, options);
+            format!("({} {})", x, y)
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        "MULTIPOINT({})\t{}{}",
+        coordinates,
+        param.type_value,
+        options.line_ending.as_str()
+    )
+}
+
+/// Met en forme l'entité GeoJSON correspondant à un point. Les coordonnées sont arrondies
+/// à la précision des options ; le séparateur décimal est toujours le point.
+///
+/// # Arguments
+/// * `point` - Le point à écrire
+/// * `param` - Les paramètres de végétation du point
+/// * `attributes` - Les attributs du polygone, ajoutés aux propriétés
+/// * `options` - Options de mise en forme de l'export
+///
+/// # Retours
+/// L'entité, fin de ligne comprise
+fn format_point_feature(
+    point: &Point<f64>,
+    param: &VegetationParams,
+    attributes: &BTreeMap<String, String>,
+    options: &ExportOptions,
+) -> String {
+    let round = |value: f64| match options.coordinate_precision {
+        Some(precision) => {
+            let scale = 10f64.powi(precision as i32);
+            (value * scale).round() / scale
+        }
+        None => value,
+    };
+    let mut properties = serde_json::Map::new();
+    for (name, value) in attributes {
+        properties.insert(name.clone(), value.clone().into());
+    }
+    properties.insert("type".to_string(), param.type_value.into());
+    // Une altitude numérique reste un nombre, une altitude vide devient null
+    let z = match attributes.get("z") {
+        None => 0.into(),
+        Some(z) if z.is_empty() => serde_json::Value::Null,
+        Some(z) => z
+            .parse::<f64>()
+            .map_or_else(|_| z.clone().into(), Into::into),
+    };
+    properties.insert("z".to_string(), z);
+    let feature = serde_json::json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "Point",
+            "coordinates": [round(point.x()), round(point.y())],
+        },
+        "properties": properties,
+    });
+    format!("{}{}", feature, options.line_ending.as_str())
+}
+
+/// Commande Tauri pour obtenir la liste des colonnes du fichier de sortie.
+///
+/// # Retours
+/// Les noms des colonnes, dans l'ordre de l'en-tête écrit par `write_header`
+#[tauri::command]
+pub fn get_output_columns() -> Vec<String> {
+    OUTPUT_HEADER.split('\t').map(str::to_string).collect()
+}
+
+/// Complète les options d'un export lancé depuis l'interface avec les paramètres
+/// enregistrés : sans options, les coordonnées sont écrites avec la précision enregistrée ;
+/// sans modèle de lignes dans les options, le modèle enregistré est utilisé.
+///
+/// # Arguments
+/// * `options` - Options transmises par l'interface
+///
+/// # Retours
+/// Les options de l'export
+fn saved_export_options(options: Option<ExportOptions>) -> ExportOptions {
+    let mut options = options.unwrap_or_else(|| ExportOptions {
+        coordinate_precision: Some(get_coordinate_precision()),
+        ..Default::default()
+    });
+    if options.template.is_none() {
+        options.template = Settings::with_read(|s| s.get_output_template().unwrap_or(None));
+    }
+    options
+}
+
+/// Commande Tauri lançant l'export en arrière-plan, avec les options complétées par
+/// `saved_export_options`.
+#[tauri::command]
+pub fn export_results(
+    data: Vec<Polygon<f64>>,
+    param: VegetationParams,
+    overrides: Option<Vec<PolygonOverrides>>,
+    options: Option<ExportOptions>,
+    source_file: Option<String>,
+    state: State<'_, VegetationProcessingState>,
+    app_handle: AppHandle,
+) {
+    state.clear_cancel();
+    let state_arc = std::sync::Arc::new((*state.inner()).clone());
+    let param = param.clone();
+    let handle = app_handle.clone();
+    let options = saved_export_options(options);
+
+    std::thread::spawn(move || {
+        match run_export(
+            data,
+            param,
+            overrides.unwrap_or_default(),
+            options,
+            source_file,
+            state_arc.clone(),
+            handle.clone(),
+        ) {
+            Ok(filename) => {
+                let _ = handle.emit("vegetation-export-finished", &filename);
+            }
+            Err(err_msg) if state_arc.is_cancel_requested() => {
+                println!("{}", err_msg);
+                let _ = handle.emit("vegetation-export-cancelled", &err_msg);
+            }
+            Err(err_msg) => {
+                eprintln!("Export failed: {}", err_msg);
+                let _ = handle.emit("vegetation-export-error", &err_msg);
+            }
+        }
+    });
+}
+
+fn run_export(
+    data: Vec<Polygon<f64>>,
+    param: VegetationParams,
+    overrides: Vec<PolygonOverrides>,
+    options: ExportOptions,
+    source_file: Option<String>,
+    state: std::sync::Arc<VegetationProcessingState>,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    let export_path = get_export_path();
+    // Sans polygones transmis, le fichier source est lu : un CSV au fil de l'export,
+    // les autres formats en entier
+    let (data, skipped_features) = match &source_file {
+        Some(file_path) if data.is_empty() => {
+            if let InputFormat::Csv = input_format(file_path)? {
+                return export_csv_to_directory(
+                    file_path,
+                    &param,
+                    &options,
+                    std::path::Path::new(&export_path),
+                    chrono::Local::now(),
+                    &state,
+                    &app_handle,
+                );
+            }
+            let input = parse_geometry_file(file_path)?;
+            (input.polygons, input.skipped_features)
+        }
+        _ => (data, Vec::new()),
+    };
+    export_input_to_directory(
+        &data,
+        &skipped_features,
+        &param,
+        &overrides,
+        &options,
+        source_file.as_deref(),
+        std::path::Path::new(&export_path),
+        chrono::Local::now(),
+        &state,
+        &app_handle,
+    )
+}
+
+/// Commande Tauri exportant les mêmes polygones pour plusieurs types de végétation,
+/// un fichier par type, en arrière-plan. Les options et le fichier source sont traités
+/// comme par `export_results`. L'événement `vegetation-export-all-finished` transmet
+/// les noms de tous les fichiers créés.
+///
+/// # Arguments
+/// * `data` - Les polygones à remplir, lus depuis `source_file` si vide
+/// * `params` - Les paramètres de chaque type de végétation à exporter
+/// * `options` - Options de mise en forme des exports
+/// * `source_file` - Fichier source des polygones
+#[tauri::command]
+pub fn export_all_types(
+    data: Vec<Polygon<f64>>,
+    params: Vec<VegetationParams>,
+    options: Option<ExportOptions>,
+    source_file: Option<String>,
+    state: State<'_, VegetationProcessingState>,
+    app_handle: AppHandle,
+) {
+    state.clear_cancel();
+    let state_arc = std::sync::Arc::new((*state.inner()).clone());
+    let handle = app_handle.clone();
+    let options = saved_export_options(options);
+
+    std::thread::spawn(move || {
+        let export_path = get_export_path();
+        match export_all_types_to_directory(
+            data,
+            &params,
+            &options,
+            source_file.as_deref(),
+            Path::new(&export_path),
+            chrono::Local::now(),
+            &state_arc,
+            &handle,
+        ) {
+            Ok(filenames) => {
+                let _ = handle.emit("vegetation-export-all-finished", &filenames);
+            }
+            Err(err_msg) if state_arc.is_cancel_requested() => {
+                println!("{}", err_msg);
+                let _ = handle.emit("vegetation-export-cancelled", &err_msg);
+            }
+            Err(err_msg) => {
+                eprintln!("Export failed: {}", err_msg);
+                let _ = handle.emit("vegetation-export-error", &err_msg);
+            }
+        }
+    });
+}
+
+/// Exporte les mêmes polygones pour chaque type de végétation de `params`, dans un fichier
+/// par type dont le nom porte le type après le préfixe des options. La progression
+/// notifiée à `reporter` couvre l'ensemble des types ; les erreurs de remplissage sont
+/// précédées du type concerné.
+///
+/// # Arguments
+/// * `data` - Les polygones à remplir, lus depuis `source_file` si vide
+/// * `params` - Les paramètres de chaque type de végétation à exporter
+/// * `options` - Options de mise en forme des exports
+/// * `source_file` - Fichier source des polygones, relu pour chaque type s'il s'agit d'un CSV
+/// * `directory` - Dossier de destination
+/// * `timestamp` - Instant de l'export
+/// * `state` - État de progression de l'export
+/// * `reporter` - Destination des notifications de progression
+///
+/// # Retours
+/// Les noms des fichiers créés, dans l'ordre de `params`, ou la première erreur
+#[allow(clippy::too_many_arguments)]
+pub fn export_all_types_to_directory(
+    data: Vec<Polygon<f64>>,
+    params: &[VegetationParams],
+    options: &ExportOptions,
+    source_file: Option<&str>,
+    directory: &Path,
+    timestamp: DateTime<Local>,
+    state: &VegetationProcessingState,
+    reporter: &(impl ProgressReporter + Sync),
+) -> Result<Vec<String>, String> {
+    if params.is_empty() {
+        return Err("No vegetation type to export.".to_string());
+    }
+    let csv_source = match source_file {
+        Some(file_path) if data.is_empty() => match input_format(file_path)? {
+            InputFormat::Csv => Some(file_path),
+            _ => None,
+        },
+        _ => None,
+    };
+    let (data, skipped_features, rows_per_type) = match (csv_source, source_file) {
+        (Some(file_path), _) => (data, Vec::new(), count_csv_rows(file_path, None, None)?),
+        (None, Some(file_path)) if data.is_empty() => {
+            let input = parse_geometry_file(file_path)?;
+            let rows = input.polygons.len();
+            (input.polygons, input.skipped_features, rows)
+        }
+        _ => {
+            let rows = data.len();
+            (data, Vec::new(), rows)
+        }
+    };
+
+    let mut combined = CombinedProgress {
+        reporter,
+        start: Instant::now(),
+        total_rows: rows_per_type * params.len(),
+        done_rows: 0,
+        done_items: 0,
+        errors: Vec::new(),
+        prefix: String::new(),
+    };
+    let mut filenames = Vec::with_capacity(params.len());
+    for param in params {
+        combined.prefix = format!("Type {}: ", param.vegetation_type);
+        let type_options = ExportOptions {
+            filename_prefix: format!("{} type {}", options.filename_prefix, param.vegetation_type),
+            ..options.clone()
+        };
+        let filename = match csv_source {
+            Some(file_path) => export_csv_to_directory(
+                file_path,
+                param,
+                &type_options,
+                directory,
+                timestamp,
+                state,
+                &combined,
+            ),
+            None => export_input_to_directory(
+                &data,
+                &skipped_features,
+                param,
+                &[],
+                &type_options,
+                source_file,
+                directory,
+                timestamp,
+                state,
+                &combined,
+            ),
+        }
+        .map_err(|e| format!("{}{}", combined.prefix, e))?;
+        filenames.push(filename);
+
+        combined.done_rows += rows_per_type;
+        combined.done_items += *state.created_items.lock().unwrap();
+        let type_errors = state.errors.lock().unwrap().clone();
+        combined.errors.extend(
+            type_errors
+                .into_iter()
+                .map(|e| format!("{}{}", combined.prefix, e)),
+        );
+    }
+
+    // L'état reflète l'ensemble des types, pour `get_vegetation_progress`
+    *state.processed_rows.lock().unwrap() = combined.done_rows;
+    *state.total_rows.lock().unwrap() = combined.total_rows;
+    *state.created_items.lock().unwrap() = combined.done_items;
+    *state.errors.lock().unwrap() = combined.errors;
+    *state.start_time.lock().unwrap() = Some(combined.start);
+    state.set_finished(reporter);
+    Ok(filenames)
+}
+
+/// Reporte la progression de l'export d'un type sur l'ensemble des types exportés.
+struct CombinedProgress<'a, R> {
+    reporter: &'a R,
+    /// Début de l'export du premier type
+    start: Instant,
+    /// Nombre de polygones à traiter pour l'ensemble des types
+    total_rows: usize,
+    /// Polygones et points des types déjà exportés
+    done_rows: usize,
+    done_items: usize,
+    /// Erreurs des types déjà exportés, précédées de leur type
+    errors: Vec<String>,
+    /// Préfixe des erreurs du type en cours
+    prefix: String,
+}
+
+impl<R: ProgressReporter> ProgressReporter for CombinedProgress<'_, R> {
+    fn report(&self, progress: &VegetationProgressInfo) {
+        let current_row = self.done_rows + progress.current_row;
+        let created_items = self.done_items + progress.created_items;
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let rows_per_second = (elapsed > 0.0).then(|| current_row as f64 / elapsed);
+        let mut errors = self.errors.clone();
+        errors.extend(
+            progress
+                .errors
+                .iter()
+                .map(|e| format!("{}{}", self.prefix, e)),
+        );
+        self.reporter.report(&VegetationProgressInfo {
+            current_row,
+            total_rows: self.total_rows,
+            created_items,
+            errors,
+            percentage: if self.total_rows > 0 {
+                current_row as f64 / self.total_rows as f64 * 100.0
+            } else {
+                0.0
+            },
+            elapsed_seconds: Some(elapsed as u64),
+            estimated_remaining_seconds: rows_per_second
+                .filter(|&rate| rate > 0.0 && current_row < self.total_rows)
+                .map(|rate| ((self.total_rows - current_row) as f64 / rate) as u64),
+            rows_per_second,
+            points_per_second: (elapsed > 0.0).then(|| created_items as f64 / elapsed),
+            // La fin est notifiée une fois tous les types exportés et l'état mis à jour
+            is_finished: false,
+            current_bounds: progress.current_bounds,
+            density_adjustments: progress.density_adjustments.clone(),
+        });
+    }
+}
+
+/// Lit les polygones d'une géométrie WKT `POLYGON` ou `MULTIPOLYGON`.
+///
+/// # Arguments
+/// * `wkt` - La géométrie WKT
+///
+/// # Retours
+/// Les polygones, ou une erreur si la géométrie est invalide ou d'un autre type
+pub fn parse_wkt_polygons(wkt: &str) -> Result<Vec<Polygon<f64>>, String> {
+    let (flat_wkt, _) = strip_elevation(wkt).map_err(|e| format!("{}: {}", e, wkt))?;
+    let parsed: Wkt<f64> = flat_wkt
+        .parse()
+        .map_err(|_| format!("Invalid WKT format: {}", wkt))?;
+    let geometry: Geometry<f64> = parsed
+        .try_into()
+        .map_err(|_| format!("Cannot convert WKT to geo geometry: {}", wkt))?;
+    match geometry {
+        Geometry::Polygon(polygon) => Ok(vec![polygon]),
+        Geometry::MultiPolygon(multi_polygon) if !multi_polygon.0.is_empty() => Ok(multi_polygon.0),
+        _ => Err(format!("WKT is not a Polygon or MultiPolygon: {}", wkt)),
+    }
+}
+
+/// Remplit les polygones d'une géométrie WKT et les exporte dans `directory`
+/// par la même chaîne qu'un fichier CSV.
+///
+/// # Arguments
+/// * `wkt` - La géométrie WKT `POLYGON` ou `MULTIPOLYGON`
+/// * `param` - Les paramètres de végétation
+/// * `options` - Options de mise en forme de l'export
+/// * `directory` - Dossier de destination
+/// * `state` - État de progression de l'export
+/// * `reporter` - Destination des notifications de progression
+///
+/// # Retours
+/// Le nom du fichier créé ou une erreur
+pub fn export_wkt_to_directory(
+    wkt: &str,
+    param: &VegetationParams,
+    options: &ExportOptions,
+    directory: &Path,
+    state: &VegetationProcessingState,
+    reporter: &(impl ProgressReporter + Sync),
+) -> Result<String, String> {
+    let polygons = parse_wkt_polygons(wkt)?;
+    export_to_directory(
+        &polygons,
+        param,
+        &[],
+        options,
+        None,
+        directory,
+        chrono::Local::now(),
+        state,
+        reporter,
+    )
+}
+
+/// Commande Tauri pour exporter les points d'un polygone WKT collé par l'utilisateur,
+/// sans passer par un fichier CSV. L'export s'exécute hors du thread principal, avec
+/// la précision et le modèle de lignes enregistrés, comme `export_results`.
+///
+/// # Arguments
+/// * `wkt` - La géométrie WKT `POLYGON` ou `MULTIPOLYGON`
+/// * `param` - Les paramètres de végétation
+/// * `format` - Format du fichier produit, `SdisTxt` si non défini
+///
+/// # Retours
+/// Le nom du fichier créé dans le dossier d'export ou une erreur
 #[tauri::command]
-pub fn parse_csv_file(file_path: &str) -> Result<Vec<Polygon<f64>>, String> {
-    let mut reader = ReaderBuilder::new()
-        .delimiter(b'\t')
-        .has_headers(true)
-        .from_path(file_path)
-        .map_err(|e| format!("Failed to open file: {}", e))?;
-    let mut polygons = Vec::new();
+pub async fn export_single_wkt(
+    wkt: String,
+    param: VegetationParams,
+    format: Option<OutputFormat>,
+    state: State<'_, VegetationProcessingState>,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    state.clear_cancel();
+    let state = state.inner().clone();
+    let options = ExportOptions {
+        format: format.unwrap_or_default(),
+        ..saved_export_options(None)
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        export_wkt_to_directory(
+            &wkt,
+            &param,
+            &options,
+            std::path::Path::new(&get_export_path()),
+            &state,
+            &app_handle,
+        )
+    })
+    .await
+    .map_err(|e| format!("Export failed: {}", e))?
+}
 
-    for result in reader.records() {
-        let record = result.map_err(|e| format!("CSV read error: {}", e))?;
-        if let Some(geometry_field) = record.get(0) {
-            let wkt: Wkt<f64> = geometry_field
-                .parse()
-                .map_err(|_| format!("Invalid WKT format: {}", geometry_field))?;
-            let geometry: Geometry<f64> = wkt
-                .try_into()
-                .map_err(|_| format!("Cannot convert WKT to geo geometry: {}", geometry_field))?;
-            if let Geometry::Polygon(polygon) = geometry {
-                polygons.push(polygon);
-            } else {
-                return Err(format!("WKT is not a Polygon: {}", geometry_field));
+/// Construit le nom du fichier d'export à partir de l'horodatage.
+///
+/// # Arguments
+/// * `options` - Options de l'export (préfixe, format et compression)
+/// * `timestamp` - Instant de l'export
+pub fn export_filename(options: &ExportOptions, timestamp: DateTime<Local>) -> String {
+    format!(
+        "{} {}.{}",
+        options.filename_prefix,
+        timestamp.format("%d-%m-%Y %Hh%M-%S"),
+        options.extension()
+    )
+}
+
+/// Réserve un nom de fichier d'export libre dans `directory` en créant son fichier
+/// temporaire `<nom>.tmp`, ce qu'un seul export peut faire pour un nom donné.
+/// Si le nom horodaté à la seconde est déjà pris (deux exports lancés dans la même seconde),
+/// un compteur ` (1)`, ` (2)`… est ajouté avant l'extension.
+///
+/// # Arguments
+/// * `directory` - Dossier de destination
+/// * `options` - Options de l'export (préfixe et format)
+/// * `timestamp` - Instant de l'export
+///
+/// # Retours
+/// Le nom réservé et son fichier temporaire ouvert en écriture, ou une erreur de création
+fn reserve_export_filename(
+    directory: &Path,
+    options: &ExportOptions,
+    timestamp: DateTime<Local>,
+) -> Result<(String, std::fs::File), String> {
+    let filename = export_filename(options, timestamp);
+    let extension = options.extension();
+    let stem = filename
+        .strip_suffix(&format!(".{}", extension))
+        .unwrap_or(&filename)
+        .to_string();
+
+    let mut counter = 0;
+    loop {
+        let candidate = match counter {
+            0 => filename.clone(),
+            _ => format!("{} ({}).{}", stem, counter, extension),
+        };
+        let temp_path = directory.join(format!("{}.tmp", candidate));
+        let file = match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+        {
+            Ok(file) => file,
+            // Un export en cours n'existe encore que sous son nom temporaire
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                counter += 1;
+                continue;
             }
-        } else {
-            return Err("Missing geometry field in record".to_string());
+            Err(e) => return Err(format!("Failed to create file: {}", e)),
+        };
+        // Le renommage d'un export terminé est atomique : un nom dont le fichier
+        // temporaire vient d'être libéré a déjà son fichier définitif
+        if directory.join(&candidate).exists() {
+            drop(file);
+            let _ = std::fs::remove_file(&temp_path);
+            counter += 1;
+            continue;
         }
+        if counter > 0 {
+            println!(
+                "Export file {} already exists, writing to {} instead",
+                filename, candidate
+            );
+        }
+        return Ok((candidate, file));
     }
-    Ok(polygons)
 }
 
-#[tauri::command]
-pub fn get_preview_data(
+/// Crée le fichier d'export dans `directory` et y écrit les points de tous les polygones,
+/// ainsi que le résumé de l'export si `options.write_summary_json` est activé.
+///
+/// # Retours
+/// Le nom du fichier créé ou une erreur
+#[allow(clippy::too_many_arguments)]
+pub fn export_to_directory(
+    data: &[Polygon<f64>],
+    param: &VegetationParams,
+    overrides: &[PolygonOverrides],
+    options: &ExportOptions,
+    source_file: Option<&str>,
+    directory: &Path,
+    timestamp: DateTime<Local>,
+    state: &VegetationProcessingState,
+    reporter: &(impl ProgressReporter + Sync),
+) -> Result<String, String> {
+    export_input_to_directory(
+        data,
+        &[],
+        param,
+        overrides,
+        options,
+        source_file,
+        directory,
+        timestamp,
+        state,
+        reporter,
+    )
+}
+
+/// Exporte les polygones lus dans un fichier source comme `export_to_directory`, en
+/// ajoutant aux erreurs de l'export, et donc à son résumé, les entités GeoJSON ignorées
+/// à la lecture.
+///
+/// # Arguments
+/// * `skipped_features` - Les entités ignorées à la lecture du fichier source
+#[allow(clippy::too_many_arguments)]
+fn export_input_to_directory(
+    data: &[Polygon<f64>],
+    skipped_features: &[SkippedRow],
+    param: &VegetationParams,
+    overrides: &[PolygonOverrides],
+    options: &ExportOptions,
+    source_file: Option<&str>,
+    directory: &Path,
+    timestamp: DateTime<Local>,
+    state: &VegetationProcessingState,
+    reporter: &(impl ProgressReporter + Sync),
+) -> Result<String, String> {
+    write_export_to_directory(
+        param,
+        options,
+        source_file,
+        directory,
+        timestamp,
+        state,
+        |mut writer| {
+            let total_points = export_to_writer(
+                data,
+                param,
+                overrides,
+                options,
+                &mut writer,
+                state,
+                reporter,
+            )?;
+            for (feature, reason) in skipped_features {
+                state.add_error(
+                    format!("GeoJSON feature {} skipped: {}", feature, reason),
+                    reporter,
+                );
+            }
+            Ok(total_points)
+        },
+    )
+}
+
+/// Crée le fichier d'export dans `directory` en lisant les polygones du fichier CSV
+/// au fur et à mesure, comme `export_csv_to_writer`, puis écrit le résumé de l'export
+/// si `options.write_summary_json` est activé.
+///
+/// # Retours
+/// Le nom du fichier créé ou une erreur
+pub fn export_csv_to_directory(
     file_path: &str,
+    param: &VegetationParams,
+    options: &ExportOptions,
+    directory: &Path,
+    timestamp: DateTime<Local>,
+    state: &VegetationProcessingState,
+    reporter: &(impl ProgressReporter + Sync),
+) -> Result<String, String> {
+    write_export_to_directory(
+        param,
+        options,
+        Some(file_path),
+        directory,
+        timestamp,
+        state,
+        |mut writer| export_csv_to_writer(file_path, param, options, &mut writer, state, reporter),
+    )
+}
+
+/// Écrit un export sous un nom libre de `directory` avec `write`, puis son résumé.
+/// Si `options.compress` est activé, l'export est compressé au fil de l'écriture.
+///
+/// # Retours
+/// Le nom du fichier créé ou une erreur
+fn write_export_to_directory(
+    param: &VegetationParams,
+    options: &ExportOptions,
+    source_file: Option<&str>,
+    directory: &Path,
+    timestamp: DateTime<Local>,
+    state: &VegetationProcessingState,
+    write: impl FnOnce(&mut dyn Write) -> Result<usize, String>,
+) -> Result<String, String> {
+    let start = Instant::now();
+    let (output_filename, file) = reserve_export_filename(directory, options, timestamp)?;
+    let output_path = directory.join(&output_filename);
+    let total_points = write_reserved_export(file, &output_path, state, |writer| {
+        if !options.compress {
+            return write(writer);
+        }
+        let mut encoder = GzEncoder::new(writer, Compression::default());
+        let total_points = write(&mut encoder)?;
+        encoder
+            .finish()
+            .map_err(|e| format!("Failed to finish compressed export: {}", e))?;
+        Ok(total_points)
+    })?;
+
+    if options.write_summary_json {
+        let warnings = options
+            .separator_warning()
+            .into_iter()
+            .chain(
+                state
+                    .density_adjustments
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|adjustment| {
+                        format!(
+                            "Minimum distance multiplied by {:.2} from polygon {} to meet the target duration",
+                            adjustment.factor, adjustment.from_polygon
+                        )
+                    }),
+            )
+            .collect();
+        let summary = ExportSummary {
+            input_file: source_file.map(str::to_string),
+            output_file: output_filename.clone(),
+            parameters: param.clone(),
+            format: options.format,
+            total_points,
+            points_per_type: [(param.type_value, total_points)].into_iter().collect(),
+            duration_seconds: start.elapsed().as_secs_f64(),
+            warnings,
+            errors: state.errors.lock().unwrap().clone(),
+        };
+        write_summary(
+            &directory.join(ExportSummary::filename(&output_filename)),
+            &summary,
+        )?;
+    }
+
+    Ok(output_filename)
+}
+
+/// Export lancé hors du système d'événements de Tauri, par exemple depuis un script.
+pub struct ExportStream {
+    /// Notifications de progression, terminées par celle où `is_finished` est vrai ;
+    /// le flux se ferme à la fin de l'export
+    pub progress: UnboundedReceiverStream<VegetationProgressInfo>,
+    /// Nom du fichier créé ou erreur, disponible à la fin de l'export
+    pub result: oneshot::Receiver<Result<String, String>>,
+}
+
+/// Lance un export dans un thread et retourne sa progression sous forme de flux asynchrone,
+/// pour qu'un script puisse attendre chaque notification puis le résultat final.
+///
+/// # Arguments
+/// * `data` - Les polygones à remplir
+/// * `param` - Les paramètres de végétation globaux
+/// * `overrides` - Les paramètres propres à chaque polygone, dans le même ordre que `data`
+/// * `options` - Options de mise en forme de l'export
+/// * `directory` - Dossier de destination
+///
+/// # Retours
+/// Le flux de progression et le résultat de l'export
+pub fn export_progress_stream(
+    data: Vec<Polygon<f64>>,
     param: VegetationParams,
-) -> Result<(SimplePolygon, Vec<SimplePoint>), String> {
-    let polygons = parse_csv_file(file_path)?;
+    overrides: Vec<PolygonOverrides>,
+    options: ExportOptions,
+    directory: std::path::PathBuf,
+) -> ExportStream {
+    let (progress_sender, progress_receiver) = mpsc::unbounded_channel();
+    let (result_sender, result_receiver) = oneshot::channel();
 
-    if polygons.is_empty() {
-        return Err("No polygons found in file".to_string());
+    std::thread::spawn(move || {
+        let reporter = CallbackReporter(move |progress: &VegetationProgressInfo| {
+            let _ = progress_sender.send(progress.clone());
+        });
+        let result = export_to_directory(
+            &data,
+            &param,
+            &overrides,
+            &options,
+            None,
+            &directory,
+            chrono::Local::now(),
+            &VegetationProcessingState::new(),
+            &reporter,
+        );
+        // Ferme le flux de progression avant de publier le résultat
+        drop(reporter);
+        let _ = result_sender.send(result);
+    });
+
+    ExportStream {
+        progress: UnboundedReceiverStream::new(progress_receiver),
+        result: result_receiver,
     }
+}
 
-    let first_polygon = &polygons[0];
+/// Écrit le résumé d'un export au format JSON.
+///
+/// # Arguments
+/// * `path` - Chemin du fichier de résumé
+/// * `summary` - Le résumé à écrire
+fn write_summary(path: &Path, summary: &ExportSummary) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(summary)
+        .map_err(|e| format!("Failed to serialize export summary: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write export summary: {}", e))
+}
 
-    let exterior: Vec<SimplePoint> = first_polygon
-        .exterior()
-        .coords()
-        .map(|coord| SimplePoint {
-            x: coord.x,
-            y: coord.y,
-        })
-        .collect();
+/// Écrit un fichier d'export dans `<nom>.tmp`, renommé en `output_path` seulement une fois
+/// l'écriture terminée et vidée : un outil qui surveille le dossier ne voit jamais
+/// un export tronqué sous son nom définitif. En cas d'annulation, le fichier temporaire
+/// est supprimé ; en cas d'erreur, il est conservé sous le nom `<nom>.partial`.
+///
+/// # Arguments
+/// * `output_path` - Chemin définitif du fichier d'export
+/// * `state` - État de l'export, pour distinguer une annulation d'une erreur
+/// * `write` - Écriture du contenu de l'export
+///
+/// # Retours
+/// Le résultat de `write`, ou l'erreur de l'écriture, du vidage ou du renommage
+pub fn write_export_atomically<T>(
+    output_path: &Path,
+    state: &VegetationProcessingState,
+    write: impl FnOnce(&mut std::io::BufWriter<std::fs::File>) -> Result<T, String>,
+) -> Result<T, String> {
+    let file = std::fs::File::create(temp_export_path(output_path))
+        .map_err(|e| format!("Failed to create file: {}", e))?;
+    write_reserved_export(file, output_path, state, write)
+}
 
-    let interiors: Vec<Vec<SimplePoint>> = first_polygon
-        .interiors()
-        .iter()
-        .map(|interior| {
-            interior
-                .coords()
-                .map(|coord| SimplePoint {
-                    x: coord.x,
-                    y: coord.y,
-                })
-                .collect()
-        })
-        .collect();
+/// Chemin du fichier temporaire `<nom>.tmp` d'un export.
+fn temp_export_path(output_path: &Path) -> std::path::PathBuf {
+    let mut temp_path = output_path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    std::path::PathBuf::from(temp_path)
+}
 
-    let simple_polygon = SimplePolygon {
-        exterior,
-        interiors,
-    };
+/// Écrit un export comme `write_export_atomically`, dans son fichier temporaire déjà créé
+/// par `reserve_export_filename`.
+///
+/// # Arguments
+/// * `file` - Fichier temporaire `<nom>.tmp` de l'export, ouvert en écriture
+/// * `output_path` - Chemin définitif du fichier d'export
+/// * `state` - État de l'export, pour distinguer une annulation d'une erreur
+/// * `write` - Écriture du contenu de l'export
+///
+/// # Retours
+/// Le résultat de `write`, ou l'erreur de l'écriture, du vidage ou du renommage
+fn write_reserved_export<T>(
+    file: std::fs::File,
+    output_path: &Path,
+    state: &VegetationProcessingState,
+    write: impl FnOnce(&mut std::io::BufWriter<std::fs::File>) -> Result<T, String>,
+) -> Result<T, String> {
+    let temp_path = temp_export_path(output_path);
+    let mut writer = std::io::BufWriter::new(file);
 
-    let point_strings = fill_polygon(first_polygon.clone(), param)?;
-    let preview_points: Vec<SimplePoint> = point_strings
-        .iter()
-        .filter_map(|point_str| {
-            let parts: Vec<&str> = point_str.trim().split('\t').collect();
-            if parts.len() >= 2
-                && let (Ok(x), Ok(y)) = (
-                    parts[0].trim().parse::<f64>(),
-                    parts[1].trim().parse::<f64>(),
-                )
-            {
-                return Some(SimplePoint { x, y });
+    let result = write(&mut writer).and_then(|value| {
+        writer
+            .flush()
+            .map_err(|e| format!("Failed to flush writer: {}", e))?;
+        Ok(value)
+    });
+
+    match result {
+        Ok(value) => {
+            drop(writer);
+            std::fs::rename(&temp_path, output_path).map_err(|e| {
+                let _ = std::fs::remove_file(&temp_path);
+                format!("Failed to move export to its final name: {}", e)
+            })?;
+            Ok(value)
+        }
+        Err(err_msg) if state.is_cancel_requested() => {
+            drop(writer);
+            if let Err(e) = std::fs::remove_file(&temp_path) {
+                eprintln!("Failed to remove cancelled export: {}", e);
             }
+            Err(err_msg)
+        }
+        Err(err_msg) => {
+            let _ = writer.flush();
+            drop(writer);
+            Err(match move_to_partial(&temp_path, output_path) {
+                Some(partial_path) => format!(
+                    "{}. Partial output kept in {}",
+                    err_msg,
+                    partial_path.display()
+                ),
+                None => err_msg,
+            })
+        }
+    }
+}
+
+/// Renomme un fichier d'export interrompu en `<nom>.partial`, pour qu'il ne soit pas
+/// confondu avec un export complet tout en restant consultable.
+///
+/// # Arguments
+/// * `output_path` - Chemin du fichier d'export interrompu
+///
+/// # Retours
+/// Le chemin du fichier partiel, ou None si le renommage a échoué
+pub fn keep_partial_export(output_path: &Path) -> Option<std::path::PathBuf> {
+    move_to_partial(output_path, output_path)
+}
+
+/// Déplace un fichier d'export interrompu vers `<output_path>.partial`.
+///
+/// # Arguments
+/// * `written_path` - Chemin du fichier écrit
+/// * `output_path` - Chemin définitif qu'aurait eu l'export complet
+///
+/// # Retours
+/// Le chemin du fichier partiel, ou None si le renommage a échoué
+fn move_to_partial(written_path: &Path, output_path: &Path) -> Option<std::path::PathBuf> {
+    let mut partial_path = output_path.as_os_str().to_owned();
+    partial_path.push(".partial");
+    let partial_path = std::path::PathBuf::from(partial_path);
+
+    match std::fs::rename(written_path, &partial_path) {
+        Ok(()) => Some(partial_path),
+        Err(e) => {
+            eprintln!("Failed to keep partial export: {}", e);
             None
-        })
-        .collect();
+        }
+    }
+}
 
-    Ok((simple_polygon, preview_points))
+/// Calcule la réduction de densité à appliquer aux polygones restants pour tenir
+/// la durée visée. Le temps de remplissage étant proportionnel au nombre de points,
+/// soit à l'inverse du carré de la distance minimale, les distances sont multipliées
+/// par la racine du dépassement du dernier polygone sur son budget.
+///
+/// # Arguments
+/// * `target` - Durée visée de l'export, en secondes
+/// * `elapsed` - Durée écoulée depuis le début de l'export
+/// * `last_polygon` - Durée de traitement du dernier polygone
+/// * `remaining` - Nombre de polygones restants
+///
+/// # Retours
+/// Le facteur à appliquer aux distances, ou None si l'export tient la durée visée
+pub fn density_step(target: f64, elapsed: f64, last_polygon: f64, remaining: usize) -> Option<f64> {
+    if remaining == 0 {
+        return None;
+    }
+    let budget = (target - elapsed) / remaining as f64;
+    if last_polygon <= budget {
+        return None;
+    }
+    if budget <= 0.0 {
+        return Some(MAX_DENSITY_STEP);
+    }
+    Some((last_polygon / budget).sqrt().min(MAX_DENSITY_STEP))
 }
 
-/// Écrit l'en-tête dans le fichier de sortie.
+/// Nombre de polygones remplis par thread dans chaque lot d'un export parallèle.
+const POLYGONS_PER_THREAD: usize = 4;
+
+/// Nombre de points estimé à partir duquel un export parallèle ne garde pas en mémoire
+/// les points d'un polygone : il est rempli au fil de l'écriture, par le thread qui écrit.
+const STREAMED_POLYGON_POINTS: usize = 100_000;
+
+/// Message d'erreur d'un export annulé avant le polygone `index`.
+fn cancelled_message(index: usize) -> String {
+    format!("Export cancelled after {} polygon(s)", index)
+}
+
+/// Complète les attributs d'un polygone par la valeur de la colonne `z` choisie par
+/// `options.z_source`. Une colonne `z` du fichier source reste prioritaire sur
+/// l'altitude moyenne de la géométrie.
+fn elevation_attributes<'a>(
+    overrides: &'a PolygonOverrides,
+    options: &ExportOptions,
+) -> Cow<'a, BTreeMap<String, String>> {
+    let z = match (options.z_source, overrides.elevation) {
+        (ZSource::PolygonMean, Some(elevation)) if !overrides.attributes.contains_key("z") => {
+            match options.coordinate_precision {
+                Some(precision) => format!("{:.*}", precision as usize, elevation),
+                None => elevation.to_string(),
+            }
+        }
+        (ZSource::None, _) => String::new(),
+        _ => return Cow::Borrowed(&overrides.attributes),
+    };
+    let mut attributes = overrides.attributes.clone();
+    attributes.insert("z".to_string(), z);
+    Cow::Owned(attributes)
+}
+
+/// Désigne un polygone dans les messages d'erreur, avec sa ligne dans le fichier source si connue.
+fn polygon_label(index: usize, overrides: Option<&PolygonOverrides>) -> String {
+    match overrides.and_then(|o| o.source_line) {
+        Some(line) => format!("polygon {} (line {})", index + 1, line),
+        None => format!("polygon {}", index + 1),
+    }
+}
+
+/// Génère les points de chaque polygone et les écrit, précédés de l'en-tête, dans `writer`.
+/// La progression est suivie dans `state` et notifiée à `reporter`.
 ///
 /// # Arguments
-/// * `writer` - Writer pour écrire dans le fichier
+/// * `data` - Les polygones à remplir
+/// * `param` - Les paramètres de végétation globaux
+/// * `overrides` - Les paramètres propres à chaque polygone, dans le même ordre que `data`
+/// * `options` - Options de mise en forme de l'export
+/// * `writer` - Destination des lignes générées
+/// * `state` - État de progression de l'export
+/// * `reporter` - Destination des notifications de progression
 ///
 /// # Retours
-/// Ok(()) en cas de succès ou une erreur
-pub fn write_header(writer: &mut BufWriter<File>) -> Result<(), Box<dyn Error>> {
-    writer.write_all(b"X\tY\tNom\tNUMERO_DEPARTEMENT\tCODE_BASS\tCODE_INSEE\tIDIndexDATA\tCLEGCES\tNOM_PLAN_DEPLOIEMENT\tCODE_REGION\tCODE_INSEE_SGA\tchamp_graphe\tlongueur_specifique\tvitesse_specifique\tNUMERO_INSEE\tGROUPEMENT\tNOM_ZONE_OP\tSECTEUR_SINISTRE\tOBSERVATIONS\tDFCI_ID_MOT\tAUTRE_APPELATION\tAUTRE_APPELATION_1\tAUTRE_APPELATION_2\tAUTRE_APPELATION_3\tTYPE_AUTRE_APPELATION\tTYPE_AUTRE_APPELATION_1\tTYPE_AUTRE_APPELATION_2\tTYPE_AUTRE_APPELATION_3\tADRESSE\tLongueur specifique\tVitesse specifique\tIdZoneGeo\tz\ttype\tID\n")?;
-    Ok(())
+/// Le nombre total de points écrits, ou une erreur indiquant le polygone et le point
+/// dont l'écriture a échoué
+pub fn export_to_writer<W: Write>(
+    data: &[Polygon<f64>],
+    param: &VegetationParams,
+    overrides: &[PolygonOverrides],
+    options: &ExportOptions,
+    writer: &mut W,
+    state: &VegetationProcessingState,
+    reporter: &(impl ProgressReporter + Sync),
+) -> Result<usize, String> {
+    let rows = data.iter().enumerate().map(|(index, polygon)| {
+        Ok((
+            polygon.clone(),
+            overrides.get(index).cloned().unwrap_or_default(),
+        ))
+    });
+    export_rows_to_writer(rows, data.len(), param, options, writer, state, reporter)
 }
 
-#[tauri::command]
-pub fn export_results(
-    data: Vec<Polygon<f64>>,
-    param: VegetationParams,
-    state: State<'_, VegetationProcessingState>,
-    app_handle: AppHandle,
-) {
-    let state_arc = std::sync::Arc::new((*state.inner()).clone());
-    let param = param.clone();
-    let handle = app_handle.clone();
+/// Génère les points de chaque polygone d'un fichier CSV et les écrit dans `writer`
+/// comme `export_to_writer`, en lisant le fichier ligne à ligne : la mémoire occupée
+/// ne dépend pas de la taille du fichier. Les lignes sont d'abord comptées par
+/// `count_csv_rows` pour suivre la progression.
+///
+/// # Arguments
+/// * `file_path` - Chemin du fichier CSV, au format de `parse_csv_file`
+/// * `param` - Les paramètres de végétation globaux
+/// * `options` - Options de mise en forme de l'export
+/// * `writer` - Destination des lignes générées
+/// * `state` - État de progression de l'export
+/// * `reporter` - Destination des notifications de progression
+///
+/// # Retours
+/// Le nombre total de points écrits, ou une erreur de lecture ou d'écriture
+pub fn export_csv_to_writer<W: Write>(
+    file_path: &str,
+    param: &VegetationParams,
+    options: &ExportOptions,
+    writer: &mut W,
+    state: &VegetationProcessingState,
+    reporter: &(impl ProgressReporter + Sync),
+) -> Result<usize, String> {
+    let total_rows = count_csv_rows(file_path, None, None)?;
+    // Le comptage parcourt tout le fichier : l'annulation a pu être demandée entre-temps
+    if state.is_cancel_requested() {
+        return Err(cancelled_message(0));
+    }
+    let rows = iter_csv_rows(file_path, None, None)?;
+    export_rows_to_writer(rows, total_rows, param, options, writer, state, reporter)
+}
 
-    std::thread::spawn(
-        move || match run_export(data, param, state_arc, handle.clone()) {
-            Ok(filename) => {
-                let _ = handle.emit("vegetation-export-finished", &filename);
+/// Remplit et écrit les polygones lus au fur et à mesure dans `rows`.
+///
+/// # Arguments
+/// * `rows` - Les polygones et leurs paramètres propres ; une ligne en erreur est
+///   signalée dans les erreurs de l'export, qui continue
+/// * `total_rows` - Nombre de polygones attendus, 0 s'il est inconnu
+///
+/// # Retours
+/// Le nombre total de points écrits, ou une erreur
+fn export_rows_to_writer<W: Write>(
+    rows: impl Iterator<Item = Result<PolygonRow, String>>,
+    total_rows: usize,
+    param: &VegetationParams,
+    options: &ExportOptions,
+    writer: &mut W,
+    state: &VegetationProcessingState,
+    reporter: &(impl ProgressReporter + Sync),
+) -> Result<usize, String> {
+    state.initialize(total_rows, reporter);
+
+    if let Some(warning) = options.separator_warning() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    // Les polygones sont échantillonnés dans la projection métrique demandée, puis
+    // les points écrits dans le système de sortie, celui du fichier source par défaut
+    let to_sampling = match (param.source_epsg, param.sampling_epsg) {
+        (Some(source), Some(sampling)) => Some(coordinate_transform(source, sampling)?),
+        (None, Some(_)) => {
+            return Err("A sampling EPSG code requires the source EPSG code.".to_string());
+        }
+        (_, None) => None,
+    };
+    let rows = rows.map(move |row| {
+        row.map(|(polygon, overrides)| match to_sampling {
+            Some(transform) => (polygon.map_coords(transform), overrides),
+            None => (polygon, overrides),
+        })
+    });
+    let working_epsg = param.sampling_epsg.or(param.source_epsg);
+    let to_output = match (working_epsg, options.output_epsg.or(param.source_epsg)) {
+        (Some(from), Some(to)) => coordinate_transform(from, to)?,
+        (None, Some(_)) => {
+            return Err("An output EPSG code requires the source EPSG code.".to_string());
+        }
+        (_, None) => same_coordinates,
+    };
+
+    write_header_with(writer, options).map_err(|e| format!("Failed to write header: {}", e))?;
+
+    let output = PolygonOutput {
+        options,
+        to_output,
+        state,
+        reporter,
+    };
+    // La densité adaptative dépend de la durée des polygones précédents : elle impose
+    // un traitement dans l'ordre
+    let total_created_items = if options.adaptive_density || options.max_threads == Some(1) {
+        fill_serial(rows, total_rows, param, writer, &output)?
+    } else {
+        fill_parallel(rows, param, writer, &output)?
+    };
+
+    write_footer(writer, options).map_err(|e| format!("Failed to write footer: {}", e))?;
+
+    state.set_finished(reporter);
+
+    Ok(total_created_items)
+}
+
+/// Contexte d'écriture des points d'un export, commun à tous les polygones.
+struct PolygonOutput<'a, R> {
+    options: &'a ExportOptions,
+    to_output: CoordinateTransform,
+    state: &'a VegetationProcessingState,
+    reporter: &'a R,
+}
+
+impl<R: ProgressReporter> PolygonOutput<'_, R> {
+    /// Écrit les points d'un polygone, ou enregistre l'erreur de remplissage.
+    /// Les points sont écrits au fur et à mesure qu'ils sont produits.
+    ///
+    /// # Arguments
+    /// * `writer` - Destination des lignes générées
+    /// * `index` - Indice du polygone
+    /// * `overrides` - Les paramètres propres du polygone, dont ses attributs
+    /// * `polygon_param` - Les paramètres de végétation du polygone
+    /// * `polygon_points` - Les points générés, ou l'erreur de remplissage
+    /// * `written` - Nombre de points déjà écrits
+    ///
+    /// # Retours
+    /// Le nombre total de points écrits, ou une erreur d'écriture
+    fn write<W: Write>(
+        &self,
+        writer: &mut W,
+        index: usize,
+        overrides: &PolygonOverrides,
+        polygon_param: &VegetationParams,
+        polygon_points: Result<impl IntoIterator<Item = Point<f64>>, String>,
+        written: usize,
+    ) -> Result<usize, String> {
+        let (options, state, reporter) = (self.options, self.state, self.reporter);
+        let label = || polygon_label(index, Some(overrides));
+        let points = match polygon_points {
+            Ok(points) => points,
+            Err(e) => {
+                state.add_error(format!("Error filling {}: {}", label(), e), reporter);
+                return Ok(written);
             }
-            Err(err_msg) => {
-                eprintln!("Export failed: {}", err_msg);
-                let _ = handle.emit("vegetation-export-error", &err_msg);
+        };
+
+        let attributes = &elevation_attributes(overrides, options);
+        let mut count = 0;
+        if options.format == OutputFormat::WktMultipoint {
+            let output_points: Vec<Point<f64>> = points
+                .into_iter()
+                .map(|point| Point::from((self.to_output)(point.0)))
+                .collect();
+            count = output_points.len();
+            // Un remplissage vide sans erreur n'a pas de place pour ses points
+            if count == 0 {
+                return Ok(written);
             }
-        },
-    );
-}
+            let row = format_multipoint_row(&output_points, polygon_param, options);
+            if let Err(e) = writer.write_all(row.as_bytes()) {
+                let error_msg = format!(
+                    "Failed to write {} ({} points written): {}",
+                    label(),
+                    written,
+                    e
+                );
+                state.add_error(error_msg.clone(), reporter);
+                return Err(error_msg);
+            }
+        } else {
+            for (point_index, point) in points.into_iter().enumerate() {
+                let row = format_point_row_with_attributes(
+                    &Point::from((self.to_output)(point.0)),
+                    polygon_param,
+                    attributes,
+                    options,
+                );
+                let separator = if written + point_index > 0 {
+                    options.format.row_separator()
+                } else {
+                    ""
+                };
+                if let Err(e) = writer
+                    .write_all(separator.as_bytes())
+                    .and_then(|()| writer.write_all(row.as_bytes()))
+                {
+                    let error_msg = format!(
+                        "Failed to write point {} of {} ({} points written): {}",
+                        point_index + 1,
+                        label(),
+                        written + point_index,
+                        e
+                    );
+                    state.add_error(error_msg.clone(), reporter);
+                    return Err(error_msg);
+                }
+                count += 1;
+            }
+        }
 
-fn run_export(
-    data: Vec<Polygon<f64>>,
-    param: VegetationParams,
-    state: std::sync::Arc<VegetationProcessingState>,
-    app_handle: AppHandle,
-) -> Result<String, String> {
-    state.initialize(data.len(), &app_handle);
+        let written = written + count;
+        state.update_created_items(written, reporter);
+        Ok(written)
+    }
 
-    let now = chrono::Local::now();
-    let output_filename = format!("Export {}.txt", now.format("%d-%m-%Y %Hh%M-%S"));
-    let export_path = get_export_path();
-    let export_path = std::path::Path::new(&export_path);
-    let mut writer = std::io::BufWriter::new(
-        std::fs::File::create(export_path.join(&output_filename))
-            .map_err(|e| format!("Failed to create file: {}", e))?,
-    );
+    /// Remplit un polygone avec le sampler de `streaming_sampler` et écrit ses points
+    /// au fil de leur génération, sans garder en mémoire la liste complète des points.
+    ///
+    /// # Arguments
+    /// * `writer` - Destination des lignes générées
+    /// * `index` - Indice du polygone
+    /// * `overrides` - Les paramètres propres du polygone, dont ses attributs
+    /// * `polygon_param` - Les paramètres de végétation du polygone
+    /// * `polygon` - Le polygone à remplir, déjà réparé si demandé
+    /// * `sampler` - Le sampler du polygone
+    /// * `rng` - Le générateur des décalages
+    /// * `written` - Nombre de points déjà écrits
+    ///
+    /// # Retours
+    /// Le nombre total de points écrits, ou une erreur d'écriture
+    #[allow(clippy::too_many_arguments)]
+    fn write_streamed<W: Write>(
+        &self,
+        writer: &mut W,
+        index: usize,
+        overrides: &PolygonOverrides,
+        polygon_param: &VegetationParams,
+        polygon: &Polygon<f64>,
+        sampler: &mut SpatialDistributionSampler,
+        rng: &mut StdRng,
+        written: usize,
+    ) -> Result<usize, String> {
+        let mut points = sampler
+            .iter_points(polygon)
+            .map(|point| {
+                vary_point(
+                    point,
+                    polygon,
+                    polygon_param.variation,
+                    polygon_param.edge_margin,
+                    polygon_param.interior_buffer,
+                    &polygon_param.exclusion_zones,
+                    rng,
+                )
+            })
+            .peekable();
+        // Un polygone rempli au fil de l'eau peut se révéler vide dès le départ
+        match points.peek() {
+            Some(_) => self.write(writer, index, overrides, polygon_param, Ok(points), written),
+            None => self.write(
+                writer,
+                index,
+                overrides,
+                polygon_param,
+                empty_fill(std::slice::from_ref(polygon), polygon_param.edge_margin),
+                written,
+            ),
+        }
+    }
+}
 
-    write_header(&mut writer).map_err(|e| format!("Failed to write header: {}", e))?;
-    let cloned_param = param.clone();
+/// Répare le polygone si les paramètres le demandent, avant un remplissage au fil de l'eau
+/// qui doit travailler sur le polygone déjà réparé.
+fn repaired(polygon: Polygon<f64>, param: &VegetationParams) -> Result<Polygon<f64>, String> {
+    if param.repair_geometry {
+        repair_polygon(&polygon)
+    } else {
+        Ok(polygon)
+    }
+}
 
+/// Remplit et écrit les polygones un par un, en réduisant la densité des polygones
+/// restants si l'export risque de dépasser la durée visée.
+///
+/// # Retours
+/// Le nombre total de points écrits, ou une erreur d'écriture
+fn fill_serial<W: Write, R: ProgressReporter>(
+    rows: impl Iterator<Item = Result<PolygonRow, String>>,
+    total_rows: usize,
+    param: &VegetationParams,
+    writer: &mut W,
+    output: &PolygonOutput<'_, R>,
+) -> Result<usize, String> {
+    let (options, state, reporter) = (output.options, output.state, output.reporter);
     let mut total_created_items = 0;
+    let export_start = Instant::now();
+    let mut density_factor = 1.0;
+    // Sampler du polygone précédent, dont la grille est réutilisée par le suivant
+    let mut spare_sampler = None;
 
-    for (index, polygon) in data.iter().enumerate() {
-        let polygon_points = fill_polygon(polygon.clone(), cloned_param.clone());
-        match polygon_points {
-            Ok(points) => {
-                let points_len = points.len();
-                for point in points {
-                    writer
-                        .write_all(point.as_bytes())
-                        .map_err(|e| format!("Failed to write to file: {}", e))?;
-                }
-                total_created_items += points_len;
-                state.update_created_items(total_created_items, &app_handle);
+    for (index, row) in rows.enumerate() {
+        if state.is_cancel_requested() {
+            return Err(cancelled_message(index));
+        }
+        let (polygon, overrides) = match row {
+            Ok(row) => row,
+            // Une ligne illisible est signalée avec sa ligne source, comme un polygone
+            // non rempli, sans interrompre l'export
+            Err(e) => {
+                state.add_error(e, reporter);
+                state.update_processed_rows(index + 1, reporter);
+                continue;
             }
+        };
+        state.set_current_bounds(calculate_polygon_bounds(&polygon), reporter);
+        let polygon_start = Instant::now();
+
+        let polygon_param = overrides.apply(param);
+        let polygon_param = if density_factor > 1.0 {
+            polygon_param.coarsened(density_factor)
+        } else {
+            polygon_param
+        };
+        let polygon = match repaired(polygon, &polygon_param) {
+            Ok(polygon) => polygon,
             Err(e) => {
-                let error_msg = format!("Error filling polygon {}: {}", index + 1, e);
-                state.add_error(error_msg, &app_handle);
+                total_created_items = output.write(
+                    writer,
+                    index,
+                    &overrides,
+                    &polygon_param,
+                    Err::<Vec<Point<f64>>, _>(e),
+                    total_created_items,
+                )?;
+                state.update_processed_rows(index + 1, reporter);
+                continue;
             }
+        };
+        let polygon = &polygon;
+        // Un disque de Poisson simple est écrit au fil de sa génération, sans garder
+        // en mémoire la liste complète des points du polygone
+        total_created_items = match streaming_sampler(polygon, &polygon_param) {
+            Some((sampler, mut rng)) => {
+                let mut sampler = match spare_sampler.take() {
+                    Some(previous) => sampler.reusing(previous),
+                    None => sampler,
+                };
+                let written = output.write_streamed(
+                    writer,
+                    index,
+                    &overrides,
+                    &polygon_param,
+                    polygon,
+                    &mut sampler,
+                    &mut rng,
+                    total_created_items,
+                )?;
+                spare_sampler = Some(sampler);
+                written
+            }
+            None => output.write(
+                writer,
+                index,
+                &overrides,
+                &polygon_param,
+                generate_points(polygon.clone(), &polygon_param),
+                total_created_items,
+            )?,
+        };
+
+        state.update_processed_rows(index + 1, reporter);
+
+        if options.adaptive_density
+            && let Some(target) = options.target_duration_seconds
+            && let Some(step) = density_step(
+                target,
+                export_start.elapsed().as_secs_f64(),
+                polygon_start.elapsed().as_secs_f64(),
+                total_rows.saturating_sub(index + 1),
+            )
+        {
+            density_factor *= step;
+            state.add_density_adjustment(
+                DensityAdjustment {
+                    from_polygon: index + 2,
+                    factor: density_factor,
+                },
+                reporter,
+            );
         }
+    }
+
+    Ok(total_created_items)
+}
+
+/// Remplissage d'un polygone préparé par un thread d'un export parallèle, en attente
+/// de son écriture.
+enum PendingFill {
+    /// Les points générés, ou l'erreur de remplissage
+    Points(Result<Vec<Point<f64>>, String>),
+    /// Un polygone d'au moins `STREAMED_POLYGON_POINTS` points estimés, rempli au fil
+    /// de l'écriture avec son sampler et le générateur des décalages
+    Streamed(Box<(Polygon<f64>, SpatialDistributionSampler, StdRng)>),
+}
 
-        state.update_processed_rows(index + 1, &app_handle);
+impl PendingFill {
+    /// Prépare le remplissage d'un polygone : ses points sont générés tout de suite,
+    /// sauf pour un polygone trop peuplé pour les garder en mémoire, que `streaming_sampler`
+    /// sait remplir au fil de l'eau.
+    ///
+    /// # Arguments
+    /// * `polygon` - Le polygone à remplir
+    /// * `polygon_param` - Les paramètres de végétation du polygone
+    /// * `spare_sampler` - Sampler du polygone précédent de la même tâche rayon, dont la
+    ///   grille est réutilisée
+    fn prepare(
+        polygon: &Polygon<f64>,
+        polygon_param: &VegetationParams,
+        spare_sampler: &mut Option<SpatialDistributionSampler>,
+    ) -> Self {
+        let polygon = match repaired(polygon.clone(), polygon_param) {
+            Ok(polygon) => polygon,
+            Err(e) => return PendingFill::Points(Err(e)),
+        };
+        match streaming_sampler(&polygon, polygon_param) {
+            Some((sampler, rng)) if sampler.estimate_count(&polygon) >= STREAMED_POLYGON_POINTS => {
+                PendingFill::Streamed(Box::new((polygon, sampler, rng)))
+            }
+            Some((sampler, mut rng)) => {
+                let mut sampler = match spare_sampler.take() {
+                    Some(previous) => sampler.reusing(previous),
+                    None => sampler,
+                };
+                let points: Vec<Point<f64>> = sampler
+                    .iter_points(&polygon)
+                    .map(|point| {
+                        vary_point(
+                            point,
+                            &polygon,
+                            polygon_param.variation,
+                            polygon_param.edge_margin,
+                            polygon_param.interior_buffer,
+                            &polygon_param.exclusion_zones,
+                            &mut rng,
+                        )
+                    })
+                    .collect();
+                *spare_sampler = Some(sampler);
+                PendingFill::Points(if points.is_empty() {
+                    empty_fill(std::slice::from_ref(&polygon), polygon_param.edge_margin)
+                } else {
+                    Ok(points)
+                })
+            }
+            None => PendingFill::Points(generate_points(polygon, polygon_param)),
+        }
     }
+}
+
+/// Remplit les polygones en parallèle par lots, puis écrit les points de chaque lot
+/// dans l'ordre des polygones, pour que le fichier produit ne dépende pas de l'ordre
+/// de fin des threads. Les lots bornent la mémoire occupée par les points en attente ;
+/// les polygones d'au moins `STREAMED_POLYGON_POINTS` points estimés sont remplis au fil
+/// de l'écriture, sans paralléliser leur échantillonnage.
+///
+/// # Retours
+/// Le nombre total de points écrits, ou une erreur d'écriture
+fn fill_parallel<W: Write, R: ProgressReporter + Sync>(
+    mut rows: impl Iterator<Item = Result<PolygonRow, String>>,
+    param: &VegetationParams,
+    writer: &mut W,
+    output: &PolygonOutput<'_, R>,
+) -> Result<usize, String> {
+    let (state, reporter) = (output.state, output.reporter);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(output.options.max_threads.unwrap_or(0))
+        .build()
+        .map_err(|e| format!("Failed to start export threads: {}", e))?;
+    let chunk_size = pool.current_num_threads() * POLYGONS_PER_THREAD;
+    let mut total_created_items = 0;
+    let mut first_index = 0;
 
-    state.set_finished(&app_handle);
+    loop {
+        // Seul le lot en cours est gardé en mémoire
+        let chunk: Vec<Result<PolygonRow, String>> = rows.by_ref().take(chunk_size).collect();
+        if chunk.is_empty() {
+            break;
+        }
+        if state.is_cancel_requested() {
+            return Err(cancelled_message(first_index));
+        }
+        let filled: Vec<Result<(&PolygonRow, VegetationParams, PendingFill), String>> = pool
+            .install(|| {
+                // Chaque tâche rayon garde le sampler de son dernier polygone, dont la
+                // grille est réutilisée par le suivant
+                chunk
+                    .par_iter()
+                    .map_init(
+                        || None,
+                        |spare_sampler, row| {
+                            let filled = row.as_ref().map_err(String::clone).map(|row| {
+                                let polygon_param = row.1.apply(param);
+                                let pending =
+                                    PendingFill::prepare(&row.0, &polygon_param, spare_sampler);
+                                (row, polygon_param, pending)
+                            });
+                            state.increment_processed_rows(reporter);
+                            filled
+                        },
+                    )
+                    .collect()
+            });
 
-    writer
-        .flush()
-        .map_err(|e| format!("Failed to flush writer: {}", e))?;
+        for (offset, filled) in filled.into_iter().enumerate() {
+            let index = first_index + offset;
+            let ((polygon, overrides), polygon_param, pending) = match filled {
+                Ok(filled) => filled,
+                // Une ligne illisible est signalée comme en remplissage série
+                Err(e) => {
+                    state.add_error(e, reporter);
+                    continue;
+                }
+            };
+            state.set_current_bounds(calculate_polygon_bounds(polygon), reporter);
+            total_created_items = match pending {
+                PendingFill::Points(polygon_points) => output.write(
+                    writer,
+                    index,
+                    overrides,
+                    &polygon_param,
+                    polygon_points,
+                    total_created_items,
+                )?,
+                PendingFill::Streamed(streamed) => {
+                    let (polygon, mut sampler, mut rng) = *streamed;
+                    output.write_streamed(
+                        writer,
+                        index,
+                        overrides,
+                        &polygon_param,
+                        &polygon,
+                        &mut sampler,
+                        &mut rng,
+                        total_created_items,
+                    )?
+                }
+            };
+        }
+        first_index += chunk.len();
+    }
 
-    Ok(output_filename)
+    Ok(total_created_items)
 }
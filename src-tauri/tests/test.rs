@@ -1,20 +1,129 @@
 #[cfg(test)]
 mod tests {
-    use vegepoly_lib::sampling::fill_polygon;
+    use vegepoly_lib::diff::diff_exports;
+    use vegepoly_lib::models::export::{
+        ExportOptions, ExportPreset, ExportSummary, LineEnding, OutputFormat, OutputTemplate,
+        ZSource,
+    };
+    use vegepoly_lib::models::processing::{
+        CallbackReporter, VegetationProcessingState, VegetationProgressInfo,
+    };
+    use vegepoly_lib::models::settings::{
+        DEFAULT_COORDINATE_PRECISION, Settings, SettingsBundle, SettingsProfile,
+    };
+    use vegepoly_lib::models::vegetations::{
+        DensityMode, ParamsError, VegetationParams, VegetationParamsBuilder,
+    };
+    use vegepoly_lib::risk::{CrsGuess, risk_report};
+    use vegepoly_lib::sampling::{
+        DensityField, DensityGradient, SamplerConfig, SamplingStrategy, SpatialDistributionSampler,
+        calculate_polygon_bounds, distance_to_boundary, distance_to_holes, estimate_total_points,
+        fill_polygon, fill_polygon_with_stats, generate_points, generate_tileable_pattern,
+        get_sampling_stats, point_in_polygon, repair_polygon, resolve_effective_min_distance,
+        suggest_point,
+    };
 
-    use vegepoly_lib::utils::parse_csv_file;
+    use vegepoly_lib::utils::{
+        GeometryColumn, MAX_DENSITY_STEP, OUTPUT_HEADER, SimplePoint, count_csv_rows, density_step,
+        export_all_types_to_directory, export_csv_to_writer, export_filename,
+        export_progress_stream, export_to_directory, export_to_writer, export_wkt_to_directory,
+        format_point_row, format_point_row_with_attributes, get_output_columns, get_preview_data,
+        iter_csv_polygons, keep_partial_export, parse_csv_file, parse_csv_file_lenient,
+        parse_csv_file_with, parse_csv_file_with_lines, parse_csv_file_with_overrides,
+        parse_geojson_file, parse_geojson_file_lenient, parse_geometry_file, parse_input_file,
+        parse_wkt_polygons, reproject_point, reproject_polygon, reproject_polygons,
+        write_export_atomically, write_header,
+    };
+
+    const SQUARE_WKT: &str = "POLYGON((0 0,200 0,200 200,0 200,0 0))";
+    const DONUT_WKT: &str =
+        "POLYGON((0 0,200 0,200 200,0 200,0 0),(50 50,150 50,150 150,50 150,50 50))";
+
+    /// Fichier temporaire d'un test, supprimé quand il sort de portée, même si le test échoue.
+    struct TempFile(std::path::PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            TempFile(std::env::temp_dir().join(format!("vegepoly-{}-{}", std::process::id(), name)))
+        }
+
+        fn path(&self) -> &std::path::Path {
+            &self.0
+        }
+    }
+
+    impl std::ops::Deref for TempFile {
+        type Target = str;
+
+        fn deref(&self) -> &str {
+            self.0.to_str().expect("Temporary path is not valid UTF-8")
+        }
+    }
+
+    impl AsRef<std::path::Path> for TempFile {
+        fn as_ref(&self) -> &std::path::Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn write_temp_file(name: &str, contents: &str) -> TempFile {
+        let file = TempFile::new(name);
+        std::fs::write(file.path(), contents).expect("Failed to write temporary file");
+        file
+    }
+
+    fn open_temp_settings(name: &str) -> (Settings, TempFile) {
+        let database = TempFile::new(&format!("{}.db", name));
+        let _ = std::fs::remove_file(&database);
+        let settings =
+            Settings::open(database.path().to_path_buf()).expect("Failed to open settings");
+        (settings, database)
+    }
+
+    fn parse_points(rows: &[String]) -> Vec<(f64, f64)> {
+        rows.iter()
+            .filter_map(|row| {
+                let mut fields = row.split('\t');
+                let x = fields.next()?.trim().parse().ok()?;
+                let y = fields.next()?.trim().parse().ok()?;
+                Some((x, y))
+            })
+            .collect()
+    }
+
+    fn nearest_neighbor_distances(points: &[(f64, f64)]) -> Vec<f64> {
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                points
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, b)| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt())
+                    .fold(f64::INFINITY, f64::min)
+            })
+            .collect()
+    }
 
     #[test]
     fn test_fill_polygon() {
-        let polygons =
-            parse_csv_file("tests/VEGETATION_ARBRES.csv").expect("Failed to parse CSV file");
+        let polygons = parse_csv_file("tests/VEGETATION_ARBRES.csv", None, None)
+            .expect("Failed to parse CSV file");
         println!("Parsed {} polygons from CSV file", polygons.len());
         println!("First polygon: {:?}", polygons[0]);
 
-        let params = vegepoly_lib::models::vegetations::VegetationParams {
+        let params = VegetationParams {
             vegetation_type: 1,
             density: 28.0,
             type_value: 10,
+            ..Default::default()
         };
 
         let result = fill_polygon(polygons[0].clone(), params)
@@ -23,4 +132,4549 @@ mod tests {
 
         println!("{:?}", result);
     }
+
+    #[test]
+    fn test_output_columns_match_header() {
+        let mut buffer = Vec::new();
+        write_header(&mut buffer).expect("Failed to write header");
+        let header = String::from_utf8(buffer).expect("Header is not valid UTF-8");
+
+        let columns = get_output_columns();
+        assert_eq!(columns.len(), header.trim_end().split('\t').count());
+        assert_eq!(columns.first().map(String::as_str), Some("X"));
+        assert_eq!(columns.last().map(String::as_str), Some("ID"));
+    }
+
+    #[test]
+    fn test_per_polygon_variation() {
+        let path = write_temp_file(
+            "variation.csv",
+            &format!("Geometry\tvariation\n{SQUARE_WKT}\t0\n{SQUARE_WKT}\t10\n"),
+        );
+        let rows =
+            parse_csv_file_with_overrides(&path, None, None).expect("Failed to parse CSV file");
+        assert_eq!(rows.len(), 2);
+
+        let params = VegetationParams {
+            density: 10.0,
+            ..Default::default()
+        };
+        let close_pairs: Vec<usize> = rows
+            .iter()
+            .map(|(polygon, overrides)| {
+                let rows = fill_polygon(polygon.clone(), overrides.apply(&params))
+                    .expect("Failed to fill polygon");
+                nearest_neighbor_distances(&parse_points(&rows))
+                    .into_iter()
+                    .filter(|distance| *distance < params.density / 2.0)
+                    .count()
+            })
+            .collect();
+
+        assert_eq!(close_pairs[0], 0);
+        assert!(close_pairs[1] > 0);
+    }
+
+    #[test]
+    fn test_flush_settings_persists_params() {
+        let (settings, path) = open_temp_settings("flush");
+        let params = VegetationParams {
+            vegetation_type: 2,
+            density: 7.5,
+            type_value: 25,
+            ..Default::default()
+        };
+        settings
+            .set_user_vegetation_params(2, params)
+            .expect("Failed to set user params");
+        settings.flush().expect("Failed to flush settings");
+
+        let reopened =
+            Settings::open(path.path().to_path_buf()).expect("Failed to reopen settings");
+        let stored = reopened
+            .get_user_vegetation_params(2)
+            .expect("Failed to read user params")
+            .expect("User params were not persisted");
+        assert_eq!(stored.density, 7.5);
+        assert_eq!(stored.type_value, 25);
+    }
+
+    #[test]
+    fn test_progress_reports_current_bounds() {
+        let polygons =
+            parse_csv_file("tests/VEGETATION_ARBRES.csv", None, None).expect("Failed to parse CSV");
+        let polygons = &polygons[..2];
+        let params = VegetationParams {
+            density: 28.0,
+            ..Default::default()
+        };
+
+        let reported = std::sync::Mutex::new(Vec::new());
+        let reporter = CallbackReporter(|progress: &VegetationProgressInfo| {
+            let bounds = progress.current_bounds;
+            let mut reported = reported.lock().unwrap();
+            if bounds.is_some() && reported.last() != Some(&bounds) {
+                reported.push(bounds);
+            }
+        });
+        let state = VegetationProcessingState::new();
+        export_to_writer(
+            polygons,
+            &params,
+            &[],
+            &ExportOptions::default(),
+            &mut Vec::<u8>::new(),
+            &state,
+            &reporter,
+        )
+        .expect("Export failed");
+
+        let expected: Vec<_> = polygons.iter().map(calculate_polygon_bounds).collect();
+        assert_eq!(*reported.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_rebuild_default_params_restores_code_defaults() {
+        let (settings, path) = open_temp_settings("rebuild");
+        let conn = rusqlite::Connection::open(&path).expect("Failed to open database");
+        conn.execute(
+            "UPDATE default_vegetation_params SET density = 99.0 WHERE vegetation_type = 1",
+            [],
+        )
+        .expect("Failed to alter default params");
+
+        settings
+            .rebuild_default_params()
+            .expect("Failed to rebuild default params");
+
+        let params = settings
+            .get_default_vegetation_params(1)
+            .expect("Failed to read default params")
+            .expect("Default params missing");
+        assert_eq!(params.density, 28.0);
+    }
+
+    #[test]
+    fn test_tileable_pattern_respects_distance_across_wrap() {
+        let min_distance = 5.0;
+        let tile_size = 50.0;
+        let pattern = generate_tileable_pattern(min_distance, tile_size);
+        assert!(!pattern.is_empty());
+
+        let wrapped = |a: f64, b: f64| {
+            let d = (a - b).abs();
+            d.min(tile_size - d)
+        };
+        for (i, a) in pattern.iter().enumerate() {
+            for b in &pattern[i + 1..] {
+                let dx = wrapped(a.x(), b.x());
+                let dy = wrapped(a.y(), b.y());
+                assert!((dx * dx + dy * dy).sqrt() >= min_distance - 1e-9);
+            }
+        }
+
+        let polygons = parse_csv_file(
+            &write_temp_file("tileable.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let params = VegetationParams {
+            density: min_distance,
+            strategy: SamplingStrategy::TileableBlueNoise { tile_size },
+            ..Default::default()
+        };
+        let points = parse_points(
+            &fill_polygon(polygons[0].clone(), params).expect("Failed to fill polygon"),
+        );
+        let closest = nearest_neighbor_distances(&points)
+            .into_iter()
+            .fold(f64::INFINITY, f64::min);
+        assert!(closest >= min_distance - 1e-6);
+    }
+
+    #[test]
+    fn test_diff_exports() {
+        let polygons =
+            parse_csv_file("tests/VEGETATION_ARBRES.csv", None, None).expect("Failed to parse CSV");
+        let params = VegetationParams {
+            density: 28.0,
+            ..Default::default()
+        };
+        let rows = fill_polygon(polygons[0].clone(), params).expect("Failed to fill polygon");
+
+        let mut header = Vec::new();
+        write_header(&mut header).expect("Failed to write header");
+        let header = String::from_utf8(header).expect("Header is not valid UTF-8");
+        let shifted: Vec<String> = parse_points(&rows)
+            .iter()
+            .map(|(x, y)| format!("{}\t{}\n", x + 0.5, y))
+            .collect();
+
+        let baseline = write_temp_file("diff-a.txt", &(header.clone() + &rows.concat()));
+        let moved = write_temp_file("diff-b.txt", &(header + &shifted.concat()));
+
+        let same = diff_exports(&baseline, &baseline, 1.0).expect("Diff failed");
+        assert!(same.added.is_empty() && same.removed.is_empty() && same.moved.is_empty());
+        assert_eq!(same.unchanged, rows.len());
+
+        let diff = diff_exports(&baseline, &moved, 1.0).expect("Diff failed");
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.moved.len(), rows.len());
+    }
+
+    #[test]
+    fn test_fill_polygon_clip_region() {
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "clip.csv",
+                &format!(
+                    "Geometry\n{SQUARE_WKT}\nPOLYGON((-50 -50,100 -50,100 250,-50 250,-50 -50))\n"
+                ),
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let params = VegetationParams {
+            density: 10.0,
+            clip_region: Some(polygons[1].clone()),
+            ..Default::default()
+        };
+
+        let points = parse_points(
+            &fill_polygon(polygons[0].clone(), params).expect("Failed to fill polygon"),
+        );
+        assert!(!points.is_empty());
+        assert!(
+            points
+                .iter()
+                .all(|(x, y)| (0.0..=100.0).contains(x) && (0.0..=200.0).contains(y))
+        );
+    }
+
+    #[test]
+    fn test_point_in_polygon_include_boundary() {
+        let square = geo::Polygon::new(
+            geo::LineString::from(vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]),
+            vec![],
+        );
+        let edge_points: Vec<geo::Point<f64>> = (0..=10)
+            .flat_map(|i| {
+                let t = i as f64;
+                [(t, 0.0), (t, 10.0), (0.0, t), (10.0, t)]
+            })
+            .map(geo::Point::from)
+            .collect();
+
+        assert!(
+            edge_points
+                .iter()
+                .all(|p| point_in_polygon(&square, p, true))
+        );
+        assert!(
+            edge_points
+                .iter()
+                .all(|p| !point_in_polygon(&square, p, false))
+        );
+        assert!(!point_in_polygon(
+            &square,
+            &geo::Point::new(10.5, 5.0),
+            true
+        ));
+        assert!(point_in_polygon(&square, &geo::Point::new(5.0, 5.0), false));
+    }
+
+    #[test]
+    fn test_export_preset_round_trip() {
+        let (settings, _database) = open_temp_settings("presets");
+        let preset = ExportPreset {
+            options: ExportOptions {
+                coordinate_precision: Some(2),
+                line_ending: LineEnding::CrLf,
+                filename_prefix: "Parcelles".to_string(),
+                ..Default::default()
+            },
+            density: Some(12.0),
+        };
+        settings
+            .save_export_preset("terrain", &preset)
+            .expect("Failed to save preset");
+
+        assert_eq!(
+            settings
+                .list_export_presets()
+                .expect("Failed to list presets"),
+            vec!["terrain".to_string()]
+        );
+        let applied = settings
+            .get_export_preset("terrain")
+            .expect("Failed to load preset");
+        let (params, options) = applied.resolve(&VegetationParams::default());
+        assert_eq!(params.density, 12.0);
+        assert_eq!(options, preset.options);
+        assert!(settings.get_export_preset("inconnu").is_err());
+    }
+
+    #[test]
+    fn test_export_does_not_overwrite_existing_file() {
+        let directory =
+            std::env::temp_dir().join(format!("vegepoly-{}-unique", std::process::id()));
+        std::fs::create_dir_all(&directory).expect("Failed to create export directory");
+        let options = ExportOptions::default();
+        let timestamp = chrono::Local::now();
+        let taken = export_filename(&options, timestamp);
+        std::fs::write(directory.join(&taken), "existing").expect("Failed to create file");
+
+        let polygons =
+            parse_csv_file("tests/VEGETATION_ARBRES.csv", None, None).expect("Failed to parse CSV");
+        let params = VegetationParams {
+            density: 28.0,
+            ..Default::default()
+        };
+        let state = VegetationProcessingState::new();
+        let filename = export_to_directory(
+            &polygons[..1],
+            &params,
+            &[],
+            &options,
+            None,
+            &directory,
+            timestamp,
+            &state,
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+
+        assert_ne!(filename, taken);
+        assert!(directory.join(&filename).exists());
+        assert_eq!(
+            std::fs::read_to_string(directory.join(&taken)).unwrap(),
+            "existing"
+        );
+        std::fs::remove_dir_all(&directory).expect("Failed to clean export directory");
+    }
+
+    #[test]
+    fn test_anisotropic_spacing() {
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "anisotropic.csv",
+                "Geometry\nPOLYGON((0 0,400 0,400 400,0 400,0 0))\n",
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let (spacing_x, spacing_y) = (20.0, 10.0);
+        let params = VegetationParams {
+            density: spacing_y,
+            min_distance_x: Some(spacing_x),
+            min_distance_y: Some(spacing_y),
+            ..Default::default()
+        };
+        let points = parse_points(
+            &fill_polygon(polygons[0].clone(), params).expect("Failed to fill polygon"),
+        );
+
+        let (mut sum_dx, mut sum_dy) = (0.0, 0.0);
+        for (i, a) in points.iter().enumerate() {
+            let nearest = points
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, b)| (b.0 - a.0, b.1 - a.1))
+                .min_by(|u, v| {
+                    let norm =
+                        |d: &(f64, f64)| (d.0 / spacing_x).powi(2) + (d.1 / spacing_y).powi(2);
+                    norm(u).total_cmp(&norm(v))
+                })
+                .expect("Not enough points");
+            sum_dx += nearest.0.abs();
+            sum_dy += nearest.1.abs();
+        }
+
+        let ratio = sum_dx / sum_dy;
+        assert!((1.5..2.6).contains(&ratio), "spacing ratio was {}", ratio);
+    }
+
+    #[test]
+    fn test_risk_report_flags_geographic_coordinates() {
+        let path = write_temp_file(
+            "geographic.csv",
+            "Geometry\nPOLYGON((9.1 42.1,9.2 42.1,9.2 42.2,9.1 42.2,9.1 42.1))\n",
+        );
+        let report = risk_report(&path, Some(28.0), None, None).expect("Failed to build report");
+        assert_eq!(report.likely_crs, CrsGuess::Geographic);
+        assert!(report.warnings.iter().any(|w| w.contains("degrees")));
+
+        let report = risk_report("tests/VEGETATION_ARBRES.csv", Some(28.0), None, None)
+            .expect("Failed to build report");
+        assert_eq!(report.likely_crs, CrsGuess::Projected);
+        assert!(report.invalid_coordinates.is_empty());
+    }
+
+    struct FailingWriter {
+        writes_left: usize,
+    }
+
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.writes_left == 0 {
+                return Err(std::io::Error::other("disk full"));
+            }
+            self.writes_left -= 1;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_export_reports_write_failure_position() {
+        let polygons = parse_csv_file(
+            &write_temp_file("failing.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let params = VegetationParams {
+            density: 10.0,
+            ..Default::default()
+        };
+        let state = VegetationProcessingState::new();
+        // En-tête et fin de ligne, puis cinq lignes de points avant l'échec
+        let mut writer = FailingWriter { writes_left: 7 };
+
+        let error = export_to_writer(
+            &polygons,
+            &params,
+            &[],
+            &ExportOptions::default(),
+            &mut writer,
+            &state,
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect_err("Export should fail");
+        assert!(error.contains("point 6 of polygon 1"), "{}", error);
+        assert_eq!(state.errors.lock().unwrap().len(), 1);
+
+        let output =
+            std::env::temp_dir().join(format!("vegepoly-{}-partial.txt", std::process::id()));
+        std::fs::write(&output, "X\tY\n1\t2\n").expect("Failed to write file");
+        let partial = keep_partial_export(&output).expect("Partial file not kept");
+        assert!(!output.exists());
+        assert_eq!(std::fs::read_to_string(&partial).unwrap(), "X\tY\n1\t2\n");
+        std::fs::remove_file(partial).expect("Failed to clean partial export");
+    }
+
+    #[test]
+    fn test_no_points_inside_interior_ring() {
+        let polygons = parse_csv_file(
+            &write_temp_file("donut.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let in_hole =
+            |(x, y): &(f64, f64)| (50.0..=150.0).contains(x) && (50.0..=150.0).contains(y);
+
+        for strategy in [
+            SamplingStrategy::PoissonDisk,
+            SamplingStrategy::TileableBlueNoise { tile_size: 40.0 },
+        ] {
+            let params = VegetationParams {
+                density: 10.0,
+                variation: 8.0,
+                strategy,
+                ..Default::default()
+            };
+            let points = parse_points(
+                &fill_polygon(polygons[0].clone(), params).expect("Failed to fill polygon"),
+            );
+            assert!(!points.is_empty());
+            assert_eq!(points.iter().filter(|p| in_hole(p)).count(), 0);
+        }
+    }
+
+    #[test]
+    fn test_dense_distribution_respects_min_distance() {
+        let polygons = parse_csv_file(
+            &write_temp_file("dense.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let params = VegetationParams {
+            density: 5.0,
+            ..Default::default()
+        };
+        let points = parse_points(
+            &fill_polygon(polygons[0].clone(), params.clone()).expect("Failed to fill polygon"),
+        );
+
+        let closest = nearest_neighbor_distances(&points)
+            .into_iter()
+            .fold(f64::INFINITY, f64::min);
+        assert!(
+            closest >= params.density - 1e-9,
+            "closest pair was {}",
+            closest
+        );
+    }
+
+    #[test]
+    fn test_seeded_sampler_is_reproducible() {
+        let polygons = parse_csv_file(
+            &write_temp_file("seeded.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let bounds = calculate_polygon_bounds(&polygons[0]).expect("Polygon has no bounds");
+
+        let first = SpatialDistributionSampler::with_seed(10.0, bounds, 42)
+            .generate_distribution(&polygons[0]);
+        let second = SpatialDistributionSampler::with_seed(10.0, bounds, 42)
+            .generate_distribution(&polygons[0]);
+        let other = SpatialDistributionSampler::with_seed(10.0, bounds, 43)
+            .generate_distribution(&polygons[0]);
+
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn test_comma_decimal_separator() {
+        let options = ExportOptions {
+            coordinate_precision: Some(2),
+            decimal_separator: ',',
+            ..Default::default()
+        };
+        let row = format_point_row(
+            &geo::Point::new(652314.25, 6862471.5),
+            &VegetationParams::default(),
+            &options,
+        );
+        let fields: Vec<&str> = row.split('\t').collect();
+
+        assert_eq!(fields[0].trim(), "652314,25");
+        assert_eq!(fields[1].trim(), "6862471,50");
+        assert_eq!(fields.len(), get_output_columns().len());
+        assert!(options.separator_warning().is_none());
+        assert!(
+            ExportOptions {
+                decimal_separator: '\t',
+                ..Default::default()
+            }
+            .separator_warning()
+            .is_some()
+        );
+    }
+
+    #[test]
+    fn test_suggest_point_avoids_existing_points() {
+        let polygons = parse_csv_file(
+            &write_temp_file("suggest.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let avoid = SimplePoint { x: 100.0, y: 100.0 };
+
+        let point = suggest_point(polygons[0].clone(), vec![avoid.clone()], 80.0)
+            .expect("No point suggested");
+
+        assert!(point_in_polygon(
+            &polygons[0],
+            &geo::Point::new(point.x, point.y),
+            false
+        ));
+        assert!(((point.x - avoid.x).powi(2) + (point.y - avoid.y).powi(2)).sqrt() >= 80.0);
+        assert!(suggest_point(polygons[0].clone(), vec![avoid], 500.0).is_none());
+    }
+
+    #[test]
+    fn test_estimate_matches_generated_count() {
+        let path = write_temp_file(
+            "estimate.csv",
+            &format!("Geometry\n{SQUARE_WKT}\n{SQUARE_WKT}\n"),
+        );
+        let polygons = parse_csv_file(&path, None, None).expect("Failed to parse CSV file");
+        let bounds = calculate_polygon_bounds(&polygons[0]).expect("Polygon has no bounds");
+        let sampler = SpatialDistributionSampler::with_seed(5.0, bounds, 7);
+        let estimate = sampler.estimate_count(&polygons[0]);
+
+        let generated = SpatialDistributionSampler::with_seed(5.0, bounds, 7)
+            .generate_distribution(&polygons[0])
+            .len();
+        let ratio = generated as f64 / estimate as f64;
+        assert!((0.7..=1.3).contains(&ratio), "ratio was {}", ratio);
+
+        let params = VegetationParams {
+            density: 5.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            estimate_total_points(&path, params.clone(), None, None).expect("Failed to estimate"),
+            2 * estimate
+        );
+
+        // Les paramètres sont validés comme pour un remplissage
+        for invalid in [
+            VegetationParams {
+                density: f64::NAN,
+                ..params.clone()
+            },
+            VegetationParams {
+                variation: f64::NAN,
+                ..params.clone()
+            },
+            VegetationParams {
+                sampling_epsg: Some(2154),
+                ..params.clone()
+            },
+            VegetationParams {
+                parallel_tiles: Some((2, 2)),
+                density_mode: DensityMode::TargetCount,
+                ..params
+            },
+        ] {
+            assert!(estimate_total_points(&path, invalid, None, None).is_err());
+        }
+    }
+
+    #[test]
+    fn test_points_per_hectare_density() {
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "hectare.csv",
+                "Geometry\nPOLYGON((0 0,100 0,100 100,0 100,0 0))\n",
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let params = VegetationParams {
+            density: 100.0,
+            density_mode: DensityMode::PointsPerHectare,
+            seed: Some(11),
+            ..Default::default()
+        };
+
+        let count = fill_polygon(polygons[0].clone(), params)
+            .expect("Failed to fill polygon")
+            .len();
+        assert!((70..=100).contains(&count), "generated {} points", count);
+    }
+
+    #[test]
+    fn test_adaptive_density_coarsens_remaining_polygons() {
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "adaptive.csv",
+                &format!("Geometry\n{}", format!("{SQUARE_WKT}\n").repeat(6)),
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let params = VegetationParams {
+            density: 2.0,
+            seed: Some(3),
+            ..Default::default()
+        };
+        let reporter = CallbackReporter(|_: &VegetationProgressInfo| {});
+
+        let state = VegetationProcessingState::new();
+        let full_count = export_to_writer(
+            &polygons,
+            &params,
+            &[],
+            &ExportOptions::default(),
+            &mut Vec::<u8>::new(),
+            &state,
+            &reporter,
+        )
+        .expect("Export failed");
+
+        // Une durée visée inatteignable réduit la densité après chaque polygone,
+        // quelle que soit la charge de la machine
+        let options = ExportOptions {
+            adaptive_density: true,
+            target_duration_seconds: Some(1e-9),
+            ..Default::default()
+        };
+        let adaptive_count = export_to_writer(
+            &polygons,
+            &params,
+            &[],
+            &options,
+            &mut Vec::<u8>::new(),
+            &state,
+            &reporter,
+        )
+        .expect("Export failed");
+
+        let adjustments = state.density_adjustments.lock().unwrap().clone();
+        assert_eq!(adjustments.len(), polygons.len() - 1);
+        assert_eq!(adjustments[0].from_polygon, 2);
+        assert!(
+            adjustments
+                .windows(2)
+                .all(|w| w[1].factor > w[0].factor && w[1].from_polygon > w[0].from_polygon)
+        );
+        assert!(adjustments[0].factor > 1.0);
+        assert!(adaptive_count < full_count);
+    }
+
+    #[test]
+    fn test_density_step() {
+        // Dans le budget, ou sans polygone restant : pas de réduction
+        assert_eq!(density_step(10.0, 2.0, 1.0, 4), None);
+        assert_eq!(density_step(10.0, 2.0, 2.0, 4), None);
+        assert_eq!(density_step(10.0, 20.0, 5.0, 0), None);
+        // Quatre fois le budget de 2 s : distances multipliées par la racine, 2
+        assert_eq!(density_step(10.0, 2.0, 8.0, 4), Some(2.0));
+        // Réduction bornée, y compris une fois la durée visée dépassée
+        assert_eq!(density_step(10.0, 2.0, 200.0, 4), Some(MAX_DENSITY_STEP));
+        assert_eq!(density_step(10.0, 12.0, 0.5, 4), Some(MAX_DENSITY_STEP));
+    }
+
+    #[test]
+    fn test_configurable_geometry_column() {
+        let path = write_temp_file(
+            "geometry_column.csv",
+            &format!("id\tnom\tWKT\n1\tparcelle\t{SQUARE_WKT}\n"),
+        );
+
+        let by_name = parse_csv_file(&path, Some(GeometryColumn::Name("wkt".to_string())), None)
+            .expect("Failed to parse CSV file");
+        let by_index = parse_csv_file(&path, Some(GeometryColumn::Index(2)), None)
+            .expect("Failed to parse CSV file");
+        assert_eq!(by_name.len(), 1);
+        assert_eq!(by_name, by_index);
+
+        let error = parse_csv_file(&path, Some(GeometryColumn::Name("geom".to_string())), None)
+            .expect_err("Unknown column should fail");
+        assert!(error.contains("'geom'"), "{}", error);
+        assert!(parse_csv_file(&path, Some(GeometryColumn::Index(3)), None).is_err());
+        assert!(parse_csv_file(&path, None, None).is_err());
+    }
+
+    #[test]
+    fn test_target_count_density() {
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "target_count.csv",
+                &format!("Geometry\n{SQUARE_WKT}\n{DONUT_WKT}\n"),
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+
+        for polygon in polygons {
+            let params = VegetationParams {
+                density: 300.0,
+                density_mode: DensityMode::TargetCount,
+                seed: Some(5),
+                ..Default::default()
+            };
+            let points =
+                parse_points(&fill_polygon(polygon, params).expect("Failed to fill polygon"));
+            assert_eq!(points.len(), 300);
+        }
+    }
+
+    #[test]
+    fn test_source_lines_survive_skipped_rows() {
+        let path = write_temp_file(
+            "source_lines.csv",
+            &format!(
+                "Geometry\tnom\n{SQUARE_WKT}\ta\n\tvide\n\nPOLYGON((0 0,1 0,2 0,0 0))\tb\n{DONUT_WKT}\tc\n"
+            ),
+        );
+
+        let lines: Vec<usize> = parse_csv_file_with_lines(&path, None, None)
+            .expect("Failed to parse CSV file")
+            .into_iter()
+            .map(|(line, _)| line)
+            .collect();
+        assert_eq!(lines, vec![2, 5, 6]);
+
+        let (polygons, overrides): (Vec<_>, Vec<_>) =
+            parse_csv_file_with_overrides(&path, None, None)
+                .expect("Failed to parse CSV file")
+                .into_iter()
+                .unzip();
+        let state = VegetationProcessingState::new();
+        export_to_writer(
+            &polygons,
+            &VegetationParams::default(),
+            &overrides,
+            &ExportOptions::default(),
+            &mut Vec::<u8>::new(),
+            &state,
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+
+        let errors = state.errors.lock().unwrap().clone();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("polygon 2 (line 5)"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn test_uniform_random_strategy() {
+        let polygons = parse_csv_file(
+            &write_temp_file("uniform.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let bounds = calculate_polygon_bounds(&polygons[0]).expect("Polygon has no bounds");
+        let mut sampler = SpatialDistributionSampler::with_seed(5.0, bounds, 9)
+            .with_strategy(SamplingStrategy::UniformRandom);
+        let expected = sampler.estimate_count(&polygons[0]);
+
+        let points = sampler.generate_distribution(&polygons[0]);
+        assert!(
+            points
+                .iter()
+                .all(|point| point_in_polygon(&polygons[0], point, false))
+        );
+        assert_eq!(points.len(), expected);
+    }
+
+    #[test]
+    fn test_csv_delimiters() {
+        let inputs = [
+            (
+                "tab.csv",
+                format!("Geometry\tnom\n{SQUARE_WKT}\ta\n{DONUT_WKT}\tb\n"),
+                "\t",
+            ),
+            (
+                "comma.csv",
+                format!("Geometry,nom\n\"{SQUARE_WKT}\",a\n\"{DONUT_WKT}\",b\n"),
+                ",",
+            ),
+            (
+                "semicolon.csv",
+                format!("Geometry;nom\n{SQUARE_WKT};a\n{DONUT_WKT};b\n"),
+                ";",
+            ),
+        ];
+        let expected = parse_csv_file(
+            &write_temp_file(
+                "expected.csv",
+                &format!("Geometry\n{SQUARE_WKT}\n{DONUT_WKT}\n"),
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+
+        for (name, contents, delimiter) in inputs {
+            let path = write_temp_file(name, &contents);
+            let explicit = parse_csv_file(&path, None, Some(delimiter.to_string()))
+                .expect("Failed to parse CSV file");
+            let sniffed = parse_csv_file(&path, None, Some("auto".to_string()))
+                .expect("Failed to parse CSV file");
+            assert_eq!(explicit, expected, "{}", name);
+            assert_eq!(sniffed, expected, "{}", name);
+        }
+        assert!(
+            parse_csv_file("tests/VEGETATION_ARBRES.csv", None, Some("::".to_string())).is_err()
+        );
+    }
+
+    #[test]
+    fn test_export_summary_json() {
+        let directory =
+            std::env::temp_dir().join(format!("vegepoly-{}-summary", std::process::id()));
+        std::fs::create_dir_all(&directory).expect("Failed to create export directory");
+        let polygons =
+            parse_csv_file("tests/VEGETATION_ARBRES.csv", None, None).expect("Failed to parse CSV");
+        let params = VegetationParams {
+            density: 28.0,
+            ..Default::default()
+        };
+        let options = ExportOptions {
+            write_summary_json: true,
+            ..Default::default()
+        };
+
+        let filename = export_to_directory(
+            &polygons[..2],
+            &params,
+            &[],
+            &options,
+            Some("tests/VEGETATION_ARBRES.csv"),
+            &directory,
+            chrono::Local::now(),
+            &VegetationProcessingState::new(),
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+
+        let rows = std::fs::read_to_string(directory.join(&filename))
+            .expect("Failed to read export")
+            .lines()
+            .skip(1)
+            .count();
+        let summary: ExportSummary = serde_json::from_str(
+            &std::fs::read_to_string(directory.join(ExportSummary::filename(&filename)))
+                .expect("Failed to read summary"),
+        )
+        .expect("Summary is not valid JSON");
+
+        assert_eq!(summary.total_points, rows);
+        assert_eq!(summary.points_per_type.get(&params.type_value), Some(&rows));
+        assert_eq!(summary.output_file, filename);
+        assert_eq!(
+            summary.input_file.as_deref(),
+            Some("tests/VEGETATION_ARBRES.csv")
+        );
+        assert!(summary.errors.is_empty());
+        std::fs::remove_dir_all(&directory).expect("Failed to clean export directory");
+    }
+
+    #[test]
+    fn test_jittered_grid_strategy() {
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "jittered.csv",
+                &format!("Geometry\n{SQUARE_WKT}\n{DONUT_WKT}\n"),
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let params = VegetationParams {
+            density: 5.0,
+            variation: 1.0,
+            strategy: SamplingStrategy::JitteredGrid,
+            ..Default::default()
+        };
+        let (min_x, min_y, max_x, max_y) =
+            calculate_polygon_bounds(&polygons[0]).expect("Polygon has no bounds");
+        let lattice_count = (max_x - min_x) * (max_y - min_y) / (params.density * params.density);
+
+        let square = parse_points(
+            &fill_polygon(polygons[0].clone(), params.clone()).expect("Failed to fill polygon"),
+        );
+        let ratio = square.len() as f64 / lattice_count;
+        assert!((0.95..=1.05).contains(&ratio), "ratio was {}", ratio);
+
+        let donut = parse_points(
+            &fill_polygon(polygons[1].clone(), params).expect("Failed to fill polygon"),
+        );
+        assert!(!donut.is_empty());
+        assert!(
+            donut
+                .iter()
+                .all(|&(x, y)| !(50.0..=150.0).contains(&x) || !(50.0..=150.0).contains(&y))
+        );
+        assert!(donut.len() < square.len());
+    }
+
+    #[test]
+    fn test_lambert93_reprojection() {
+        let origin = reproject_point(geo::Point::new(700000.0, 6600000.0), 2154, 4326)
+            .expect("Failed to reproject");
+        assert!((origin.x() - 3.0).abs() < 1e-9 && (origin.y() - 46.5).abs() < 1e-9);
+
+        let polygons =
+            parse_csv_file("tests/VEGETATION_ARBRES.csv", None, None).expect("Failed to parse CSV");
+        let wgs84 = reproject_polygon(&polygons[0], 2154, 4326).expect("Failed to reproject");
+        let back = reproject_polygon(&wgs84, 4326, 2154).expect("Failed to reproject");
+        for (original, round_trip) in polygons[0]
+            .exterior()
+            .coords()
+            .zip(back.exterior().coords())
+        {
+            assert!((original.x - round_trip.x).abs() < 1e-6);
+            assert!((original.y - round_trip.y).abs() < 1e-6);
+        }
+        assert!(reproject_polygon(&polygons[0], 2154, 3857).is_err());
+    }
+
+    #[test]
+    fn test_geojson_feature_collection() {
+        let path = write_temp_file(
+            "parcelles.geojson",
+            r#"{
+                "type": "FeatureCollection",
+                "features": [
+                    {
+                        "type": "Feature",
+                        "properties": { "nom": "carré" },
+                        "geometry": {
+                            "type": "Polygon",
+                            "coordinates": [[[0, 0], [200, 0], [200, 200], [0, 200], [0, 0]]]
+                        }
+                    },
+                    {
+                        "type": "Feature",
+                        "properties": { "nom": "anneau" },
+                        "geometry": {
+                            "type": "MultiPolygon",
+                            "coordinates": [[
+                                [[0, 0], [200, 0], [200, 200], [0, 200], [0, 0]],
+                                [[50, 50], [150, 50], [150, 150], [50, 150], [50, 50]]
+                            ]]
+                        }
+                    }
+                ]
+            }"#,
+        );
+        let expected = parse_csv_file(
+            &write_temp_file(
+                "parcelles.csv",
+                &format!("Geometry\n{SQUARE_WKT}\n{DONUT_WKT}\n"),
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+
+        let polygons = parse_input_file(&path, None, None).expect("Failed to parse GeoJSON file");
+        assert_eq!(polygons, expected);
+        assert!(fill_polygon(polygons[1].clone(), VegetationParams::default()).is_ok());
+    }
+
+    #[test]
+    fn test_multiline_quoted_geometry() {
+        let path = write_temp_file(
+            "multiline.csv",
+            &format!(
+                "Geometry\tnom\n\"POLYGON((0 0,200 0,\n200 200,0 200,\n0 0))\"\ta\n{DONUT_WKT}\tb\n"
+            ),
+        );
+        let expected = parse_csv_file(
+            &write_temp_file("single_line.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+
+        let rows = parse_csv_file_with_lines(&path, None, None).expect("Failed to parse CSV file");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0], (2, expected[0].clone()));
+        assert_eq!(rows[1].0, 5);
+    }
+
+    #[test]
+    fn test_lloyd_relaxation() {
+        let polygons = parse_csv_file(
+            &write_temp_file("relax.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let bounds = calculate_polygon_bounds(&polygons[0]).expect("Polygon has no bounds");
+        let mut sampler = SpatialDistributionSampler::with_seed(10.0, bounds, 21)
+            .with_strategy(SamplingStrategy::UniformRandom);
+        let as_tuples = |points: &[geo::Point<f64>]| -> Vec<(f64, f64)> {
+            points.iter().map(|p| (p.x(), p.y())).collect()
+        };
+
+        let points = sampler.generate_distribution(&polygons[0]);
+        let relaxed = sampler.relax(10, &polygons[0]);
+
+        assert_eq!(relaxed.len(), points.len());
+        for point in &relaxed {
+            assert!(
+                point_in_polygon(&polygons[0], point, false),
+                "Relaxed point {:?} left the polygon or fell in the hole",
+                point
+            );
+        }
+        let closest = |points: &[(f64, f64)]| {
+            nearest_neighbor_distances(points)
+                .into_iter()
+                .fold(f64::INFINITY, f64::min)
+        };
+        assert!(closest(&as_tuples(&relaxed)) > closest(&as_tuples(&points)));
+
+        let param = VegetationParams {
+            density: 10.0,
+            relax_iterations: 3,
+            seed: Some(21),
+            ..Default::default()
+        };
+        let rows = fill_polygon(polygons[0].clone(), param).expect("Failed to fill polygon");
+        for (x, y) in parse_points(&rows) {
+            let point = geo::Point::new(x, y);
+            assert!(point_in_polygon(&polygons[0], &point, false));
+        }
+    }
+
+    #[test]
+    fn test_resolve_effective_min_distance() {
+        let polygons = parse_csv_file(
+            &write_temp_file("effective.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+
+        // 4 ha à 100 points par hectare : 400 points, soit sqrt(0.7 * 40000 / 400)
+        let param = VegetationParams {
+            density: 100.0,
+            density_mode: DensityMode::PointsPerHectare,
+            ..Default::default()
+        };
+        let resolved = resolve_effective_min_distance(polygons[0].clone(), param.clone())
+            .expect("Failed to resolve min distance");
+        assert!((resolved - 70.0_f64.sqrt()).abs() < 1e-9);
+
+        let anisotropic = VegetationParams {
+            min_distance_y: Some(3.0),
+            ..param
+        };
+        let resolved = resolve_effective_min_distance(polygons[0].clone(), anisotropic.clone())
+            .expect("Failed to resolve min distance");
+        assert_eq!(resolved, 3.0);
+
+        let invalid = VegetationParams {
+            density: f64::NAN,
+            ..anisotropic.clone()
+        };
+        assert!(resolve_effective_min_distance(polygons[0].clone(), invalid).is_err());
+        let invalid = VegetationParams {
+            strategy: SamplingStrategy::BestCandidate { candidates: 0 },
+            ..anisotropic
+        };
+        assert!(resolve_effective_min_distance(polygons[0].clone(), invalid).is_err());
+    }
+
+    #[test]
+    fn test_geojson_export() {
+        let directory =
+            std::env::temp_dir().join(format!("vegepoly-{}-geojson", std::process::id()));
+        std::fs::create_dir_all(&directory).expect("Failed to create export directory");
+        let polygons = parse_csv_file(
+            &write_temp_file("geojson_export.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let params = VegetationParams {
+            density: 20.0,
+            type_value: 7,
+            ..Default::default()
+        };
+        let options = ExportOptions {
+            format: OutputFormat::GeoJson,
+            coordinate_precision: Some(2),
+            decimal_separator: ',',
+            ..Default::default()
+        };
+
+        let filename = export_to_directory(
+            &polygons,
+            &params,
+            &[],
+            &options,
+            None,
+            &directory,
+            chrono::Local::now(),
+            &VegetationProcessingState::new(),
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+        assert!(filename.ends_with(".geojson"));
+
+        let collection: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(directory.join(&filename)).expect("Failed to read export"),
+        )
+        .expect("Export is not valid JSON");
+        assert_eq!(collection["type"], "FeatureCollection");
+        let features = collection["features"]
+            .as_array()
+            .expect("FeatureCollection has no features");
+        assert!(!features.is_empty());
+        for feature in features {
+            assert_eq!(feature["type"], "Feature");
+            assert_eq!(feature["geometry"]["type"], "Point");
+            assert_eq!(feature["properties"]["type"], 7);
+            assert_eq!(feature["properties"]["z"], 0);
+            let coordinates = feature["geometry"]["coordinates"]
+                .as_array()
+                .expect("Point has no coordinates");
+            for coordinate in coordinates {
+                let value = coordinate.as_f64().expect("Coordinate is not a number");
+                assert!((0.0..=200.0).contains(&value));
+                assert_eq!((value * 100.0).round() / 100.0, value);
+            }
+        }
+        std::fs::remove_dir_all(&directory).expect("Failed to clean export directory");
+    }
+
+    #[test]
+    fn test_wkt_multipoint_export() {
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "multipoint.csv",
+                &format!("Geometry\n{SQUARE_WKT}\n{DONUT_WKT}\n"),
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let params = VegetationParams {
+            density: 15.0,
+            type_value: 3,
+            seed: Some(5),
+            ..Default::default()
+        };
+        let options = ExportOptions {
+            format: OutputFormat::WktMultipoint,
+            ..Default::default()
+        };
+        let mut output = Vec::<u8>::new();
+
+        let total_points = export_to_writer(
+            &polygons,
+            &params,
+            &[],
+            &options,
+            &mut output,
+            &VegetationProcessingState::new(),
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+
+        let output = String::from_utf8(output).expect("Output is not UTF-8");
+        let lines: Vec<&str> = output.lines().skip(1).collect();
+        assert_eq!(lines.len(), polygons.len());
+
+        let mut written_points = 0;
+        for (line, polygon) in lines.iter().zip(&polygons) {
+            let (geometry, type_value) = line.split_once('\t').expect("Missing type column");
+            assert_eq!(type_value, "3");
+            assert!(geometry.starts_with("MULTIPOINT("));
+            let count = geometry.matches('(').count() - 1;
+            let expected = fill_polygon(polygon.clone(), params.clone())
+                .expect("Failed to fill polygon")
+                .len();
+            assert_eq!(count, expected);
+            written_points += count;
+        }
+        assert_eq!(written_points, total_points);
+    }
+
+    #[test]
+    fn test_edge_margin() {
+        let polygons = parse_csv_file(
+            &write_temp_file("edge_margin.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let bounds = calculate_polygon_bounds(&polygons[0]).expect("Polygon has no bounds");
+
+        let without_margin = SpatialDistributionSampler::with_seed(8.0, bounds, 4)
+            .generate_distribution(&polygons[0]);
+        let zero_margin = SpatialDistributionSampler::with_seed(8.0, bounds, 4)
+            .with_edge_margin(0.0)
+            .generate_distribution(&polygons[0]);
+        assert_eq!(without_margin, zero_margin);
+
+        let param = VegetationParams {
+            density: 8.0,
+            variation: 3.0,
+            edge_margin: 10.0,
+            seed: Some(4),
+            ..Default::default()
+        };
+        let rows = fill_polygon(polygons[0].clone(), param).expect("Failed to fill polygon");
+        assert!(!rows.is_empty());
+        for (x, y) in parse_points(&rows) {
+            let distance = distance_to_boundary(&polygons[0], &geo::Point::new(x, y));
+            assert!(
+                distance >= 10.0,
+                "Point ({}, {}) is {} from the boundary",
+                x,
+                y,
+                distance
+            );
+        }
+    }
+
+    #[test]
+    fn test_lenient_csv_parsing() {
+        let path = write_temp_file(
+            "lenient.csv",
+            &format!("Geometry\n{SQUARE_WKT}\nPOLYGON((0 0,1 0\nPOINT(1 1)\n{DONUT_WKT}\n"),
+        );
+
+        assert!(parse_csv_file(&path, None, None).is_err());
+
+        let parsed = parse_csv_file_lenient(&path, None, None).expect("Failed to read CSV file");
+        assert_eq!(parsed.polygons.len(), 2);
+        assert_eq!(parsed.polygons[1].interiors().len(), 1);
+        let lines: Vec<usize> = parsed.skipped_rows.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![3, 4]);
+        assert!(parsed.skipped_rows[0].1.contains("Invalid WKT"));
+        assert!(parsed.skipped_rows[1].1.contains("not a Polygon"));
+    }
+
+    #[test]
+    fn test_degenerate_polygons() {
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "degenerate.csv",
+                "Geometry\nPOLYGON((0 0,10 0,20 0,0 0))\nPOLYGON((5 5,5 5,5 5,5 5))\n",
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+
+        let err = fill_polygon(polygons[0].clone(), VegetationParams::default())
+            .expect_err("A zero-area polygon should not be filled");
+        assert!(err.contains("zero area"), "Unexpected error: {}", err);
+
+        let rows = fill_polygon(polygons[1].clone(), VegetationParams::default())
+            .expect("Failed to fill point polygon");
+        assert_eq!(parse_points(&rows), vec![(5.0, 5.0)]);
+    }
+
+    #[test]
+    fn test_grid_size_guard() {
+        let polygons = parse_csv_file(
+            &write_temp_file("grid_guard.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let fill = |density: f64, max_grid_cells: Option<usize>| {
+            fill_polygon(
+                polygons[0].clone(),
+                VegetationParams {
+                    density,
+                    max_grid_cells,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let err = fill(0.0, None).expect_err("A zero density should be rejected");
+        assert!(
+            err.contains("Density must be positive"),
+            "Unexpected error: {}",
+            err
+        );
+
+        let err = fill(1e-6, None).expect_err("A tiny density should be rejected");
+        assert!(err.contains("too small"), "Unexpected error: {}", err);
+
+        let err = fill(5.0, Some(100)).expect_err("The grid cap should be configurable");
+        assert!(err.contains("maximum 100"), "Unexpected error: {}", err);
+        assert!(fill(5.0, None).is_ok());
+
+        let bounds = calculate_polygon_bounds(&polygons[0]).expect("Polygon has no bounds");
+        assert!(SpatialDistributionSampler::try_new_anisotropic(0.0, 5.0, bounds, 1000).is_err());
+    }
+
+    #[test]
+    fn test_distribution_keeps_pairwise_spacing() {
+        let polygons = parse_csv_file(
+            &write_temp_file("pairwise.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let bounds = calculate_polygon_bounds(&polygons[0]).expect("Polygon has no bounds");
+        let min_distance = 2.0;
+
+        for seed in 0..3 {
+            let points: Vec<(f64, f64)> =
+                SpatialDistributionSampler::with_seed(min_distance, bounds, seed)
+                    .generate_distribution(&polygons[0])
+                    .iter()
+                    .map(|point| (point.x(), point.y()))
+                    .collect();
+            assert!(points.len() > 1000);
+            for (i, a) in points.iter().enumerate() {
+                for b in &points[i + 1..] {
+                    let distance = ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+                    assert!(
+                        distance >= min_distance,
+                        "Points {:?} and {:?} are only {} apart",
+                        a,
+                        b,
+                        distance
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_export_progress_stream() {
+        use tokio_stream::StreamExt;
+
+        let directory =
+            std::env::temp_dir().join(format!("vegepoly-{}-stream", std::process::id()));
+        std::fs::create_dir_all(&directory).expect("Failed to create export directory");
+        let polygons =
+            parse_csv_file("tests/VEGETATION_ARBRES.csv", None, None).expect("Failed to parse CSV");
+
+        let mut stream = export_progress_stream(
+            polygons[..3].to_vec(),
+            VegetationParams {
+                density: 28.0,
+                ..Default::default()
+            },
+            Vec::new(),
+            ExportOptions::default(),
+            directory.clone(),
+        );
+
+        tauri::async_runtime::block_on(async {
+            let mut updates = Vec::new();
+            while let Some(progress) = stream.progress.next().await {
+                updates.push(progress);
+            }
+            let last = updates.last().expect("No progress was reported");
+            assert!(last.is_finished);
+            assert_eq!(last.current_row, 3);
+            assert!(!updates[0].is_finished);
+
+            let filename = stream
+                .result
+                .await
+                .expect("Export thread dropped its result")
+                .expect("Export failed");
+            assert!(directory.join(filename).exists());
+        });
+        std::fs::remove_dir_all(&directory).expect("Failed to clean export directory");
+    }
+
+    #[test]
+    fn test_grid_keeps_every_point_of_a_cell() {
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "shared_cell.csv",
+                "Geometry\nPOLYGON((0 0,10 0,10 10,0 10,0 0))\n",
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        // Avec une distance de 4, les cellules mesurent 2.83 : ces deux points du coin
+        // supérieur de l'emprise tombent dans la même cellule de bord
+        let avoid = vec![
+            SimplePoint { x: 8.5, y: 9.9 },
+            SimplePoint { x: 9.9, y: 8.5 },
+        ];
+
+        for _ in 0..200 {
+            let Some(point) = suggest_point(polygons[0].clone(), avoid.clone(), 4.0) else {
+                continue;
+            };
+            for other in &avoid {
+                let distance = ((point.x - other.x).powi(2) + (point.y - other.y).powi(2)).sqrt();
+                assert!(
+                    distance >= 4.0,
+                    "Suggested ({}, {}) is {} from ({}, {})",
+                    point.x,
+                    point.y,
+                    distance,
+                    other.x,
+                    other.y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_collapsed_bounds_generate_nothing() {
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "collapsed.csv",
+                "Geometry\nPOLYGON((3 3,3 3,3 3,3 3))\nPOLYGON((0 2,10 2,5 2,0 2))\n",
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+
+        for polygon in &polygons {
+            let bounds = calculate_polygon_bounds(polygon).expect("Polygon has no bounds");
+            for strategy in [
+                SamplingStrategy::PoissonDisk,
+                SamplingStrategy::JitteredGrid,
+            ] {
+                let points = SpatialDistributionSampler::with_seed(1.0, bounds, 2)
+                    .with_strategy(strategy)
+                    .generate_distribution(polygon);
+                assert!(points.is_empty());
+            }
+        }
+
+        let inverted = SpatialDistributionSampler::with_seed(1.0, (10.0, 10.0, 0.0, 0.0), 2)
+            .generate_distribution(&polygons[0]);
+        assert!(inverted.is_empty());
+    }
+
+    #[test]
+    fn test_progress_rows_match_parsed_polygons() {
+        let path = write_temp_file(
+            "row_count.csv",
+            &format!("Geometry\tnom\n{SQUARE_WKT}\ta\n\tvide\n{DONUT_WKT}\tb\n"),
+        );
+        let polygons = parse_csv_file(&path, None, None).expect("Failed to parse CSV file");
+        assert_eq!(polygons.len(), 2);
+
+        let total_rows = std::sync::Mutex::new(Vec::new());
+        export_to_writer(
+            &polygons,
+            &VegetationParams {
+                density: 20.0,
+                ..Default::default()
+            },
+            &[],
+            &ExportOptions::default(),
+            &mut Vec::<u8>::new(),
+            &VegetationProcessingState::new(),
+            &CallbackReporter(|progress: &VegetationProgressInfo| {
+                total_rows.lock().unwrap().push(progress.total_rows)
+            }),
+        )
+        .expect("Export failed");
+
+        let total_rows = total_rows.into_inner().unwrap();
+        assert!(!total_rows.is_empty());
+        assert!(total_rows.iter().all(|&total| total == polygons.len()));
+    }
+
+    #[test]
+    fn test_seed_fallback_for_sparse_concave_polygon() {
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "thin_l.csv",
+                "Geometry\nPOLYGON((0 0,1000 0,1000 1,1 1,1 1000,0 1000,0 0))\n",
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let bounds = calculate_polygon_bounds(&polygons[0]).expect("Polygon has no bounds");
+
+        let points = SpatialDistributionSampler::with_seed(50.0, bounds, 1)
+            .with_attempts(30, 0)
+            .generate_distribution(&polygons[0]);
+        assert!(!points.is_empty());
+
+        let param = VegetationParams {
+            density: 50.0,
+            seed_attempts: Some(0),
+            max_attempts: Some(60),
+            seed: Some(1),
+            ..Default::default()
+        };
+        let rows = fill_polygon(polygons[0].clone(), param).expect("Failed to fill polygon");
+        assert!(!rows.is_empty());
+        for (x, y) in parse_points(&rows) {
+            assert!(point_in_polygon(
+                &polygons[0],
+                &geo::Point::new(x, y),
+                false
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parallel_export_matches_serial() {
+        let polygons =
+            parse_csv_file("tests/VEGETATION_ARBRES.csv", None, None).expect("Failed to parse CSV");
+        let polygons = &polygons[..polygons.len().min(12)];
+        let params = VegetationParams {
+            density: 28.0,
+            seed: Some(3),
+            ..Default::default()
+        };
+        let export = |max_threads: Option<usize>| {
+            let state = VegetationProcessingState::new();
+            let mut output = Vec::<u8>::new();
+            let total = export_to_writer(
+                polygons,
+                &params,
+                &[],
+                &ExportOptions {
+                    max_threads,
+                    ..Default::default()
+                },
+                &mut output,
+                &state,
+                &CallbackReporter(|_: &VegetationProgressInfo| {}),
+            )
+            .expect("Export failed");
+            let progress = *state.processed_rows.lock().unwrap();
+            let created = *state.created_items.lock().unwrap();
+            (output, total, progress, created)
+        };
+
+        let serial = export(Some(1));
+        let parallel = export(Some(4));
+
+        assert_eq!(serial.0, parallel.0);
+        assert_eq!(serial.1, parallel.1);
+        assert_eq!(parallel.2, polygons.len());
+        assert_eq!(parallel.3, parallel.1);
+    }
+
+    #[test]
+    fn test_export_single_wkt() {
+        let directory =
+            std::env::temp_dir().join(format!("vegepoly-{}-single-wkt", std::process::id()));
+        std::fs::create_dir_all(&directory).expect("Failed to create export directory");
+        let params = VegetationParams {
+            density: 20.0,
+            ..Default::default()
+        };
+        let export = |wkt: &str| {
+            export_wkt_to_directory(
+                wkt,
+                &params,
+                &ExportOptions::default(),
+                &directory,
+                &VegetationProcessingState::new(),
+                &CallbackReporter(|_: &VegetationProgressInfo| {}),
+            )
+        };
+
+        let filename = export(SQUARE_WKT).expect("Export failed");
+        let content =
+            std::fs::read_to_string(directory.join(filename)).expect("Failed to read export");
+        let mut lines = content.lines();
+        assert_eq!(lines.next(), Some(OUTPUT_HEADER));
+        assert!(lines.count() > 0);
+
+        let err = export("POINT(1 1)").expect_err("A point is not a polygon");
+        assert!(err.contains("not a Polygon"), "Unexpected error: {}", err);
+        std::fs::remove_dir_all(&directory).expect("Failed to clean export directory");
+    }
+
+    #[test]
+    fn test_cancel_export() {
+        let directory =
+            std::env::temp_dir().join(format!("vegepoly-{}-cancel", std::process::id()));
+        std::fs::create_dir_all(&directory).expect("Failed to create export directory");
+        let polygons =
+            parse_csv_file("tests/VEGETATION_ARBRES.csv", None, None).expect("Failed to parse CSV");
+        let params = VegetationParams {
+            density: 28.0,
+            ..Default::default()
+        };
+        let options = ExportOptions {
+            max_threads: Some(1),
+            filename_prefix: "Cancelled".to_string(),
+            ..Default::default()
+        };
+        let state = VegetationProcessingState::new();
+        let reporter = CallbackReporter(|progress: &VegetationProgressInfo| {
+            if progress.current_row == 1 {
+                state.request_cancel();
+            }
+        });
+
+        let err = export_to_directory(
+            &polygons[..3],
+            &params,
+            &[],
+            &options,
+            None,
+            &directory,
+            chrono::Local::now(),
+            &state,
+            &reporter,
+        )
+        .expect_err("The export should have been cancelled");
+
+        assert!(err.contains("cancelled"), "Unexpected error: {}", err);
+        assert_eq!(*state.processed_rows.lock().unwrap(), 1);
+        let leftovers = std::fs::read_dir(&directory)
+            .expect("Failed to list export directory")
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("Cancelled"))
+            .count();
+        assert_eq!(leftovers, 0);
+        std::fs::remove_dir_all(&directory).expect("Failed to clean export directory");
+    }
+
+    #[test]
+    fn test_sampling_stats() {
+        let polygon = parse_csv_file(
+            &write_temp_file("stats.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV")
+        .remove(0);
+        let bounds = calculate_polygon_bounds(&polygon).expect("Polygon should have bounds");
+        let (points, stats) = SpatialDistributionSampler::new(10.0, bounds)
+            .seeded(7)
+            .generate_distribution_with_stats(&polygon);
+
+        assert_eq!(stats.generated, points.len());
+        assert!(stats.total_attempts >= stats.generated);
+        assert!(stats.rejected_distance > 0);
+        let ratio = stats.rejection_ratio();
+        assert!((0.0..=1.0).contains(&ratio), "Unexpected ratio: {}", ratio);
+
+        let params = VegetationParams {
+            density: 10.0,
+            seed: Some(7),
+            ..Default::default()
+        };
+        let command_stats =
+            get_sampling_stats(polygon, params).expect("Failed to compute sampling stats");
+        assert!(command_stats.generated > 0);
+        assert!(command_stats.total_attempts >= command_stats.generated);
+    }
+
+    #[test]
+    fn test_interior_buffer() {
+        let polygons = parse_csv_file(
+            &write_temp_file("interior_buffer.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let polygon = &polygons[0];
+
+        let param = VegetationParams {
+            density: 6.0,
+            variation: 2.0,
+            interior_buffer: 15.0,
+            seed: Some(9),
+            ..Default::default()
+        };
+        let rows = fill_polygon(polygon.clone(), param).expect("Failed to fill polygon");
+        let points = parse_points(&rows);
+        assert!(!points.is_empty());
+
+        let mut closest_to_exterior = f64::INFINITY;
+        for (x, y) in points {
+            let point = geo::Point::new(x, y);
+            let distance = distance_to_holes(polygon, &point);
+            assert!(
+                distance >= 15.0,
+                "Point ({}, {}) is {} from the hole",
+                x,
+                y,
+                distance
+            );
+            closest_to_exterior = closest_to_exterior.min(distance_to_boundary(polygon, &point));
+        }
+        assert!(
+            closest_to_exterior < 15.0,
+            "The exterior buffer should not be affected by the interior buffer"
+        );
+    }
+
+    #[test]
+    fn test_clustered_distribution() {
+        let polygons = parse_csv_file(
+            &write_temp_file("clustered.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let bounds = calculate_polygon_bounds(&polygons[0]).expect("Polygon has no bounds");
+
+        let points = SpatialDistributionSampler::with_seed(2.0, bounds, 11)
+            .with_strategy(SamplingStrategy::Clustered {
+                cluster_count: 4,
+                cluster_radius: 15.0,
+                points_per_cluster: 10,
+            })
+            .generate_distribution(&polygons[0]);
+
+        assert!(!points.is_empty());
+        assert!(points.len() <= 40, "Too many points: {}", points.len());
+        for point in &points {
+            assert!(point_in_polygon(&polygons[0], point, false));
+        }
+        let distances =
+            nearest_neighbor_distances(&points.iter().map(|p| (p.x(), p.y())).collect::<Vec<_>>());
+        assert!(distances.iter().all(|&d| d >= 2.0 - 1e-9));
+
+        // Les points d'un bosquet sont bien plus proches entre eux que ne le seraient
+        // autant de points répartis sur tout le carré
+        let mean = distances.iter().sum::<f64>() / distances.len() as f64;
+        assert!(
+            mean < 10.0,
+            "Points are not clustered: mean spacing {}",
+            mean
+        );
+
+        let invalid = VegetationParams {
+            strategy: SamplingStrategy::Clustered {
+                cluster_count: 0,
+                cluster_radius: 15.0,
+                points_per_cluster: 10,
+            },
+            ..Default::default()
+        };
+        assert!(fill_polygon(polygons[0].clone(), invalid).is_err());
+    }
+
+    #[test]
+    fn test_variation_column_migration() {
+        let path = TempFile::new("migration.db");
+        let _ = std::fs::remove_file(&path);
+        {
+            let conn = rusqlite::Connection::open(&path).expect("Failed to create database");
+            for table in ["default_vegetation_params", "user_vegetation_params"] {
+                conn.execute(
+                    &format!(
+                        "CREATE TABLE {} (
+                            vegetation_type INTEGER PRIMARY KEY,
+                            density REAL NOT NULL,
+                            type_value INTEGER NOT NULL
+                        )",
+                        table
+                    ),
+                    [],
+                )
+                .expect("Failed to create old table");
+            }
+            conn.execute("INSERT INTO user_vegetation_params VALUES (2, 7.5, 20)", [])
+                .expect("Failed to insert old params");
+        }
+
+        let settings =
+            Settings::open(path.path().to_path_buf()).expect("Failed to migrate settings");
+        let migrated = settings
+            .get_user_vegetation_params(2)
+            .expect("Failed to read migrated params")
+            .expect("Migrated params missing");
+        assert_eq!(migrated.density, 7.5);
+        assert_eq!(migrated.variation, 0.0);
+
+        settings
+            .set_user_vegetation_params(
+                2,
+                VegetationParams {
+                    variation: 1.5,
+                    ..migrated
+                },
+            )
+            .expect("Failed to save params");
+        let saved = settings
+            .get_vegetation_params(2)
+            .expect("Failed to read params")
+            .expect("Params missing");
+        assert_eq!(saved.variation, 1.5);
+
+        // Une seconde ouverture ne doit pas tenter d'ajouter à nouveau la colonne
+        let reopened =
+            Settings::open(path.path().to_path_buf()).expect("Failed to reopen settings");
+        let default = reopened
+            .get_default_vegetation_params(1)
+            .expect("Failed to read default params")
+            .expect("Default params missing");
+        assert_eq!(default.variation, 0.0);
+    }
+
+    #[test]
+    fn test_export_does_not_query_settings() {
+        // Les paramètres sont transmis par l'interface : l'export ne lit pas la base,
+        // qui n'est pas initialisée ici et ferait échouer le test au premier accès
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "no_settings.csv",
+                &format!("Geometry\n{}", format!("{SQUARE_WKT}\n").repeat(50)),
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let params = VegetationParams {
+            density: 40.0,
+            vegetation_type: 2,
+            ..Default::default()
+        };
+
+        let mut output = Vec::<u8>::new();
+        export_to_writer(
+            &polygons,
+            &params,
+            &[],
+            &ExportOptions::default(),
+            &mut output,
+            &VegetationProcessingState::new(),
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+        assert!(!output.is_empty());
+    }
+
+    #[test]
+    fn test_generate_distribution_with_rng() {
+        use rand::SeedableRng;
+
+        let polygons = parse_csv_file(
+            &write_temp_file("injected_rng.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let bounds = calculate_polygon_bounds(&polygons[0]).expect("Polygon has no bounds");
+
+        let seeded = SpatialDistributionSampler::with_seed(12.0, bounds, 21)
+            .generate_distribution(&polygons[0]);
+        let mut sampler = SpatialDistributionSampler::new(12.0, bounds);
+        let injected = sampler.generate_distribution_with_rng(
+            &polygons[0],
+            &mut rand::rngs::StdRng::seed_from_u64(21),
+        );
+        assert_eq!(seeded, injected);
+        assert_eq!(sampler.stats().generated, injected.len());
+    }
+
+    #[test]
+    fn test_vegetation_type_labels() {
+        let (settings, _database) = open_temp_settings("labels");
+        assert_eq!(
+            settings.get_vegetation_type_label(1).unwrap().as_deref(),
+            Some("Arbres")
+        );
+
+        settings
+            .set_user_vegetation_params(
+                4,
+                VegetationParams {
+                    vegetation_type: 4,
+                    density: 12.0,
+                    type_value: 40,
+                    ..Default::default()
+                },
+            )
+            .expect("Failed to save custom type");
+        assert_eq!(settings.get_vegetation_type_label(4).unwrap(), None);
+        settings
+            .set_vegetation_type_label(4, "Haies")
+            .expect("Failed to set label");
+        assert_eq!(
+            settings.get_vegetation_type_label(4).unwrap().as_deref(),
+            Some("Haies")
+        );
+
+        // Enregistrer de nouveaux paramètres ne doit pas effacer le libellé
+        settings
+            .set_user_vegetation_params(
+                4,
+                VegetationParams {
+                    vegetation_type: 4,
+                    density: 15.0,
+                    type_value: 40,
+                    ..Default::default()
+                },
+            )
+            .expect("Failed to update custom type");
+
+        let types = settings
+            .list_vegetation_types()
+            .expect("Failed to list vegetation types");
+        assert_eq!(
+            types.iter().map(|t| t.vegetation_type).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        let custom = &types[3];
+        assert_eq!(custom.label.as_deref(), Some("Haies"));
+        assert_eq!(custom.density, 15.0);
+        assert_eq!(custom.type_value, 40);
+
+        assert!(settings.set_vegetation_type_label(9, "Inconnu").is_err());
+    }
+
+    #[test]
+    fn test_max_points_cap() {
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "max_points.csv",
+                "Geometry\nPOLYGON((0 0,1000 0,1000 1000,0 1000,0 0))\n",
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let bounds = calculate_polygon_bounds(&polygons[0]).expect("Polygon has no bounds");
+
+        let start = std::time::Instant::now();
+        let points = SpatialDistributionSampler::with_seed(1.0, bounds, 17)
+            .with_max_points(Some(1000))
+            .generate_distribution(&polygons[0]);
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(5),
+            "Capped generation took {:?}",
+            start.elapsed()
+        );
+        assert!(!points.is_empty());
+        assert!(points.len() <= 1000, "Too many points: {}", points.len());
+
+        // Les points couvrent les quatre quarts de la parcelle au lieu d'un seul coin
+        for (qx, qy) in [(0.0, 0.0), (500.0, 0.0), (0.0, 500.0), (500.0, 500.0)] {
+            assert!(
+                points.iter().any(|p| p.x() >= qx
+                    && p.x() < qx + 500.0
+                    && p.y() >= qy
+                    && p.y() < qy + 500.0),
+                "No point in the quarter starting at ({}, {})",
+                qx,
+                qy
+            );
+        }
+
+        let params = VegetationParams {
+            density: 1.0,
+            max_points: Some(1000),
+            seed: Some(17),
+            ..Default::default()
+        };
+        let rows = fill_polygon(polygons[0].clone(), params).expect("Failed to fill polygon");
+        assert!(rows.len() <= 1000, "Too many rows: {}", rows.len());
+
+        let params = VegetationParams {
+            max_points: Some(0),
+            ..Default::default()
+        };
+        assert!(fill_polygon(polygons[0].clone(), params).is_err());
+    }
+
+    #[test]
+    fn test_iter_points() {
+        let polygons = parse_csv_file(
+            &write_temp_file("iter_points.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let bounds = calculate_polygon_bounds(&polygons[0]).expect("Polygon has no bounds");
+
+        for strategy in [
+            SamplingStrategy::PoissonDisk,
+            SamplingStrategy::JitteredGrid,
+        ] {
+            let eager = SpatialDistributionSampler::with_seed(6.0, bounds, 13)
+                .with_strategy(strategy.clone())
+                .generate_distribution(&polygons[0]);
+            let mut sampler =
+                SpatialDistributionSampler::with_seed(6.0, bounds, 13).with_strategy(strategy);
+            let lazy: Vec<_> = sampler.iter_points(&polygons[0]).collect();
+            assert_eq!(eager, lazy);
+            assert_eq!(sampler.stats().generated, lazy.len());
+        }
+
+        // Les premiers points sont produits sans générer toute la distribution
+        let mut full = SpatialDistributionSampler::with_seed(6.0, bounds, 13);
+        let (_, full_stats) = full.generate_distribution_with_stats(&polygons[0]);
+        let mut sampler = SpatialDistributionSampler::with_seed(6.0, bounds, 13);
+        assert_eq!(sampler.iter_points(&polygons[0]).take(5).count(), 5);
+        assert!(sampler.stats().total_attempts < full_stats.total_attempts / 10);
+    }
+
+    #[test]
+    fn test_strategy_grid_guards() {
+        let polygons = parse_csv_file(
+            &write_temp_file("strategy_guard.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let fill = |strategy: SamplingStrategy| {
+            fill_polygon(
+                polygons[0].clone(),
+                VegetationParams {
+                    density: 5.0,
+                    strategy,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let err = fill(SamplingStrategy::Clustered {
+            cluster_count: 3,
+            cluster_radius: 1e-6,
+            points_per_cluster: 5,
+        })
+        .expect_err("A tiny cluster radius should be rejected");
+        assert!(err.contains("Cluster radius"), "Unexpected error: {}", err);
+
+        let err = fill(SamplingStrategy::TileableBlueNoise { tile_size: 1e6 })
+            .expect_err("A huge tile should be rejected");
+        assert!(err.contains("too large"), "Unexpected error: {}", err);
+
+        assert!(fill(SamplingStrategy::TileableBlueNoise { tile_size: 50.0 }).is_ok());
+    }
+
+    #[test]
+    fn test_parallel_tiles() {
+        let polygons = parse_csv_file(
+            &write_temp_file("parallel_tiles.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        let fill = |parallel_tiles: Option<(usize, usize)>| {
+            parse_points(
+                &fill_polygon(
+                    polygons[0].clone(),
+                    VegetationParams {
+                        density: 5.0,
+                        seed: Some(8),
+                        parallel_tiles,
+                        ..Default::default()
+                    },
+                )
+                .expect("Failed to fill polygon"),
+            )
+        };
+
+        let single = fill(None);
+        let tiled = fill(Some((3, 3)));
+        assert_eq!(tiled, fill(Some((3, 3))));
+        assert!(
+            tiled.len() * 10 >= single.len() * 9,
+            "Tiling lost too many points: {} vs {}",
+            tiled.len(),
+            single.len()
+        );
+        let closest = nearest_neighbor_distances(&tiled)
+            .into_iter()
+            .fold(f64::INFINITY, f64::min);
+        assert!(closest >= 5.0 - 1e-9, "Points {} apart", closest);
+        for (x, y) in &tiled {
+            assert!(!(*x > 50.0 && *x < 150.0 && *y > 50.0 && *y < 150.0));
+        }
+
+        let invalid = VegetationParams {
+            parallel_tiles: Some((0, 2)),
+            ..Default::default()
+        };
+        assert!(fill_polygon(polygons[0].clone(), invalid).is_err());
+    }
+
+    #[test]
+    fn test_exclusion_zones() {
+        let polygons = parse_csv_file(
+            &write_temp_file("exclusion_zones.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        // Une zone d'exclusion couvrant la moitié gauche de la parcelle
+        let zone = geo::Polygon::new(
+            geo::LineString::from(vec![
+                (-10.0, -10.0),
+                (100.0, -10.0),
+                (100.0, 210.0),
+                (-10.0, 210.0),
+            ]),
+            vec![],
+        );
+
+        let param = VegetationParams {
+            density: 6.0,
+            variation: 2.0,
+            seed: Some(4),
+            exclusion_zones: vec![zone],
+            ..Default::default()
+        };
+        let rows = fill_polygon(polygons[0].clone(), param).expect("Failed to fill polygon");
+        let points = parse_points(&rows);
+        assert!(!points.is_empty());
+        for (x, y) in points {
+            assert!(
+                x >= 100.0,
+                "Point ({}, {}) lies in the exclusion zone",
+                x,
+                y
+            );
+        }
+    }
+
+    #[test]
+    fn test_fill_polygon_with_stats() {
+        let polygon = parse_csv_file(
+            &write_temp_file("fill_stats.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV")
+        .remove(0);
+
+        for parallel_tiles in [None, Some((2, 2))] {
+            let params = VegetationParams {
+                density: 8.0,
+                seed: Some(11),
+                parallel_tiles,
+                ..Default::default()
+            };
+            let filled =
+                fill_polygon_with_stats(polygon.clone(), params).expect("Failed to fill polygon");
+            let stats = filled.stats;
+
+            assert_eq!(filled.rows.len(), stats.generated);
+            assert_eq!(
+                stats.accepted + stats.rejected_distance + stats.rejected_outside,
+                stats.total_attempts,
+                "Inconsistent counters: {:?}",
+                stats
+            );
+            assert!(stats.accepted >= stats.generated);
+            assert!(stats.seed_attempts >= 1);
+            // Chaque point actif finit par être désactivé
+            assert!(stats.exhausted_active_points >= stats.generated);
+        }
+    }
+
+    #[test]
+    fn test_strategies_respect_containment() {
+        let polygon = parse_csv_file(
+            &write_temp_file("strategies.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file")
+        .remove(0);
+        let bounds = calculate_polygon_bounds(&polygon).expect("Polygon has no bounds");
+        let expected = SpatialDistributionSampler::new(6.0, bounds).estimate_count(&polygon);
+
+        for strategy in [
+            SamplingStrategy::PoissonDisk,
+            SamplingStrategy::JitteredGrid,
+            SamplingStrategy::UniformRandom,
+        ] {
+            let params = VegetationParams {
+                density: 6.0,
+                variation: 1.0,
+                strategy: strategy.clone(),
+                seed: Some(20),
+                ..Default::default()
+            };
+            let points = parse_points(
+                &fill_polygon(polygon.clone(), params).expect("Failed to fill polygon"),
+            );
+            assert!(
+                points.iter().all(|&(x, y)| point_in_polygon(
+                    &polygon,
+                    &geo::Point::new(x, y),
+                    false
+                )),
+                "{:?} placed a point outside the polygon",
+                strategy
+            );
+            let ratio = points.len() as f64 / expected as f64;
+            assert!(
+                (0.4..=2.5).contains(&ratio),
+                "{:?} produced {} points, {} expected",
+                strategy,
+                points.len(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_density_gradient() {
+        let polygon = parse_csv_file(
+            &write_temp_file("density_gradient.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file")
+        .remove(0);
+        let params = VegetationParams {
+            density: 8.0,
+            seed: Some(6),
+            ..Default::default()
+        };
+
+        // Plus dense en bas de la parcelle, plus clairsemé en haut
+        let gradient = DensityGradient {
+            start: (0.0, 0.0),
+            end: (0.0, 200.0),
+            start_factor: 0.5,
+            end_factor: 2.0,
+        };
+        let points = parse_points(
+            &fill_polygon(
+                polygon.clone(),
+                VegetationParams {
+                    density_gradient: Some(gradient),
+                    ..params.clone()
+                },
+            )
+            .expect("Failed to fill polygon"),
+        );
+        let low = points.iter().filter(|&&(_, y)| y < 100.0).count();
+        let high = points.len() - low;
+        assert!(low > 2 * high, "{} points low, {} points high", low, high);
+
+        // Un gradient constant de facteur 1 reproduit la distribution uniforme
+        let constant = VegetationParams {
+            density_gradient: Some(DensityGradient {
+                start_factor: 1.0,
+                end_factor: 1.0,
+                ..gradient
+            }),
+            ..params.clone()
+        };
+        assert_eq!(
+            fill_polygon(polygon.clone(), constant).expect("Failed to fill polygon"),
+            fill_polygon(polygon.clone(), params.clone()).expect("Failed to fill polygon")
+        );
+
+        let invalid = VegetationParams {
+            density_gradient: Some(DensityGradient {
+                start_factor: 0.0,
+                ..gradient
+            }),
+            ..params
+        };
+        assert!(fill_polygon(polygon, invalid).is_err());
+    }
+
+    #[test]
+    fn test_edge_margin_on_square() {
+        let polygon = parse_csv_file(
+            &write_temp_file("border_margin.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file")
+        .remove(0);
+        let params = VegetationParams {
+            density: 6.0,
+            variation: 2.0,
+            edge_margin: 20.0,
+            seed: Some(13),
+            ..Default::default()
+        };
+
+        let points = parse_points(
+            &fill_polygon(polygon.clone(), params.clone()).expect("Failed to fill polygon"),
+        );
+        assert!(!points.is_empty());
+        for (x, y) in points {
+            assert!(
+                (20.0..=180.0).contains(&x) && (20.0..=180.0).contains(&y),
+                "Point ({}, {}) is within the margin",
+                x,
+                y
+            );
+        }
+
+        // Un retrait plus large que le rayon inscrit donne une distribution vide
+        let swallowed = VegetationParams {
+            edge_margin: 150.0,
+            ..params
+        };
+        assert_eq!(
+            fill_polygon(polygon, swallowed).expect("An oversized margin is not an error"),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_variation_stays_in_thin_polygon() {
+        let polygon = parse_csv_file(
+            &write_temp_file(
+                "thin_variation.csv",
+                "Geometry\nPOLYGON((0 0,200 0,200 4,0 4,0 0))\n",
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file")
+        .remove(0);
+
+        for strategy in [
+            SamplingStrategy::PoissonDisk,
+            SamplingStrategy::JitteredGrid,
+        ] {
+            // Une variation bien plus large que le polygone
+            let params = VegetationParams {
+                density: 2.0,
+                variation: 25.0,
+                strategy: strategy.clone(),
+                seed: Some(3),
+                ..Default::default()
+            };
+            let points = parse_points(
+                &fill_polygon(polygon.clone(), params).expect("Failed to fill polygon"),
+            );
+            assert!(!points.is_empty());
+            for (x, y) in points {
+                assert!(
+                    point_in_polygon(&polygon, &geo::Point::new(x, y), false),
+                    "{:?} moved ({}, {}) outside the polygon",
+                    strategy,
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_best_candidate_strategy() {
+        let polygon = parse_csv_file(
+            &write_temp_file("best_candidate.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file")
+        .remove(0);
+        let bounds = calculate_polygon_bounds(&polygon).expect("Polygon has no bounds");
+
+        let mut sampler = SpatialDistributionSampler::with_seed(8.0, bounds, 17)
+            .with_strategy(SamplingStrategy::BestCandidate { candidates: 10 });
+        let expected = sampler.estimate_count(&polygon);
+        let best = sampler.generate_distribution(&polygon);
+        assert_eq!(best.len(), expected);
+        assert!(
+            best.iter()
+                .all(|point| point_in_polygon(&polygon, point, false))
+        );
+
+        let uniform = SpatialDistributionSampler::with_seed(8.0, bounds, 17)
+            .with_strategy(SamplingStrategy::UniformRandom)
+            .generate_distribution(&polygon);
+        let closest = |points: &[geo::Point<f64>]| {
+            let coords: Vec<(f64, f64)> = points.iter().map(|p| (p.x(), p.y())).collect();
+            nearest_neighbor_distances(&coords)
+                .into_iter()
+                .fold(f64::INFINITY, f64::min)
+        };
+        // Les meilleurs candidats laissent moins de points accolés qu'un tirage uniforme
+        assert!(closest(&best) > closest(&uniform));
+
+        let invalid = VegetationParams {
+            strategy: SamplingStrategy::BestCandidate { candidates: 0 },
+            ..Default::default()
+        };
+        assert!(fill_polygon(polygon, invalid).is_err());
+    }
+
+    #[test]
+    fn test_sampler_from_config() {
+        let polygon = parse_csv_file(
+            &write_temp_file("sampler_config.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file")
+        .remove(0);
+        let bounds = calculate_polygon_bounds(&polygon).expect("Polygon has no bounds");
+
+        let config = SamplerConfig {
+            seed: Some(21),
+            edge_margin: 4.0,
+            max_points: Some(50),
+            ..SamplerConfig::new(7.0)
+        };
+        let from_config = SpatialDistributionSampler::from_config(config, bounds)
+            .expect("Invalid sampler configuration")
+            .generate_distribution(&polygon);
+        let built = SpatialDistributionSampler::with_seed(7.0, bounds, 21)
+            .with_edge_margin(4.0)
+            .with_max_points(Some(50))
+            .generate_distribution(&polygon);
+        assert_eq!(from_config, built);
+        assert_eq!(from_config.len(), 50);
+
+        assert!(SpatialDistributionSampler::from_config(SamplerConfig::new(0.0), bounds).is_err());
+    }
+
+    #[test]
+    fn test_generate_points_without_formatting() {
+        let polygon = parse_csv_file(
+            &write_temp_file("generate_points.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file")
+        .remove(0);
+        let params = VegetationParams {
+            density: 9.0,
+            variation: 2.0,
+            seed: Some(31),
+            ..Default::default()
+        };
+
+        let points = generate_points(polygon.clone(), &params).expect("Failed to generate points");
+        assert!(!points.is_empty());
+        assert!(
+            points
+                .iter()
+                .all(|point| point_in_polygon(&polygon, point, false))
+        );
+        assert_eq!(
+            generate_points(polygon.clone(), &params).expect("Failed to generate points"),
+            points
+        );
+
+        // fill_polygon compose la génération et la mise en forme
+        let options = ExportOptions::default();
+        let rows: Vec<String> = points
+            .iter()
+            .map(|point| format_point_row(point, &params, &options))
+            .collect();
+        assert_eq!(
+            fill_polygon(polygon, params).expect("Failed to fill polygon"),
+            rows
+        );
+    }
+
+    #[test]
+    fn test_format_point_row_layout() {
+        let params = VegetationParams {
+            type_value: 42,
+            ..Default::default()
+        };
+        let row = format_point_row(
+            &geo::Point::new(12.5, -3.25),
+            &params,
+            &ExportOptions::default(),
+        );
+        let fields: Vec<&str> = row.trim_end_matches(['\r', '\n']).split('\t').collect();
+
+        assert_eq!(fields.len(), get_output_columns().len());
+        assert_eq!(fields[0].trim(), "12.5");
+        assert_eq!(fields[1].trim(), "-3.25");
+        assert!(fields.contains(&"42"));
+        assert!(row.ends_with(ExportOptions::default().line_ending.as_str()));
+    }
+
+    #[test]
+    fn test_snap_to_grid() {
+        let polygon = parse_csv_file(
+            &write_temp_file("snap_to.csv", &format!("Geometry\n{DONUT_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file")
+        .remove(0);
+        let params = VegetationParams {
+            density: 6.0,
+            variation: 1.5,
+            seed: Some(8),
+            ..Default::default()
+        };
+
+        let snapped = generate_points(
+            polygon.clone(),
+            &VegetationParams {
+                snap_to: Some(0.5),
+                ..params.clone()
+            },
+        )
+        .expect("Failed to generate points");
+        assert!(!snapped.is_empty());
+        for point in &snapped {
+            assert_eq!(
+                (point.x() * 2.0).fract(),
+                0.0,
+                "{:?} is off the grid",
+                point
+            );
+            assert_eq!(
+                (point.y() * 2.0).fract(),
+                0.0,
+                "{:?} is off the grid",
+                point
+            );
+            assert!(point_in_polygon(&polygon, point, false));
+        }
+
+        // Une grille grossière pousse des points sur les bords : ils sont écartés
+        let coarse = generate_points(
+            polygon.clone(),
+            &VegetationParams {
+                snap_to: Some(25.0),
+                ..params.clone()
+            },
+        )
+        .expect("Failed to generate points");
+        assert!(coarse.len() < snapped.len());
+        assert!(
+            coarse
+                .iter()
+                .all(|point| point_in_polygon(&polygon, point, false))
+        );
+
+        let invalid = VegetationParams {
+            snap_to: Some(0.0),
+            ..params
+        };
+        assert!(generate_points(polygon, &invalid).is_err());
+    }
+
+    #[test]
+    fn test_csv_output_format() {
+        let polygon = parse_csv_file(
+            &write_temp_file("csv_output.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file")
+        .remove(0);
+        let params = VegetationParams {
+            density: 20.0,
+            type_value: 7,
+            seed: Some(2),
+            ..Default::default()
+        };
+        let options = ExportOptions {
+            format: OutputFormat::Csv,
+            ..Default::default()
+        };
+        assert_eq!(options.format.extension(), "csv");
+
+        let mut output = Vec::<u8>::new();
+        let count = export_to_writer(
+            std::slice::from_ref(&polygon),
+            &params,
+            &[],
+            &options,
+            &mut output,
+            &VegetationProcessingState::new(),
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+
+        let expected = generate_points(polygon, &params).expect("Failed to generate points");
+        let mut reader = csv::ReaderBuilder::new().from_reader(output.as_slice());
+        assert_eq!(
+            reader
+                .headers()
+                .expect("Missing header")
+                .iter()
+                .collect::<Vec<_>>(),
+            vec!["X", "Y", "type", "z"]
+        );
+        let records: Vec<csv::StringRecord> = reader
+            .records()
+            .collect::<Result<_, _>>()
+            .expect("Invalid CSV output");
+        assert_eq!(records.len(), count);
+        assert_eq!(records.len(), expected.len());
+        for (record, point) in records.iter().zip(&expected) {
+            assert_eq!(record.len(), 4);
+            assert_eq!(record[0].parse::<f64>().unwrap(), point.x());
+            assert_eq!(record[1].parse::<f64>().unwrap(), point.y());
+            assert_eq!(&record[2], "7");
+            assert_eq!(&record[3], "0");
+        }
+    }
+
+    #[test]
+    fn test_sampler_reset_reuses_grid() {
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "sampler_reset.csv",
+                &format!(
+                    "Geometry\n{SQUARE_WKT}\n{DONUT_WKT}\nPOLYGON((0 0,40 0,40 30,0 30,0 0))\n"
+                ),
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+
+        let mut reused: Option<SpatialDistributionSampler> = None;
+        for polygon in &polygons {
+            let bounds = calculate_polygon_bounds(polygon).expect("Polygon has no bounds");
+            let expected = SpatialDistributionSampler::with_seed(6.0, bounds, 12)
+                .generate_distribution(polygon);
+
+            let mut sampler = match reused.take() {
+                Some(mut previous) => {
+                    previous.reset(bounds);
+                    previous
+                }
+                None => SpatialDistributionSampler::with_seed(6.0, bounds, 12),
+            };
+            assert_eq!(sampler.generate_distribution(polygon), expected);
+
+            // Un sampler neuf reprenant les allocations du précédent donne la même distribution
+            let recycled = SpatialDistributionSampler::with_seed(6.0, bounds, 12)
+                .reusing(sampler)
+                .generate_distribution(polygon);
+            assert_eq!(recycled, expected);
+
+            let mut sampler = SpatialDistributionSampler::with_seed(6.0, bounds, 12);
+            sampler.generate_distribution(polygon);
+            reused = Some(sampler);
+        }
+    }
+
+    #[test]
+    fn test_atomic_export_write() {
+        use std::io::Write;
+
+        let directory =
+            std::env::temp_dir().join(format!("vegepoly-{}-atomic", std::process::id()));
+        std::fs::create_dir_all(&directory).expect("Failed to create export directory");
+        let output = directory.join("Export.txt");
+        let temp = directory.join("Export.txt.tmp");
+        let partial = directory.join("Export.txt.partial");
+        let state = VegetationProcessingState::new();
+
+        // Échec au milieu de l'écriture : aucun fichier sous le nom définitif
+        let error = write_export_atomically(&output, &state, |writer| {
+            writer
+                .write_all(b"X\tY\n1\t2\n")
+                .map_err(|e| e.to_string())?;
+            Err::<(), _>("Simulated write failure".to_string())
+        })
+        .expect_err("Write should fail");
+        assert!(error.contains("Simulated write failure"), "{}", error);
+        assert!(!output.exists());
+        assert!(!temp.exists());
+        assert_eq!(std::fs::read_to_string(&partial).unwrap(), "X\tY\n1\t2\n");
+        std::fs::remove_file(&partial).unwrap();
+
+        // Une annulation supprime le fichier temporaire sans garder de fichier partiel
+        let cancelled = VegetationProcessingState::new();
+        cancelled.request_cancel();
+        write_export_atomically(&output, &cancelled, |writer| {
+            writer.write_all(b"X\tY\n").map_err(|e| e.to_string())?;
+            Err::<(), _>("Export cancelled".to_string())
+        })
+        .expect_err("Write should be cancelled");
+        assert!(!output.exists() && !temp.exists() && !partial.exists());
+
+        let count = write_export_atomically(&output, &state, |writer| {
+            writer.write_all(b"X\tY\n").map_err(|e| e.to_string())?;
+            Ok(1)
+        })
+        .expect("Write failed");
+        assert_eq!(count, 1);
+        assert!(output.exists() && !temp.exists());
+        std::fs::remove_dir_all(&directory).expect("Failed to clean export directory");
+    }
+
+    #[test]
+    fn test_geojson_skipped_features() {
+        let feature = |geometry: &str| {
+            format!(r#"{{"type": "Feature", "properties": {{}}, "geometry": {geometry}}}"#)
+        };
+        let square = feature(
+            r#"{"type": "Polygon", "coordinates": [[[0, 0], [200, 0], [200, 200], [0, 200], [0, 0]]]}"#,
+        );
+        let point = feature(r#"{"type": "Point", "coordinates": [10, 10]}"#);
+        let broken = feature(r#"{"type": "Polygon", "coordinates": [[[0, 0], [1]]]}"#);
+        let collection = |features: &[&str]| {
+            format!(
+                r#"{{"type": "FeatureCollection", "features": [{}]}}"#,
+                features.join(",")
+            )
+        };
+
+        // Les entités qui ne sont pas des polygones sont ignorées sans interrompre la lecture
+        let path = write_temp_file("skipped.geojson", &collection(&[&point, &square]));
+        assert_eq!(
+            parse_geojson_file(&path)
+                .expect("Failed to parse GeoJSON file")
+                .len(),
+            1
+        );
+        let (preview_polygon, preview_points) =
+            get_preview_data(&path, VegetationParams::default(), None, None)
+                .expect("Failed to preview GeoJSON file");
+        assert_eq!(preview_polygon.exterior.len(), 5);
+        assert!(!preview_points.is_empty());
+
+        // La lecture par extension et les exports qui relisent le fichier les signalent
+        let input = parse_geometry_file(&path).expect("Failed to parse GeoJSON file");
+        assert_eq!(input.polygons.len(), 1);
+        assert_eq!(
+            input.skipped_features,
+            vec![(1, "Point is not a polygon".to_string())]
+        );
+        let directory =
+            std::env::temp_dir().join(format!("vegepoly-{}-skipped", std::process::id()));
+        std::fs::create_dir_all(&directory).expect("Failed to create export directory");
+        let state = VegetationProcessingState::new();
+        let filenames = export_all_types_to_directory(
+            Vec::new(),
+            &[1, 2].map(|vegetation_type| VegetationParams {
+                vegetation_type,
+                density: 20.0,
+                ..Default::default()
+            }),
+            &ExportOptions {
+                write_summary_json: true,
+                ..Default::default()
+            },
+            Some(&path),
+            &directory,
+            chrono::Local::now(),
+            &state,
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+        for filename in &filenames {
+            let summary: ExportSummary = serde_json::from_str(
+                &std::fs::read_to_string(directory.join(ExportSummary::filename(filename)))
+                    .expect("Failed to read summary"),
+            )
+            .expect("Summary is not valid JSON");
+            assert_eq!(
+                summary.errors,
+                vec!["GeoJSON feature 1 skipped: Point is not a polygon".to_string()]
+            );
+        }
+        assert_eq!(
+            *state.errors.lock().unwrap(),
+            vec![
+                "Type 1: GeoJSON feature 1 skipped: Point is not a polygon".to_string(),
+                "Type 2: GeoJSON feature 1 skipped: Point is not a polygon".to_string(),
+            ]
+        );
+        std::fs::remove_dir_all(&directory).expect("Failed to clean export directory");
+
+        let path = write_temp_file("broken.geojson", &collection(&[&point, &broken, &square]));
+        assert!(parse_geojson_file(&path).is_err());
+        let lenient = parse_geojson_file_lenient(&path).expect("Failed to read GeoJSON file");
+        assert_eq!(lenient.polygons.len(), 1);
+        let skipped: Vec<usize> = lenient
+            .skipped_features
+            .iter()
+            .map(|(feature, _)| *feature)
+            .collect();
+        assert_eq!(skipped, vec![1, 2]);
+        assert!(lenient.skipped_features[0].1.contains("Point"));
+    }
+
+    #[test]
+    fn test_parse_geometry_file_by_extension() {
+        // Une géométrie par ligne, les MULTIPOLYGON étant éclatés
+        let wkt = write_temp_file(
+            "shapes.wkt",
+            &format!(
+                "{SQUARE_WKT}\n\nMULTIPOLYGON(((0 0,10 0,10 10,0 0)),((20 20,30 20,30 30,20 20)))\n"
+            ),
+        );
+        let polygons = parse_geometry_file(&wkt)
+            .expect("Failed to parse WKT file")
+            .polygons;
+        assert_eq!(polygons.len(), 3);
+
+        let csv = write_temp_file("shapes.CSV", &format!("Geometry\n{DONUT_WKT}\n"));
+        let polygons = parse_geometry_file(&csv)
+            .expect("Failed to parse CSV file")
+            .polygons;
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].interiors().len(), 1);
+
+        let broken = write_temp_file("broken.wkt", &format!("{SQUARE_WKT}\nPOINT(1 1)\n"));
+        let error = parse_geometry_file(&broken).unwrap_err();
+        assert!(error.starts_with("Line 2"), "{}", error);
+
+        let unknown = write_temp_file("shapes.shp", SQUARE_WKT);
+        let error = parse_geometry_file(&unknown).unwrap_err();
+        assert!(
+            error.contains(".geojson") && error.contains(".wkt"),
+            "{}",
+            error
+        );
+        assert!(get_preview_data(&unknown, VegetationParams::default(), None, None).is_err());
+    }
+
+    #[test]
+    fn test_same_second_exports_do_not_clobber() {
+        let directory =
+            std::env::temp_dir().join(format!("vegepoly-{}-same-second", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).expect("Failed to create export directory");
+        let polygons = parse_geometry_file(&write_temp_file(
+            "same-second.csv",
+            &format!("Geometry\n{SQUARE_WKT}\n"),
+        ))
+        .expect("Failed to parse CSV")
+        .polygons;
+        let options = ExportOptions::default();
+        // Les trois exports partagent le même horodatage, comme des exports lancés à la suite
+        let timestamp = chrono::Local::now();
+
+        let filenames: Vec<String> = (0..3)
+            .map(|_| {
+                export_to_directory(
+                    &polygons,
+                    &VegetationParams::default(),
+                    &[],
+                    &options,
+                    None,
+                    &directory,
+                    timestamp,
+                    &VegetationProcessingState::new(),
+                    &CallbackReporter(|_: &VegetationProgressInfo| {}),
+                )
+                .expect("Export failed")
+            })
+            .collect();
+
+        let base = export_filename(&options, timestamp);
+        let stem = base.trim_end_matches(".txt");
+        assert_eq!(
+            filenames,
+            vec![
+                base.clone(),
+                format!("{stem} (1).txt"),
+                format!("{stem} (2).txt")
+            ]
+        );
+        for filename in &filenames {
+            assert!(
+                directory.join(filename).is_file(),
+                "{} is missing",
+                filename
+            );
+        }
+        assert_eq!(
+            std::fs::read_dir(&directory)
+                .expect("Failed to list export directory")
+                .count(),
+            3
+        );
+        std::fs::remove_dir_all(&directory).expect("Failed to clean export directory");
+    }
+
+    #[test]
+    fn test_parse_csv_file_with_layout() {
+        let headless = write_temp_file("headless.csv", &format!("1;{SQUARE_WKT}\n2;{DONUT_WKT}\n"));
+        let polygons = parse_csv_file_with(&headless, b';', 1, false).expect("Failed to parse CSV");
+        assert_eq!(polygons.len(), 2);
+        assert_eq!(polygons[1].interiors().len(), 1);
+
+        let error = parse_csv_file_with(&headless, b';', 2, false).unwrap_err();
+        assert!(error.contains("line 1"), "{}", error);
+
+        let quoted = write_temp_file("quoted.csv", &format!("id,wkt\n1,\"{SQUARE_WKT}\"\n"));
+        let polygons = parse_csv_file_with(&quoted, b',', 1, true).expect("Failed to parse CSV");
+        assert_eq!(polygons.len(), 1);
+
+        // Le format par défaut reste celui de `parse_csv_file`
+        assert_eq!(
+            parse_csv_file_with("tests/VEGETATION_ARBRES.csv", b'\t', 0, true),
+            parse_csv_file("tests/VEGETATION_ARBRES.csv", None, None)
+        );
+    }
+
+    #[test]
+    fn test_coordinate_precision_setting() {
+        let (settings, path) = open_temp_settings("precision");
+        assert_eq!(
+            settings
+                .get_coordinate_precision()
+                .expect("Failed to read precision"),
+            DEFAULT_COORDINATE_PRECISION
+        );
+        settings
+            .set_coordinate_precision(4)
+            .expect("Failed to set precision");
+        assert!(settings.set_coordinate_precision(40).is_err());
+        settings.flush().expect("Failed to flush settings");
+
+        let precision = Settings::open(path.path().to_path_buf())
+            .expect("Failed to reopen settings")
+            .get_coordinate_precision()
+            .expect("Failed to read precision");
+        assert_eq!(precision, 4);
+
+        let options = ExportOptions {
+            coordinate_precision: Some(precision),
+            format: OutputFormat::Csv,
+            ..Default::default()
+        };
+        let row = format_point_row(
+            &geo::Point::new(845123.4000000001, 6512000.5),
+            &VegetationParams::default(),
+            &options,
+        );
+        let fields: Vec<&str> = row.trim_end().split(',').collect();
+        assert_eq!(fields[0], "845123.4000");
+        assert_eq!(fields[1], "6512000.5000");
+    }
+
+    #[test]
+    fn test_csv_attributes_reach_output() {
+        let path = write_temp_file(
+            "attributes.csv",
+            &format!(
+                "Geometry\tCODE_REGION\tNUMERO_INSEE\ttype\tespece\n{SQUARE_WKT}\t93\t13001\t99\tpin\n"
+            ),
+        );
+        let (polygons, overrides): (Vec<_>, Vec<_>) =
+            parse_csv_file_with_overrides(&path, None, None)
+                .expect("Failed to parse CSV")
+                .into_iter()
+                .unzip();
+        assert_eq!(
+            overrides[0].attributes.get("espece").map(String::as_str),
+            Some("pin")
+        );
+        assert_eq!(
+            parse_csv_file(&path, None, None).expect("Failed to parse CSV"),
+            polygons
+        );
+
+        let params = VegetationParams {
+            density: 40.0,
+            ..Default::default()
+        };
+        let mut output = Vec::<u8>::new();
+        export_to_writer(
+            &polygons,
+            &params,
+            &overrides,
+            &ExportOptions::default(),
+            &mut output,
+            &VegetationProcessingState::new(),
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+        let output = String::from_utf8(output).expect("Export is not UTF-8");
+        let columns: Vec<&str> = OUTPUT_HEADER.split('\t').collect();
+        let column = |name: &str| columns.iter().position(|c| *c == name).unwrap();
+
+        let rows: Vec<Vec<&str>> = output
+            .lines()
+            .skip(1)
+            .map(|row| row.split('\t').collect())
+            .collect();
+        assert!(!rows.is_empty());
+        for row in rows {
+            assert_eq!(row.len(), columns.len());
+            assert_eq!(row[column("CODE_REGION")], "93");
+            assert_eq!(row[column("NUMERO_INSEE")], "13001");
+            // Les colonnes sans attribut gardent leur valeur par défaut, et le type
+            // reste celui des paramètres de végétation
+            assert_eq!(row[column("CODE_INSEE_SGA")], "20");
+            assert_eq!(row[column("type")], params.type_value.to_string());
+        }
+
+        // Sans attributs, la ligne est identique à celle de `format_point_row`
+        let point = geo::Point::new(10.0, 20.0);
+        let options = ExportOptions::default();
+        assert_eq!(
+            format_point_row_with_attributes(&point, &params, &Default::default(), &options),
+            format_point_row(&point, &params, &options)
+        );
+    }
+
+    #[test]
+    fn test_settings_profiles_are_isolated() {
+        let (settings, _path) = open_temp_settings("profiles");
+        let base_density = settings
+            .get_vegetation_params(1)
+            .expect("Failed to read params")
+            .expect("Missing default params")
+            .density;
+        let north_dir = std::env::temp_dir().join(format!("vegepoly-{}-north", std::process::id()));
+        std::fs::create_dir_all(&north_dir).expect("Failed to create export directory");
+        let profile = |export_path: &std::path::Path, density: f64| SettingsProfile {
+            export_path: export_path.to_path_buf(),
+            vegetation_params: vec![VegetationParams {
+                vegetation_type: 1,
+                density,
+                type_value: 10,
+                ..Default::default()
+            }],
+        };
+        settings
+            .create_profile("nord", &profile(&north_dir, 12.0))
+            .expect("Failed to create profile");
+        settings
+            .create_profile("sud", &profile(&std::env::temp_dir(), 40.0))
+            .expect("Failed to create profile");
+        assert_eq!(
+            settings.list_profiles().expect("Failed to list profiles"),
+            vec!["nord", "sud"]
+        );
+        assert!(settings.switch_profile(Some("ouest")).is_err());
+
+        let density = |settings: &Settings, vegetation_type| {
+            settings
+                .get_vegetation_params(vegetation_type)
+                .expect("Failed to read params")
+                .expect("Missing params")
+                .density
+        };
+        settings
+            .switch_profile(Some("nord"))
+            .expect("Failed to switch profile");
+        assert_eq!(density(&settings, 1), 12.0);
+        assert_eq!(
+            settings
+                .get_export_path()
+                .expect("Failed to read export path"),
+            north_dir
+        );
+        // Les types absents du profil gardent leurs paramètres généraux
+        assert_eq!(density(&settings, 2), 5.0);
+
+        settings
+            .switch_profile(Some("sud"))
+            .expect("Failed to switch profile");
+        assert_eq!(density(&settings, 1), 40.0);
+        assert_eq!(
+            settings
+                .get_active_profile()
+                .expect("Failed to read profile")
+                .as_deref(),
+            Some("sud")
+        );
+
+        // Supprimer le profil actif revient aux paramètres généraux
+        settings
+            .delete_profile("sud")
+            .expect("Failed to delete profile");
+        assert_eq!(
+            settings
+                .get_active_profile()
+                .expect("Failed to read profile"),
+            None
+        );
+        assert_eq!(density(&settings, 1), base_density);
+        assert!(settings.delete_profile("sud").is_err());
+        std::fs::remove_dir_all(&north_dir).expect("Failed to clean export directory");
+    }
+
+    #[test]
+    fn test_sampling_in_metric_projection() {
+        // Tour Eiffel : coordonnées Lambert-93 de référence, au mètre près
+        let eiffel = geo::Polygon::new(
+            geo::LineString::from(vec![
+                (2.294481, 48.858370),
+                (2.304481, 48.858370),
+                (2.304481, 48.868370),
+                (2.294481, 48.868370),
+            ]),
+            vec![],
+        );
+        let projected = reproject_polygons(std::slice::from_ref(&eiffel), 4326, 2154)
+            .expect("Failed to reproject");
+        let corner = projected[0].exterior().0[0];
+        assert!((corner.x - 648237.0).abs() < 5.0, "{:?}", corner);
+        assert!((corner.y - 6862271.0).abs() < 5.0, "{:?}", corner);
+        // Le carré de 0,01° mesure environ 730 m d'est en ouest
+        let width = projected[0].exterior().0[1].x - corner.x;
+        assert!((width - 733.0).abs() < 10.0, "{}", width);
+
+        let params = VegetationParams {
+            density: 50.0,
+            source_epsg: Some(4326),
+            sampling_epsg: Some(2154),
+            seed: Some(3),
+            ..Default::default()
+        };
+        let mut output = Vec::<u8>::new();
+        export_to_writer(
+            &[eiffel],
+            &params,
+            &[],
+            &ExportOptions::default(),
+            &mut output,
+            &VegetationProcessingState::new(),
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+        let rows: Vec<String> = String::from_utf8(output)
+            .expect("Export is not UTF-8")
+            .lines()
+            .skip(1)
+            .map(str::to_string)
+            .collect();
+        let points = parse_points(&rows);
+        // Une distance de 50 m laisse des dizaines de points dans le carré, écrits en degrés
+        assert!(points.len() > 50, "{}", points.len());
+        assert!(
+            points
+                .iter()
+                .all(|(x, y)| (2.29..2.31).contains(x) && (48.85..48.87).contains(y))
+        );
+
+        let invalid = VegetationParams {
+            source_epsg: None,
+            ..params
+        };
+        assert!(generate_points(projected[0].clone(), &invalid).is_err());
+    }
+
+    #[test]
+    fn test_settings_bundle_round_trip() {
+        let (source, _source_path) = open_temp_settings("bundle-source");
+        source
+            .set_export_path(std::env::temp_dir())
+            .expect("Failed to set export path");
+        source
+            .set_user_vegetation_params(
+                2,
+                VegetationParams {
+                    vegetation_type: 2,
+                    density: 7.5,
+                    type_value: 25,
+                    variation: 1.5,
+                    ..Default::default()
+                },
+            )
+            .expect("Failed to set user params");
+        source
+            .set_vegetation_type_label(2, "Haies")
+            .expect("Failed to set label");
+        let json = source.export_bundle().expect("Failed to export bundle");
+
+        let (target, _target_path) = open_temp_settings("bundle-target");
+        target
+            .import_bundle(&json)
+            .expect("Failed to import bundle");
+        assert_eq!(
+            serde_json::from_str::<SettingsBundle>(
+                &target.export_bundle().expect("Failed to export bundle")
+            )
+            .expect("Invalid bundle"),
+            serde_json::from_str::<SettingsBundle>(&json).expect("Invalid bundle")
+        );
+        let stored = target
+            .get_user_vegetation_params(2)
+            .expect("Failed to read user params")
+            .expect("User params were not imported");
+        assert_eq!((stored.density, stored.type_value), (7.5, 25));
+
+        // Une clé inconnue ou un paramètre invalide n'applique rien
+        let (fresh, _fresh_path) = open_temp_settings("bundle-invalid");
+        let before = fresh.export_bundle().expect("Failed to export bundle");
+        let mut unknown: serde_json::Value = serde_json::from_str(&json).expect("Invalid JSON");
+        unknown["colour"] = "green".into();
+        let error = fresh.import_bundle(&unknown.to_string()).unwrap_err();
+        assert!(error.to_string().contains("colour"), "{}", error);
+        let mut negative: serde_json::Value = serde_json::from_str(&json).expect("Invalid JSON");
+        negative["user_vegetation_params"][0]["density"] = (-1.0).into();
+        assert!(fresh.import_bundle(&negative.to_string()).is_err());
+        assert_eq!(
+            fresh.export_bundle().expect("Failed to export bundle"),
+            before
+        );
+    }
+
+    #[test]
+    fn test_csv_streaming_export() {
+        let streamed: Vec<_> = iter_csv_polygons("tests/VEGETATION_ARBRES.csv")
+            .collect::<Result<_, _>>()
+            .expect("Failed to stream CSV");
+        let polygons =
+            parse_csv_file("tests/VEGETATION_ARBRES.csv", None, None).expect("Failed to parse CSV");
+        assert_eq!(streamed, polygons);
+        let mut missing = iter_csv_polygons("tests/missing.csv");
+        assert!(missing.next().is_some_and(|row| row.is_err()));
+        assert!(missing.next().is_none());
+
+        // Lu au fil de l'export, le fichier donne les mêmes points qu'une fois chargé
+        let path = write_temp_file(
+            "streamed.csv",
+            &format!("Geometry\tvariation\n{SQUARE_WKT}\t0\n\t\n{DONUT_WKT}\t2\n"),
+        );
+        let (polygons, overrides): (Vec<_>, Vec<_>) =
+            parse_csv_file_with_overrides(&path, None, None)
+                .expect("Failed to parse CSV")
+                .into_iter()
+                .unzip();
+        let params = VegetationParams {
+            density: 20.0,
+            seed: Some(11),
+            ..Default::default()
+        };
+        for max_threads in [Some(1), None] {
+            let options = ExportOptions {
+                max_threads,
+                ..Default::default()
+            };
+            let export = |streaming: bool| {
+                let mut output = Vec::<u8>::new();
+                let state = VegetationProcessingState::new();
+                let reporter = CallbackReporter(|_: &VegetationProgressInfo| {});
+                let count = if streaming {
+                    export_csv_to_writer(&path, &params, &options, &mut output, &state, &reporter)
+                } else {
+                    export_to_writer(
+                        &polygons,
+                        &params,
+                        &overrides,
+                        &options,
+                        &mut output,
+                        &state,
+                        &reporter,
+                    )
+                }
+                .expect("Export failed");
+                assert_eq!(*state.processed_rows.lock().unwrap(), 2);
+                (count, output)
+            };
+            assert_eq!(export(true), export(false));
+        }
+
+        let broken = write_temp_file(
+            "streamed-broken.csv",
+            &format!("Geometry\n{SQUARE_WKT}\nPOINT(1 1)\n"),
+        );
+        let state = VegetationProcessingState::new();
+        export_csv_to_writer(
+            &broken,
+            &params,
+            &ExportOptions::default(),
+            &mut Vec::<u8>::new(),
+            &state,
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+        let errors = state.errors.lock().unwrap().clone();
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].contains("line 3"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn test_large_csv_is_processed_row_by_row() {
+        let rows: String = (0..3000)
+            .map(|index| {
+                let (x, y) = ((index % 60) as f64 * 20.0, (index / 60) as f64 * 20.0);
+                format!(
+                    "POLYGON(({x} {y},{} {y},{} {},{x} {},{x} {y}))\n",
+                    x + 10.0,
+                    x + 10.0,
+                    y + 10.0,
+                    y + 10.0
+                )
+            })
+            .collect();
+        // La dernière ligne est invalide : elle n'est lue et signalée qu'une fois les
+        // autres exportées
+        let path = write_temp_file("large.csv", &format!("Geometry\n{rows}POLYGON((0 0,1 0\n"));
+        assert_eq!(count_csv_rows(&path, None, None), Ok(3001));
+
+        let percentages = std::sync::Mutex::new(Vec::new());
+        let state = VegetationProcessingState::new();
+        export_csv_to_writer(
+            &path,
+            &VegetationParams {
+                density: 4.0,
+                seed: Some(5),
+                ..Default::default()
+            },
+            &ExportOptions {
+                max_threads: Some(1),
+                ..Default::default()
+            },
+            &mut std::io::sink(),
+            &state,
+            &CallbackReporter(|progress: &VegetationProgressInfo| {
+                percentages.lock().unwrap().push(progress.percentage)
+            }),
+        )
+        .expect("Export failed");
+        let errors = state.errors.lock().unwrap().clone();
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].contains("line 3002"), "{}", errors[0]);
+        assert_eq!(*state.total_rows.lock().unwrap(), 3001);
+        assert_eq!(*state.processed_rows.lock().unwrap(), 3001);
+        assert!(*state.created_items.lock().unwrap() >= 3000);
+        let percentages = percentages.into_inner().unwrap();
+        assert!(percentages.iter().any(|p| *p > 50.0 && *p < 100.0));
+    }
+
+    #[test]
+    fn test_streamed_export_errors_cite_source_lines() {
+        let degenerate = "POLYGON((0 0,1 0,2 0,0 0))";
+        let path = write_temp_file(
+            "streamed_source_lines.csv",
+            &format!("Geometry\n{SQUARE_WKT}\n\n{degenerate}\n  \n{DONUT_WKT}\n{degenerate}\n"),
+        );
+
+        let state = VegetationProcessingState::new();
+        let reported = std::sync::Mutex::new(Vec::new());
+        export_csv_to_writer(
+            &path,
+            &VegetationParams::default(),
+            &ExportOptions::default(),
+            &mut Vec::<u8>::new(),
+            &state,
+            &CallbackReporter(|info: &VegetationProgressInfo| {
+                *reported.lock().unwrap() = info.errors.clone();
+            }),
+        )
+        .expect("Export failed");
+
+        // Les lignes vides décalent l'index du polygone mais pas la ligne citée
+        let errors = state.errors.lock().unwrap().clone();
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors[0].contains("polygon 2 (line 4)"), "{}", errors[0]);
+        assert!(errors[1].contains("polygon 4 (line 7)"), "{}", errors[1]);
+        assert_eq!(*reported.lock().unwrap(), errors);
+    }
+
+    #[test]
+    fn test_repair_invalid_polygons_before_sampling() {
+        // Pointe aller-retour sur le bord haut : réparable en un seul carré
+        let spike = "POLYGON((0 0,200 0,200 200,100 200,100 300,100 200,0 200,0 0))";
+        // Nœud papillon : se recoupe et donne deux triangles
+        let bowtie = "POLYGON((0 0,200 200,200 0,0 200,0 0))";
+
+        let repaired = repair_polygon(&parse_wkt_polygons(spike).unwrap()[0])
+            .expect("Spike should be repairable");
+        assert!((geo::Area::unsigned_area(&repaired) - 40000.0).abs() < 1e-6);
+        let square = parse_wkt_polygons(SQUARE_WKT).unwrap().remove(0);
+        assert_eq!(repair_polygon(&square), Ok(square));
+        let error = repair_polygon(&parse_wkt_polygons(bowtie).unwrap()[0]).unwrap_err();
+        assert!(error.contains("2 parts"), "{}", error);
+
+        let path = write_temp_file(
+            "repair_geometry.csv",
+            &format!("Geometry\n{spike}\n{bowtie}\n"),
+        );
+        let param = VegetationParams {
+            repair_geometry: true,
+            seed: Some(7),
+            ..Default::default()
+        };
+        for max_threads in [Some(1), None] {
+            let state = VegetationProcessingState::new();
+            let mut output = Vec::new();
+            export_csv_to_writer(
+                &path,
+                &param,
+                &ExportOptions {
+                    max_threads,
+                    ..Default::default()
+                },
+                &mut output,
+                &state,
+                &CallbackReporter(|_: &VegetationProgressInfo| {}),
+            )
+            .expect("Export failed");
+
+            let rows: Vec<String> = String::from_utf8(output)
+                .expect("Export is not UTF-8")
+                .lines()
+                .skip(1)
+                .map(str::to_string)
+                .collect();
+            let points = parse_points(&rows);
+            assert!(!points.is_empty());
+            assert!(points.iter().all(|&(_, y)| y <= 200.0));
+            let errors = state.errors.lock().unwrap().clone();
+            assert_eq!(errors.len(), 1, "{:?}", errors);
+            assert!(errors[0].contains("polygon 2 (line 3)"), "{}", errors[0]);
+            assert!(errors[0].contains("2 parts"), "{}", errors[0]);
+        }
+    }
+
+    #[test]
+    fn test_polygon_z_elevation_reaches_z_column() {
+        let path = write_temp_file(
+            "polygon_z.csv",
+            "Geometry\nPOLYGON Z ((0 0 10,200 0 20,200 200 30,0 200 40,0 0 10))\n",
+        );
+        let rows =
+            parse_csv_file_with_overrides(&path, None, None).expect("Failed to parse CSV file");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].1.elevation, Some(25.0));
+        assert_eq!(
+            rows[0].0,
+            parse_wkt_polygons(SQUARE_WKT).unwrap().remove(0),
+            "Z should be dropped for 2D sampling"
+        );
+
+        let z_column = OUTPUT_HEADER
+            .split('\t')
+            .position(|name| name == "z")
+            .unwrap();
+        for (z_source, expected) in [
+            (ZSource::PolygonMean, "25"),
+            (ZSource::Constant, "0"),
+            (ZSource::None, ""),
+        ] {
+            let mut output = Vec::new();
+            export_csv_to_writer(
+                &path,
+                &VegetationParams {
+                    seed: Some(3),
+                    ..Default::default()
+                },
+                &ExportOptions {
+                    z_source,
+                    ..Default::default()
+                },
+                &mut output,
+                &VegetationProcessingState::new(),
+                &CallbackReporter(|_: &VegetationProgressInfo| {}),
+            )
+            .expect("Export failed");
+
+            let output = String::from_utf8(output).expect("Export is not UTF-8");
+            let rows: Vec<&str> = output.lines().skip(1).collect();
+            assert!(!rows.is_empty());
+            for row in rows {
+                assert_eq!(row.split('\t').nth(z_column), Some(expected), "{}", row);
+            }
+        }
+    }
+
+    #[test]
+    fn test_progress_reports_throughput() {
+        let state = VegetationProcessingState::new();
+        let progress = std::sync::Mutex::new(None);
+        let reporter = CallbackReporter(|info: &VegetationProgressInfo| {
+            *progress.lock().unwrap() = Some(info.clone());
+        });
+
+        // Avant le début de l'export, aucun débit n'est calculé
+        state.emit_progress(&reporter);
+        let info = progress.lock().unwrap().take().unwrap();
+        assert_eq!(info.rows_per_second, None);
+        assert_eq!(info.points_per_second, None);
+
+        let start = std::time::Instant::now();
+        *state.start_time.lock().unwrap() = Some(start);
+        *state.end_time.lock().unwrap() = Some(start + std::time::Duration::from_secs(4));
+        state.update_processed_rows(10, &reporter);
+        state.update_created_items(12800, &reporter);
+
+        let info = progress.lock().unwrap().take().unwrap();
+        assert_eq!(info.rows_per_second, Some(2.5));
+        assert_eq!(info.points_per_second, Some(3200.0));
+    }
+
+    #[test]
+    fn test_streamed_csv_export_as_geojson() {
+        let path = write_temp_file(
+            "streamed_geojson.csv",
+            &format!("Geometry\n{SQUARE_WKT}\n{DONUT_WKT}\n"),
+        );
+        let mut output = Vec::new();
+        let written = export_csv_to_writer(
+            &path,
+            &VegetationParams {
+                type_value: 12,
+                ..Default::default()
+            },
+            &ExportOptions {
+                format: OutputFormat::GeoJson,
+                ..Default::default()
+            },
+            &mut output,
+            &VegetationProcessingState::new(),
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+
+        let collection: serde_json::Value =
+            serde_json::from_slice(&output).expect("Export is not valid JSON");
+        let features = collection["features"]
+            .as_array()
+            .expect("FeatureCollection has no features");
+        assert_eq!(features.len(), written);
+        assert!(features.iter().all(|feature| {
+            feature["geometry"]["type"] == "Point" && feature["properties"]["type"] == 12
+        }));
+    }
+
+    #[test]
+    fn test_each_output_format_has_its_header_and_extension() {
+        let path = write_temp_file("output_formats.csv", &format!("Geometry\n{SQUARE_WKT}\n"));
+        let timestamp = chrono::Local::now();
+        for (format, header, extension) in [
+            (OutputFormat::SdisTxt, OUTPUT_HEADER, "txt"),
+            (OutputFormat::Csv, "X,Y,type,z", "csv"),
+            (
+                OutputFormat::GeoJson,
+                r#"{"type":"FeatureCollection","features":["#,
+                "geojson",
+            ),
+            (OutputFormat::WktMultipoint, "WKT\ttype", "csv"),
+        ] {
+            let options = ExportOptions {
+                format,
+                ..Default::default()
+            };
+            let mut output = Vec::new();
+            export_csv_to_writer(
+                &path,
+                &VegetationParams::default(),
+                &options,
+                &mut output,
+                &VegetationProcessingState::new(),
+                &CallbackReporter(|_: &VegetationProgressInfo| {}),
+            )
+            .expect("Export failed");
+
+            let output = String::from_utf8(output).expect("Export is not UTF-8");
+            assert_eq!(output.lines().next(), Some(header), "{:?}", format);
+            assert!(export_filename(&options, timestamp).ends_with(&format!(".{}", extension)));
+        }
+        assert_eq!(ExportOptions::default().format, OutputFormat::SdisTxt);
+    }
+
+    #[test]
+    fn test_radial_density_field_is_denser_at_centroid() {
+        let disk = geo::Polygon::new(
+            geo::LineString::from(
+                (0..64)
+                    .map(|i| {
+                        let angle = i as f64 * std::f64::consts::TAU / 64.0;
+                        (100.0 * angle.cos(), 100.0 * angle.sin())
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            vec![],
+        );
+        // Rapport des densités d'un disque central et d'une couronne éloignée du bord
+        let density_ratio = |density_field: DensityField| {
+            let points = generate_points(
+                disk.clone(),
+                &VegetationParams {
+                    density: 3.0,
+                    seed: Some(11),
+                    density_field,
+                    ..Default::default()
+                },
+            )
+            .expect("Failed to generate points");
+            let radius = |point: &geo::Point<f64>| point.x().hypot(point.y());
+            let inner = points.iter().filter(|point| radius(point) < 30.0).count();
+            let outer = points
+                .iter()
+                .filter(|point| (60.0..90.0).contains(&radius(point)))
+                .count();
+            let inner_area = std::f64::consts::PI * 30.0 * 30.0;
+            let outer_area = std::f64::consts::PI * (90.0 * 90.0 - 60.0 * 60.0);
+            (inner as f64 / inner_area) / (outer as f64 / outer_area)
+        };
+
+        let uniform = density_ratio(DensityField::Uniform);
+        assert!((0.8..1.25).contains(&uniform), "{}", uniform);
+        // Facteur 2 au bord : environ 1,2 au disque central, 1,75 sur la couronne,
+        // soit une densité environ (1,75 / 1,2)² ≈ 2,1 fois plus forte au centre
+        let radial = density_ratio(DensityField::RadialFromCentroid { edge_factor: 2.0 });
+        assert!((1.6..3.0).contains(&radial), "{}", radial);
+
+        let invalid = VegetationParams {
+            density_field: DensityField::RadialFromCentroid { edge_factor: 0.0 },
+            ..Default::default()
+        };
+        assert!(generate_points(disk, &invalid).is_err());
+    }
+
+    #[test]
+    fn test_vegetation_params_builder_validation() {
+        let params = VegetationParams::builder()
+            .vegetation_type(3)
+            .density(2.5)
+            .variation(0.5)
+            .type_value(30)
+            .build()
+            .expect("Valid parameters were rejected");
+        assert_eq!(
+            (
+                params.vegetation_type,
+                params.density,
+                params.variation,
+                params.type_value
+            ),
+            (3, 2.5, 0.5, 30)
+        );
+
+        let build = |builder: VegetationParamsBuilder| builder.build().unwrap_err();
+        assert_eq!(
+            build(VegetationParams::builder().vegetation_type(0)),
+            ParamsError::InvalidVegetationType(0)
+        );
+        assert_eq!(
+            build(VegetationParams::builder().vegetation_type(200)),
+            ParamsError::InvalidVegetationType(200)
+        );
+        assert_eq!(
+            build(VegetationParams::builder().density(0.0)),
+            ParamsError::InvalidDensity(0.0)
+        );
+        assert_eq!(
+            build(VegetationParams::builder().density(-4.0)),
+            ParamsError::InvalidDensity(-4.0)
+        );
+        assert!(matches!(
+            build(VegetationParams::builder().density(f64::NAN)),
+            ParamsError::InvalidDensity(_)
+        ));
+        assert_eq!(
+            build(VegetationParams::builder().variation(-0.1)),
+            ParamsError::InvalidVariation(-0.1)
+        );
+        assert_eq!(
+            build(VegetationParams::builder().type_value(0)),
+            ParamsError::InvalidTypeValue(0)
+        );
+
+        // Les paramètres enregistrés passent par la même validation
+        let (settings, _database) = open_temp_settings("params_builder");
+        let error = settings
+            .set_user_vegetation_params(
+                1,
+                VegetationParams {
+                    density: 0.0,
+                    ..Default::default()
+                },
+            )
+            .unwrap_err();
+        assert!(error.to_string().contains("Invalid density 0"), "{}", error);
+        assert!(
+            settings
+                .set_user_vegetation_params(1, VegetationParams::default())
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_output_template_drives_header_and_rows() {
+        let builtin = OutputTemplate::default();
+        assert_eq!(builtin.header(), OUTPUT_HEADER);
+        assert_eq!(builtin.columns.len(), builtin.row.split('\t').count());
+        assert!(builtin.validate().is_ok());
+        // Le modèle intégré reproduit l'ancienne ligne fixe
+        let row = builtin.render("1.00", "2.00", "0", "7", &Default::default());
+        let expected = format!(
+            "       1.00\t       2.00{}7\t",
+            "\t".repeat(9) + "20" + &"\t".repeat(4) + "20096" + &"\t".repeat(18) + "0\t"
+        );
+        assert_eq!(row, expected);
+        let fields: Vec<&str> = row.split('\t').collect();
+        let columns: Vec<&str> = OUTPUT_HEADER.split('\t').collect();
+        let value = |name: &str| fields[columns.iter().position(|c| *c == name).unwrap()];
+        assert_eq!(value("CODE_INSEE_SGA"), "20");
+        assert_eq!(value("NUMERO_INSEE"), "20096");
+        assert_eq!(value("z"), "0");
+        assert_eq!(value("type"), "7");
+
+        let template = OutputTemplate {
+            columns: vec!["X".to_string(), "Y".to_string(), "espece".to_string()],
+            row: "{x}\t{y}\tchene-{type}".to_string(),
+        };
+        let path = write_temp_file("output_template.csv", &format!("Geometry\n{SQUARE_WKT}\n"));
+        let options = ExportOptions {
+            template: Some(template.clone()),
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        export_csv_to_writer(
+            &path,
+            &VegetationParams::default(),
+            &options,
+            &mut output,
+            &VegetationProcessingState::new(),
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+        let output = String::from_utf8(output).expect("Export is not UTF-8");
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("X\tY\tespece"));
+        let type_field = format!("chene-{}", VegetationParams::default().type_value);
+        for line in lines {
+            let fields: Vec<&str> = line.split('\t').collect();
+            assert_eq!(fields.len(), 3);
+            assert!(fields[0].parse::<f64>().is_ok());
+            assert_eq!(fields[2], type_field);
+        }
+
+        let invalid = OutputTemplate {
+            columns: vec!["X".to_string()],
+            row: "{x}".to_string(),
+        };
+        assert!(invalid.validate().is_err());
+
+        let (settings, _db) = open_temp_settings("output_template");
+        assert_eq!(settings.get_output_template().unwrap(), None);
+        settings.set_output_template(Some(&template)).unwrap();
+        assert_eq!(settings.get_output_template().unwrap(), Some(template));
+        assert!(settings.set_output_template(Some(&invalid)).is_err());
+        settings.set_output_template(None).unwrap();
+        assert_eq!(settings.get_output_template().unwrap(), None);
+    }
+
+    #[test]
+    fn test_every_arm_of_concave_polygon_is_seeded() {
+        // U : deux bras verticaux reliés par une base étroite
+        let polygon = parse_wkt_polygons(
+            "POLYGON((0 0,200 0,200 200,180 200,180 20,20 20,20 200,0 200,0 0))",
+        )
+        .expect("Invalid WKT")
+        .remove(0);
+        let bounds = calculate_polygon_bounds(&polygon).expect("Polygon has no bounds");
+        let in_arms = |points: &[geo::Point<f64>]| {
+            [
+                points.iter().any(|p| p.x() < 20.0 && p.y() > 100.0),
+                points
+                    .iter()
+                    .any(|p| p.y() < 20.0 && (60.0..140.0).contains(&p.x())),
+                points.iter().any(|p| p.x() > 180.0 && p.y() > 100.0),
+            ]
+        };
+
+        for seed in 0..5 {
+            let points = SpatialDistributionSampler::with_seed(4.0, bounds, seed)
+                .generate_distribution(&polygon);
+            assert_eq!(in_arms(&points), [true; 3], "seed {}", seed);
+
+            let config = SamplerConfig {
+                seed: Some(seed),
+                initial_seeds: Some(32),
+                ..SamplerConfig::new(4.0)
+            };
+            let points = SpatialDistributionSampler::from_config(config, bounds)
+                .expect("Invalid sampler configuration")
+                .generate_distribution(&polygon);
+            assert_eq!(in_arms(&points), [true; 3], "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_compressed_export_round_trips() {
+        use std::io::Read;
+
+        let directory =
+            std::env::temp_dir().join(format!("vegepoly-{}-compressed", std::process::id()));
+        std::fs::create_dir_all(&directory).expect("Failed to create export directory");
+        let polygons =
+            parse_csv_file("tests/VEGETATION_ARBRES.csv", None, None).expect("Failed to parse CSV");
+        let params = VegetationParams {
+            seed: Some(9),
+            ..Default::default()
+        };
+        let options = ExportOptions {
+            compress: true,
+            write_summary_json: true,
+            ..Default::default()
+        };
+        let filename = export_to_directory(
+            &polygons[..2],
+            &params,
+            &[],
+            &options,
+            None,
+            &directory,
+            chrono::Local::now(),
+            &VegetationProcessingState::new(),
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+        assert!(filename.ends_with(".txt.gz"));
+        let summary = ExportSummary::filename(&filename);
+        assert_eq!(
+            summary,
+            format!("{}.summary.json", filename.strip_suffix(".txt.gz").unwrap())
+        );
+        assert!(directory.join(summary).exists());
+
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(
+            std::fs::File::open(directory.join(&filename)).expect("Failed to open export"),
+        )
+        .read_to_end(&mut decompressed)
+        .expect("Export is not valid gzip");
+
+        let mut expected = Vec::new();
+        export_to_writer(
+            &polygons[..2],
+            &params,
+            &[],
+            &ExportOptions::default(),
+            &mut expected,
+            &VegetationProcessingState::new(),
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+        assert!(!expected.is_empty());
+        assert_eq!(decompressed, expected);
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_near_zero_area_polygons_are_reported() {
+        let collinear = "POLYGON((0 0,100 0,200 0,0 0))";
+        // Sommet décalé d'un arrondi : aire résiduelle négligeable
+        let near_collinear = "POLYGON((0 0,100 0.000000000001,200 0,0 0))";
+        let repeated = "POLYGON((5 5,5 5,5 5,5 5))";
+        let path = write_temp_file(
+            "near_zero_area.csv",
+            &format!("Geometry\n{collinear}\n{near_collinear}\n{repeated}\n{SQUARE_WKT}\n"),
+        );
+        let polygons = parse_csv_file(&path, None, None).expect("Failed to parse CSV file");
+
+        for polygon in &polygons[..2] {
+            let err = fill_polygon(polygon.clone(), VegetationParams::default())
+                .expect_err("A degenerate polygon should not be filled");
+            assert!(
+                err.contains("Degenerate polygon (area ~0)"),
+                "Unexpected error: {}",
+                err
+            );
+        }
+        // Un polygone réduit à un point est rempli par ce point
+        let rows = fill_polygon(polygons[2].clone(), VegetationParams::default())
+            .expect("Failed to fill point polygon");
+        assert_eq!(parse_points(&rows), vec![(5.0, 5.0)]);
+
+        let state = VegetationProcessingState::new();
+        let total_points = export_csv_to_writer(
+            &path,
+            &VegetationParams::default(),
+            &ExportOptions::default(),
+            &mut Vec::<u8>::new(),
+            &state,
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+        assert!(total_points > 1);
+        let errors = state.errors.lock().unwrap().clone();
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors.iter().all(|e| e.contains("area ~0")), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_export_all_types_writes_one_file_per_type() {
+        let directory =
+            std::env::temp_dir().join(format!("vegepoly-{}-all-types", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).expect("Failed to create export directory");
+        let path = write_temp_file(
+            "all_types.csv",
+            &format!("Geometry\n{SQUARE_WKT}\nPOLYGON((0 0,1 0,2 0,0 0))\n{DONUT_WKT}\n"),
+        );
+        let params = [
+            VegetationParams {
+                vegetation_type: 1,
+                type_value: 11,
+                density: 20.0,
+                ..Default::default()
+            },
+            VegetationParams {
+                vegetation_type: 2,
+                type_value: 22,
+                density: 30.0,
+                ..Default::default()
+            },
+        ];
+
+        let state = VegetationProcessingState::new();
+        let reported = std::sync::Mutex::new(Vec::new());
+        let filenames = export_all_types_to_directory(
+            Vec::new(),
+            &params,
+            &ExportOptions::default(),
+            Some(&path),
+            &directory,
+            chrono::Local::now(),
+            &state,
+            &CallbackReporter(|info: &VegetationProgressInfo| {
+                reported.lock().unwrap().push((
+                    info.current_row,
+                    info.total_rows,
+                    info.is_finished,
+                ));
+            }),
+        )
+        .expect("Export failed");
+
+        assert_eq!(filenames.len(), 2);
+        let mut total_points = 0;
+        for (filename, param) in filenames.iter().zip(&params) {
+            assert!(
+                filename.starts_with(&format!("Export type {} ", param.vegetation_type)),
+                "{}",
+                filename
+            );
+            let contents =
+                std::fs::read_to_string(directory.join(filename)).expect("Missing export file");
+            let rows: Vec<&str> = contents.lines().skip(1).collect();
+            assert!(!rows.is_empty());
+            let type_column = OUTPUT_HEADER.split('\t').position(|c| c == "type").unwrap();
+            assert!(rows.iter().all(|row| {
+                row.split('\t').nth(type_column) == Some(&param.type_value.to_string()[..])
+            }));
+            total_points += rows.len();
+        }
+
+        // La progression couvre les deux types : le second part des lignes du premier
+        let reported = reported.lock().unwrap().clone();
+        assert!(
+            reported
+                .iter()
+                .all(|&(row, total, _)| total == 6 && row <= 6)
+        );
+        assert!(reported.contains(&(3, 6, false)));
+        assert!(
+            reported[..reported.len() - 1]
+                .iter()
+                .all(|&(_, _, finished)| !finished)
+        );
+        assert_eq!(reported.last(), Some(&(6, 6, true)));
+        assert_eq!(*state.created_items.lock().unwrap(), total_points);
+
+        let errors = state.errors.lock().unwrap().clone();
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors[0].starts_with("Type 1: "), "{}", errors[0]);
+        assert!(errors[1].starts_with("Type 2: "), "{}", errors[1]);
+
+        assert!(
+            export_all_types_to_directory(
+                Vec::new(),
+                &[],
+                &ExportOptions::default(),
+                Some(&path),
+                &directory,
+                chrono::Local::now(),
+                &state,
+                &CallbackReporter(|_: &VegetationProgressInfo| {}),
+            )
+            .is_err()
+        );
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_settings_saved_with_active_profile_stay_in_profile() {
+        let (settings, _path) = open_temp_settings("profile_writes");
+        let profile_dir =
+            std::env::temp_dir().join(format!("vegepoly-{}-profile-writes", std::process::id()));
+        std::fs::create_dir_all(&profile_dir).expect("Failed to create export directory");
+        let general_path = settings
+            .get_export_path()
+            .expect("Failed to read export path");
+        let general_density = settings
+            .get_vegetation_params(2)
+            .expect("Failed to read params")
+            .expect("Missing default params")
+            .density;
+        settings
+            .create_profile(
+                "est",
+                &SettingsProfile {
+                    export_path: std::env::temp_dir(),
+                    vegetation_params: Vec::new(),
+                },
+            )
+            .expect("Failed to create profile");
+        settings
+            .switch_profile(Some("est"))
+            .expect("Failed to switch profile");
+
+        let params = VegetationParams {
+            vegetation_type: 2,
+            density: 17.0,
+            type_value: 20,
+            ..Default::default()
+        };
+        settings
+            .set_user_vegetation_params(2, params)
+            .expect("Failed to save params");
+        settings
+            .set_export_path(profile_dir.clone())
+            .expect("Failed to save export path");
+
+        let saved = settings
+            .get_vegetation_params(2)
+            .expect("Failed to read params")
+            .expect("Missing params");
+        assert_eq!(saved.density, 17.0);
+        assert_eq!(
+            settings
+                .get_user_vegetation_params(2)
+                .expect("Failed to read params")
+                .map(|params| params.density),
+            Some(17.0)
+        );
+        assert_eq!(
+            settings
+                .get_export_path()
+                .expect("Failed to read export path"),
+            profile_dir
+        );
+        let profile = settings.get_profile("est").expect("Missing profile");
+        assert_eq!(profile.vegetation_params.len(), 1);
+        assert_eq!(profile.export_path, profile_dir);
+
+        // Les paramètres généraux ne sont pas modifiés
+        settings
+            .switch_profile(None)
+            .expect("Failed to switch profile");
+        assert_eq!(
+            settings
+                .get_vegetation_params(2)
+                .expect("Failed to read params")
+                .expect("Missing default params")
+                .density,
+            general_density
+        );
+        assert_eq!(
+            settings
+                .get_export_path()
+                .expect("Failed to read export path"),
+            general_path
+        );
+
+        settings
+            .switch_profile(Some("est"))
+            .expect("Failed to switch profile");
+        settings
+            .remove_user_vegetation_params(2)
+            .expect("Failed to remove params");
+        assert!(
+            settings
+                .get_profile("est")
+                .expect("Missing profile")
+                .vegetation_params
+                .is_empty()
+        );
+        let _ = std::fs::remove_dir_all(&profile_dir);
+    }
+
+    #[test]
+    fn test_estimates_use_sampling_projection() {
+        let wgs84 = geo::Polygon::new(
+            geo::LineString::from(vec![
+                (2.294481, 48.858370),
+                (2.304481, 48.858370),
+                (2.304481, 48.868370),
+                (2.294481, 48.868370),
+            ]),
+            vec![],
+        );
+        let projected = reproject_polygons(std::slice::from_ref(&wgs84), 4326, 2154)
+            .expect("Failed to reproject")
+            .remove(0);
+        let path = write_temp_file(
+            "estimate_wgs84.csv",
+            "Geometry\nPOLYGON((2.294481 48.85837,2.304481 48.85837,2.304481 48.86837,2.294481 48.86837,2.294481 48.85837))\n",
+        );
+        let metric_path = write_temp_file(
+            "estimate_lambert93.csv",
+            &format!(
+                "Geometry\nPOLYGON(({}))\n",
+                projected
+                    .exterior()
+                    .0
+                    .iter()
+                    .map(|c| format!("{} {}", c.x, c.y))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        );
+
+        // Distance minimale de 20 m : l'aire doit être mesurée en mètres
+        let params = VegetationParams {
+            density: 20.0,
+            source_epsg: Some(4326),
+            sampling_epsg: Some(2154),
+            ..Default::default()
+        };
+        let metric = VegetationParams {
+            source_epsg: None,
+            sampling_epsg: None,
+            ..params.clone()
+        };
+        let estimate =
+            estimate_total_points(&path, params.clone(), None, None).expect("Failed to estimate");
+        assert!(estimate > 500, "{}", estimate);
+        assert_eq!(
+            estimate,
+            estimate_total_points(&metric_path, metric.clone(), None, None)
+                .expect("Failed to estimate")
+        );
+
+        let target = |params: &VegetationParams| VegetationParams {
+            density: 400.0,
+            density_mode: DensityMode::TargetCount,
+            ..params.clone()
+        };
+        let resolved = resolve_effective_min_distance(wgs84, target(&params))
+            .expect("Failed to resolve min distance");
+        let expected = resolve_effective_min_distance(projected, target(&metric))
+            .expect("Failed to resolve min distance");
+        assert!(resolved > 1.0, "{}", resolved);
+        assert!(
+            (resolved - expected).abs() < 1e-6,
+            "{} {}",
+            resolved,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_oversized_margin_is_not_an_export_error() {
+        let directory =
+            std::env::temp_dir().join(format!("vegepoly-{}-oversized-margin", std::process::id()));
+        std::fs::create_dir_all(&directory).expect("Failed to create export directory");
+        let polygons = parse_csv_file(
+            &write_temp_file("oversized_margin.csv", &format!("Geometry\n{SQUARE_WKT}\n")),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        // Une zone d'exclusion couvrant toute la parcelle
+        let zone = geo::Polygon::new(
+            geo::LineString::from(vec![
+                (-10.0, -10.0),
+                (210.0, -10.0),
+                (210.0, 210.0),
+                (-10.0, 210.0),
+            ]),
+            vec![],
+        );
+        let swallowed = VegetationParams {
+            density: 6.0,
+            edge_margin: 150.0,
+            seed: Some(13),
+            ..Default::default()
+        };
+        let excluded = VegetationParams {
+            edge_margin: 5.0,
+            exclusion_zones: vec![zone],
+            ..swallowed.clone()
+        };
+
+        // Seul un retrait plus large que le rayon inscrit justifie un résultat vide
+        assert_eq!(
+            fill_polygon(polygons[0].clone(), swallowed.clone()).expect("Margin is not an error"),
+            Vec::<String>::new()
+        );
+        assert!(fill_polygon(polygons[0].clone(), excluded.clone()).is_err());
+
+        for (params, expected_errors) in [(swallowed, 0), (excluded, 1)] {
+            let state = VegetationProcessingState::new();
+            let filename = export_to_directory(
+                &polygons,
+                &params,
+                &[],
+                &ExportOptions::default(),
+                None,
+                &directory,
+                chrono::Local::now(),
+                &state,
+                &CallbackReporter(|_: &VegetationProgressInfo| {}),
+            )
+            .expect("Export failed");
+            let rows = std::fs::read_to_string(directory.join(&filename))
+                .expect("Failed to read export")
+                .lines()
+                .skip(1)
+                .count();
+            assert_eq!(rows, 0);
+            assert_eq!(state.errors.lock().unwrap().len(), expected_errors);
+        }
+        std::fs::remove_dir_all(&directory).expect("Failed to clean export directory");
+    }
+
+    #[test]
+    fn test_parallel_export_streams_large_polygons() {
+        let polygons = parse_csv_file(
+            &write_temp_file(
+                "large_polygon.csv",
+                &format!(
+                    "Geometry\nPOLYGON((0 0,2000 0,2000 2000,0 2000,0 0))\n{SQUARE_WKT}\n{DONUT_WKT}\n"
+                ),
+            ),
+            None,
+            None,
+        )
+        .expect("Failed to parse CSV file");
+        // Plus de cent mille points estimés pour le grand carré, plafonnés pour le test
+        let params = VegetationParams {
+            density: 4.0,
+            max_points: Some(3000),
+            seed: Some(17),
+            ..Default::default()
+        };
+        let export = |max_threads: Option<usize>| {
+            let mut output = Vec::<u8>::new();
+            let total = export_to_writer(
+                &polygons,
+                &params,
+                &[],
+                &ExportOptions {
+                    max_threads,
+                    ..Default::default()
+                },
+                &mut output,
+                &VegetationProcessingState::new(),
+                &CallbackReporter(|_: &VegetationProgressInfo| {}),
+            )
+            .expect("Export failed");
+            (output, total)
+        };
+
+        let serial = export(Some(1));
+        let parallel = export(Some(4));
+        assert!(serial.1 > 3000);
+        assert_eq!(serial, parallel);
+    }
+
+    #[test]
+    fn test_source_lines_with_crlf_line_endings() {
+        let path = write_temp_file(
+            "source_lines_crlf.csv",
+            &format!("Geometry\tnom\r\n{SQUARE_WKT}\ta\r\n\r\n{DONUT_WKT}\tb\r\n{SQUARE_WKT}\tc"),
+        );
+
+        let lines: Vec<usize> = parse_csv_file_with_lines(&path, None, None)
+            .expect("Failed to parse CSV file")
+            .into_iter()
+            .map(|(line, _)| line)
+            .collect();
+        assert_eq!(lines, vec![2, 4, 5]);
+    }
+
+    #[test]
+    fn test_vegetation_types_and_labels_with_active_profile() {
+        let (settings, _database) = open_temp_settings("profile_types");
+        settings
+            .create_profile(
+                "ouest",
+                &SettingsProfile {
+                    export_path: std::env::temp_dir(),
+                    vegetation_params: Vec::new(),
+                },
+            )
+            .expect("Failed to create profile");
+        settings
+            .switch_profile(Some("ouest"))
+            .expect("Failed to switch profile");
+
+        for (vegetation_type, density) in [(4, 12.0), (5, 8.0)] {
+            settings
+                .set_user_vegetation_params(
+                    vegetation_type,
+                    VegetationParams {
+                        vegetation_type: vegetation_type as u8,
+                        density,
+                        type_value: 40,
+                        ..Default::default()
+                    },
+                )
+                .expect("Failed to save custom type");
+        }
+        settings
+            .set_vegetation_type_label(4, "Haies")
+            .expect("Failed to set label");
+        assert_eq!(
+            settings.get_vegetation_type_label(4).unwrap().as_deref(),
+            Some("Haies")
+        );
+
+        let types = settings
+            .list_vegetation_types()
+            .expect("Failed to list vegetation types");
+        assert_eq!(
+            types.iter().map(|t| t.vegetation_type).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5]
+        );
+        assert_eq!(types[3].label.as_deref(), Some("Haies"));
+        assert_eq!(types[4].density, 8.0);
+
+        // La réinitialisation vide le profil actif, pas les paramètres généraux
+        settings
+            .reset_user_vegetation_params()
+            .expect("Failed to reset params");
+        assert!(
+            settings
+                .get_profile("ouest")
+                .expect("Missing profile")
+                .vegetation_params
+                .is_empty()
+        );
+        assert_eq!(
+            settings.get_available_vegetation_types().unwrap(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(
+            settings.get_vegetation_type_label(4).unwrap().as_deref(),
+            Some("Haies")
+        );
+    }
+
+    #[test]
+    fn test_invalid_wkt_row_is_reported_and_skipped() {
+        let path = write_temp_file(
+            "invalid_wkt_row.csv",
+            &format!("Geometry\n{SQUARE_WKT}\nPOLYGON((0 0,10 0\n{DONUT_WKT}\n"),
+        );
+        let params = VegetationParams {
+            density: 50.0,
+            seed: Some(3),
+            ..Default::default()
+        };
+
+        let mut outputs = Vec::new();
+        for max_threads in [Some(1), Some(4)] {
+            let state = VegetationProcessingState::new();
+            let mut output = Vec::<u8>::new();
+            let count = export_csv_to_writer(
+                &path,
+                &params,
+                &ExportOptions {
+                    max_threads,
+                    ..Default::default()
+                },
+                &mut output,
+                &state,
+                &CallbackReporter(|_: &VegetationProgressInfo| {}),
+            )
+            .expect("Export failed");
+
+            let errors = state.errors.lock().unwrap().clone();
+            assert_eq!(errors.len(), 1, "{:?}", errors);
+            assert!(
+                errors[0].contains("Invalid WKT format on line 3"),
+                "{}",
+                errors[0]
+            );
+            assert_eq!(*state.processed_rows.lock().unwrap(), 3);
+            assert!(count > 0);
+            outputs.push((count, output));
+        }
+        assert_eq!(outputs[0], outputs[1]);
+
+        // Les polygones valides qui suivent la ligne invalide sont exportés
+        let valid = write_temp_file(
+            "invalid_wkt_valid_rows.csv",
+            &format!("Geometry\n{SQUARE_WKT}\n{DONUT_WKT}\n"),
+        );
+        let mut output = Vec::<u8>::new();
+        let count = export_csv_to_writer(
+            &valid,
+            &params,
+            &ExportOptions::default(),
+            &mut output,
+            &VegetationProcessingState::new(),
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .expect("Export failed");
+        assert_eq!(outputs[0], (count, output));
+    }
+
+    #[test]
+    fn test_cancel_is_kept_across_row_count_and_types() {
+        let path = write_temp_file(
+            "cancel_kept.csv",
+            &format!("Geometry\n{SQUARE_WKT}\n{DONUT_WKT}\n"),
+        );
+        let params = VegetationParams {
+            density: 20.0,
+            ..Default::default()
+        };
+
+        // Une annulation demandée pendant le comptage des lignes n'est pas effacée
+        let state = VegetationProcessingState::new();
+        state.request_cancel();
+        let error = export_csv_to_writer(
+            &path,
+            &params,
+            &ExportOptions::default(),
+            &mut Vec::<u8>::new(),
+            &state,
+            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+        )
+        .unwrap_err();
+        assert!(error.contains("cancelled"), "{}", error);
+
+        // Une annulation demandée à la fin du premier type arrête le second
+        let directory =
+            std::env::temp_dir().join(format!("vegepoly-{}-cancel-kept", std::process::id()));
+        std::fs::create_dir_all(&directory).expect("Failed to create export directory");
+        let state = VegetationProcessingState::new();
+        let error = export_all_types_to_directory(
+            Vec::new(),
+            &[1, 2].map(|vegetation_type| VegetationParams {
+                vegetation_type,
+                ..params.clone()
+            }),
+            &ExportOptions {
+                max_threads: Some(1),
+                ..Default::default()
+            },
+            Some(&path),
+            &directory,
+            chrono::Local::now(),
+            &state,
+            &CallbackReporter(|info: &VegetationProgressInfo| {
+                if info.current_row == 2 {
+                    state.request_cancel();
+                }
+            }),
+        )
+        .unwrap_err();
+        assert!(error.contains("cancelled"), "{}", error);
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_concurrent_same_second_exports_get_distinct_files() {
+        let directory =
+            std::env::temp_dir().join(format!("vegepoly-{}-concurrent", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).expect("Failed to create export directory");
+        let polygons = parse_wkt_polygons(SQUARE_WKT).expect("Failed to parse WKT");
+        let options = ExportOptions::default();
+        let timestamp = chrono::Local::now();
+
+        let mut filenames: Vec<String> = std::thread::scope(|scope| {
+            let exports: Vec<_> = (0..8)
+                .map(|density| {
+                    let (polygons, options, directory) = (&polygons, &options, &directory);
+                    scope.spawn(move || {
+                        export_to_directory(
+                            polygons,
+                            &VegetationParams {
+                                density: 10.0 + density as f64,
+                                ..Default::default()
+                            },
+                            &[],
+                            options,
+                            None,
+                            directory,
+                            timestamp,
+                            &VegetationProcessingState::new(),
+                            &CallbackReporter(|_: &VegetationProgressInfo| {}),
+                        )
+                        .expect("Export failed")
+                    })
+                })
+                .collect();
+            exports
+                .into_iter()
+                .map(|export| export.join().unwrap())
+                .collect()
+        });
+
+        filenames.sort();
+        filenames.dedup();
+        assert_eq!(filenames.len(), 8);
+        let files = std::fs::read_dir(&directory)
+            .expect("Failed to list export directory")
+            .count();
+        assert_eq!(files, 8);
+        std::fs::remove_dir_all(&directory).expect("Failed to clean export directory");
+    }
 }
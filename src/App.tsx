@@ -6,7 +6,7 @@ import { documentDir } from "@tauri-apps/api/path";
 import "./App.css";
 
 // Types
-import { VegetationParams, ProgressInfo } from "./types";
+import { VegetationParams, ProgressInfo, OutputFormat } from "./types";
 
 // Components
 import Header from "./components/Header";
@@ -52,6 +52,8 @@ function App() {
     useState<boolean>(false);
   const [savedParams, setSavedParams] = useState<VegetationParams | null>(null);
   const [hasChanges, setHasChanges] = useState<boolean>(false);
+  const [outputFormat, setOutputFormat] = useState<OutputFormat>("SdisTxt");
+  const [compressOutput, setCompressOutput] = useState(false);
 
   useEffect(() => {
     const loadParams = async () => {
@@ -126,8 +128,8 @@ function App() {
         directory: false,
         filters: [
           {
-            name: "Fichiers CSV",
-            extensions: ["csv"],
+            name: "Fichiers de géométries",
+            extensions: ["csv", "txt", "geojson", "json", "wkt"],
           },
         ],
         defaultPath: await documentDir(),
@@ -140,13 +142,19 @@ function App() {
 
         setIsLoadingFile(true);
         try {
-          const polygons = await invoke<any[]>("parse_csv_file", {
+          const { polygons, skipped_features } = await invoke<{
+            polygons: any[];
+            skipped_features: [number, string][];
+          }>("parse_geometry_file", {
             filePath: selected,
           });
           setPolygonCount(polygons.length);
-          console.log(`Loaded ${polygons.length} polygons from CSV`);
+          console.log(`Loaded ${polygons.length} polygons from ${parts[parts.length - 1]}`);
+          for (const [feature, reason] of skipped_features) {
+            console.warn(`Skipped GeoJSON feature ${feature}: ${reason}`);
+          }
         } catch (error) {
-          console.error("Erreur lors du parsing du fichier CSV:", error);
+          console.error("Erreur lors du parsing du fichier:", error);
           alert(`Erreur lors du chargement du fichier: ${error}`);
           setPolygonCount(0);
           setSelectedFile(null);
@@ -215,16 +223,23 @@ function App() {
     });
 
     try {
-      const polygons = await invoke<any[]>("parse_csv_file", {
-        filePath: selectedFile,
-      });
+      const coordinatePrecision = await invoke<number>(
+        "get_coordinate_precision"
+      );
 
+      // Le fichier est relu côté Rust pour que les erreurs citent la ligne d'origine
       const output = await invoke<string>("export_results", {
-        data: polygons,
+        data: [],
         param: {
           ...params,
           vegetation_type: vegetationType,
         },
+        options: {
+          format: outputFormat,
+          coordinate_precision: coordinatePrecision,
+          compress: compressOutput,
+        },
+        sourceFile: selectedFile,
       });
 
       setResult(output);
@@ -283,6 +298,10 @@ function App() {
             fileName={fileName}
             isProcessing={isProcessing || isLoadingFile}
             selectedFile={selectedFile}
+            outputFormat={outputFormat}
+            setOutputFormat={setOutputFormat}
+            compressOutput={compressOutput}
+            setCompressOutput={setCompressOutput}
           />
         </div>
 
@@ -0,0 +1,22 @@
+import { invoke } from "@tauri-apps/api/core";
+import { UpdateStatus } from "../types";
+
+export async function checkForUpdatesNow(): Promise<UpdateStatus | null> {
+  try {
+    return await invoke<UpdateStatus>("check_for_updates_now");
+  } catch (error) {
+    console.error("Erreur lors de la recherche de mise à jour:", error);
+    return null;
+  }
+}
+
+export async function installUpdate(): Promise<boolean> {
+  try {
+    // L'application redémarre une fois la mise à jour installée
+    await invoke("install_update");
+    return true;
+  } catch (error) {
+    console.error("Erreur lors de l'installation de la mise à jour:", error);
+    return false;
+  }
+}
@@ -1,6 +1,7 @@
 import { faFileImport, faPlay } from "@fortawesome/free-solid-svg-icons";
 import Card from "./shared/Card";
 import Button from "./shared/Button";
+import { OutputFormat } from "../types";
 
 interface FileOperationsProps {
   selectFile: () => void;
@@ -8,6 +9,10 @@ interface FileOperationsProps {
   fileName: string | null;
   isProcessing: boolean;
   selectedFile: string | null;
+  outputFormat: OutputFormat;
+  setOutputFormat: (format: OutputFormat) => void;
+  compressOutput: boolean;
+  setCompressOutput: (compress: boolean) => void;
 }
 
 const FileOperations = ({
@@ -16,6 +21,10 @@ const FileOperations = ({
   fileName,
   isProcessing,
   selectedFile,
+  outputFormat,
+  setOutputFormat,
+  compressOutput,
+  setCompressOutput,
 }: FileOperationsProps) => {
   return (
     <Card title="Opérations de Fichier" className="flex-shrink-0">
@@ -33,6 +42,33 @@ const FileOperations = ({
         )}
       </div>
 
+      <div className="flex items-center mb-3 gap-2">
+        <label htmlFor="output-format">Format de sortie :</label>
+        <select
+          id="output-format"
+          className="border rounded-md px-2 py-1"
+          value={outputFormat}
+          disabled={isProcessing}
+          onChange={(e) => setOutputFormat(e.target.value as OutputFormat)}
+        >
+          <option value="SdisTxt">Texte SDIS (.txt)</option>
+          <option value="Csv">CSV (.csv)</option>
+          <option value="GeoJson">GeoJSON (.geojson)</option>
+          <option value="WktMultipoint">WKT MULTIPOINT (.csv)</option>
+        </select>
+      </div>
+
+      <div className="flex items-center mb-3 gap-2">
+        <input
+          id="compress-output"
+          type="checkbox"
+          checked={compressOutput}
+          disabled={isProcessing}
+          onChange={(e) => setCompressOutput(e.target.checked)}
+        />
+        <label htmlFor="compress-output">Compresser l'export (.gz)</label>
+      </div>
+
       <Button
         onClick={executeProcessing}
         disabled={isProcessing || !selectedFile}
@@ -58,6 +58,11 @@ const ProgressResults = ({
                   {progress.created_items.toLocaleString()} points générés
                 </div>
               )}
+              {progress.points_per_second != null && (
+                <div id="medium-visibility">
+                  {Math.round(progress.points_per_second).toLocaleString()} pts/s
+                </div>
+              )}
             </div>
 
             <div className="space-y-1 text-right">
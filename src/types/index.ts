@@ -4,6 +4,14 @@ export interface VegetationParams {
   type_value: number;
 }
 
+export type OutputFormat = "SdisTxt" | "Csv" | "GeoJson" | "WktMultipoint";
+
+export interface UpdateStatus {
+  available: boolean;
+  current: string;
+  latest: string;
+}
+
 export interface ProgressInfo {
   current_row: number;
   total_rows: number;
@@ -12,5 +20,7 @@ export interface ProgressInfo {
   percentage: number;
   elapsed_seconds?: number;
   estimated_remaining_seconds?: number;
+  rows_per_second?: number | null;
+  points_per_second?: number | null;
   is_finished: boolean;
 }
\ No newline at end of file